@@ -0,0 +1,90 @@
+use std::borrow::Cow;
+
+use rust_decimal::Decimal;
+
+use super::currency::Currency;
+
+/// The reserve every account has to hold in XRP.
+pub const DEFAULT_BASE_RESERVE: Decimal = Decimal::from_parts(1, 0, 0, false, 0);
+/// The additional reserve in XRP for every object the account owns (trust lines, offers, ...).
+pub const DEFAULT_OWNER_RESERVE: Decimal = Decimal::from_parts(2, 0, 0, false, 1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustLine<'a> {
+    pub currency: Currency<'a>,
+    pub balance: Decimal,
+    pub limit: Decimal,
+}
+
+/// The funds of the trading account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountState<'a> {
+    pub address: Cow<'a, str>,
+    /// The XRP balance including the reserve.
+    pub xrp_balance: Decimal,
+    pub owner_count: u32,
+    pub base_reserve: Decimal,
+    pub owner_reserve: Decimal,
+    pub trust_lines: Vec<TrustLine<'a>>,
+}
+
+impl<'a> AccountState<'a> {
+    pub fn new(address: Cow<'a, str>, xrp_balance: Decimal) -> Self {
+        Self {
+            address,
+            xrp_balance,
+            owner_count: 0,
+            base_reserve: DEFAULT_BASE_RESERVE,
+            owner_reserve: DEFAULT_OWNER_RESERVE,
+            trust_lines: Vec::new(),
+        }
+    }
+
+    /// Adds the trust line and counts it towards the owner reserve.
+    pub fn add_trust_line(&mut self, trust_line: TrustLine<'a>) {
+        self.trust_lines.push(trust_line);
+        self.owner_count += 1;
+    }
+
+    pub fn get_trust_line(&self, currency: &Currency<'_>) -> Option<&TrustLine<'a>> {
+        self.trust_lines
+            .iter()
+            .find(|trust_line| trust_line.currency.is_same_asset(currency))
+    }
+
+    /// Returns the XRP the account has to keep.
+    pub fn get_reserve(&self) -> Decimal {
+        self.base_reserve + self.owner_reserve * Decimal::from(self.owner_count)
+    }
+
+    /// Returns how much of the currency the account can spend or `None` if there is no limit
+    /// because the account issues it. XRP can only be spent above the reserve.
+    pub fn get_spendable(&self, currency: &Currency<'_>) -> Option<Decimal> {
+        if currency.is_xrp() {
+            return Some((self.xrp_balance - self.get_reserve()).max(Decimal::ZERO));
+        }
+        if currency.issuer == self.address {
+            return None;
+        }
+
+        Some(
+            self.get_trust_line(currency)
+                .map(|trust_line| trust_line.balance.max(Decimal::ZERO))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns how much of the currency the account can receive or `None` if there is no limit.
+    /// Tokens can only be received up to the limit of their trust line.
+    pub fn get_receivable(&self, currency: &Currency<'_>) -> Option<Decimal> {
+        if currency.is_xrp() || currency.issuer == self.address {
+            return None;
+        }
+
+        Some(
+            self.get_trust_line(currency)
+                .map(|trust_line| (trust_line.limit - trust_line.balance).max(Decimal::ZERO))
+                .unwrap_or_default(),
+        )
+    }
+}
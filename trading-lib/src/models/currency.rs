@@ -2,11 +2,12 @@ use std::borrow::Cow;
 
 extern crate xrpl;
 
+use serde::{Deserialize, Serialize};
 use xrpl::models::amount::{Amount, IssuedCurrencyAmount, XRPAmount};
 #[cfg(feature = "xrpl")]
-use xrpl::models::currency::Currency as XRPLCurrency;
+use xrpl::models::{currency::Currency as XRPLCurrency, PathStep};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Currency<'a> {
     pub currency_code: Cow<'a, str>,
     pub issuer: Cow<'a, str>,
@@ -22,6 +23,15 @@ impl<'a> Currency<'a> {
                 .into(),
         }
     }
+
+    /// Returns the path step converting into this currency through its order book.
+    pub fn get_path_step(&self) -> PathStep<'a> {
+        PathStep {
+            currency: Some(self.currency_code.clone()),
+            issuer: (!self.is_xrp()).then(|| self.issuer.clone()),
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(feature = "xrpl")]
@@ -43,7 +53,33 @@ impl<'a> Currency<'a> {
 }
 
 impl Currency<'_> {
+    pub fn xrp() -> Self {
+        Self {
+            currency_code: "XRP".into(),
+            issuer: "".into(),
+            transfer_fee: 0.0,
+        }
+    }
+
+    pub fn is_xrp(&self) -> bool {
+        self.currency_code == "XRP"
+    }
+
     pub fn is_same_currency(&self, other: &Self) -> bool {
         self.currency_code == other.currency_code
     }
+
+    /// Returns true if both currencies have the same currency code and the same issuer.
+    pub fn is_same_asset(&self, other: &Currency<'_>) -> bool {
+        self.currency_code == other.currency_code && self.issuer == other.issuer
+    }
+
+    /// Returns `<currency code>.<issuer>` or only the currency code if there is no issuer.
+    pub fn get_label(&self) -> String {
+        if self.issuer.is_empty() {
+            self.currency_code.to_string()
+        } else {
+            format!("{}.{}", self.currency_code, self.issuer)
+        }
+    }
 }
@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MetadataException {
+    #[error("The transaction has no metadata.")]
+    MissingMetadata,
+    #[error("Missing field {0}.")]
+    MissingField(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+}
@@ -0,0 +1,260 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{currency::Currency, exceptions::MetadataException};
+
+/// Models can't depend on the feature gated trading types, so the constant is repeated here.
+const DROPS_PER_XRP: u64 = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyAmount<'a> {
+    pub currency: Currency<'a>,
+    pub quantity: Decimal,
+}
+
+/// The part of someone else's offer consumed by a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfferFill<'a> {
+    pub owner: String,
+    /// The amount the offer gave to the taker.
+    pub taker_gets: CurrencyAmount<'a>,
+    /// The amount the offer received from the taker.
+    pub taker_pays: CurrencyAmount<'a>,
+}
+
+/// The effects of a validated transaction on an account, parsed from the transaction and its
+/// metadata as returned by the `tx` method or a transaction stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionOutcome<'a> {
+    pub hash: String,
+    /// The result code, e.g. `tesSUCCESS`.
+    pub transaction_result: String,
+    /// The fee paid if the account sent the transaction.
+    pub fee_drops: u64,
+    /// The balance changes of the account without the fee.
+    pub balance_changes: Vec<CurrencyAmount<'a>>,
+    pub offer_fills: Vec<OfferFill<'a>>,
+    /// The amount a payment actually delivered.
+    pub delivered_amount: Option<CurrencyAmount<'a>>,
+}
+
+impl TransactionOutcome<'static> {
+    pub fn from_json(transaction: &Value, account: &str) -> Result<Self> {
+        // The fields of the transaction are either at the top level or nested.
+        let fields = transaction
+            .get("tx_json")
+            .or_else(|| transaction.get("tx"))
+            .unwrap_or(transaction);
+        let Some(metadata) = transaction
+            .get("meta")
+            .or_else(|| transaction.get("metaData"))
+        else {
+            bail!(MetadataException::MissingMetadata);
+        };
+        let hash = get_str(transaction, "hash")
+            .or_else(|_| get_str(fields, "hash"))?
+            .to_string();
+        let fee_drops = if get_str(fields, "Account")? == account {
+            parse_decimal(get_str(fields, "Fee")?)?
+                .to_u64()
+                .unwrap_or_default()
+        } else {
+            0
+        };
+        let mut outcome = Self {
+            hash,
+            transaction_result: get_str(metadata, "TransactionResult")?.to_string(),
+            fee_drops,
+            balance_changes: Vec::new(),
+            offer_fills: Vec::new(),
+            delivered_amount: metadata
+                .get("delivered_amount")
+                .map(parse_amount)
+                .transpose()?,
+        };
+        let affected_nodes = metadata
+            .get("AffectedNodes")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for affected_node in affected_nodes {
+            outcome.add_affected_node(affected_node, account)?;
+        }
+        // The fee isn't part of the trade.
+        if outcome.fee_drops > 0 {
+            outcome.add_balance_change(
+                Currency::xrp(),
+                Decimal::from(outcome.fee_drops) / Decimal::from(DROPS_PER_XRP),
+            );
+        }
+        outcome
+            .balance_changes
+            .retain(|balance_change| !balance_change.quantity.is_zero());
+
+        Ok(outcome)
+    }
+
+    fn add_affected_node(&mut self, affected_node: &Value, account: &str) -> Result<()> {
+        let Some((node_type, node)) = ["CreatedNode", "ModifiedNode", "DeletedNode"]
+            .into_iter()
+            .find_map(|node_type| Some((node_type, affected_node.get(node_type)?)))
+        else {
+            return Ok(());
+        };
+        let final_fields = node
+            .get("FinalFields")
+            .or_else(|| node.get("NewFields"))
+            .unwrap_or(&Value::Null);
+        let previous_fields = node.get("PreviousFields");
+        match get_str(node, "LedgerEntryType")? {
+            "AccountRoot"
+                if final_fields.get("Account").and_then(Value::as_str) == Some(account) =>
+            {
+                let final_balance = parse_drops(final_fields, "Balance")?;
+                let previous_balance = match (node_type, previous_fields) {
+                    ("CreatedNode", _) => Decimal::ZERO,
+                    (_, Some(previous_fields)) if previous_fields.get("Balance").is_some() => {
+                        parse_drops(previous_fields, "Balance")?
+                    }
+                    _ => final_balance,
+                };
+                self.add_balance_change(Currency::xrp(), final_balance - previous_balance);
+            }
+            "RippleState" => {
+                let low_account = get_str(get_field(final_fields, "LowLimit")?, "issuer")?;
+                let high_account = get_str(get_field(final_fields, "HighLimit")?, "issuer")?;
+                // The balance is from the perspective of the low account.
+                let (sign, issuer) = if low_account == account {
+                    (Decimal::ONE, high_account)
+                } else if high_account == account {
+                    (-Decimal::ONE, low_account)
+                } else {
+                    return Ok(());
+                };
+                let final_balance = parse_token_balance(final_fields)?;
+                let previous_balance = match (node_type, previous_fields) {
+                    ("CreatedNode", _) => Decimal::ZERO,
+                    (_, Some(previous_fields)) if previous_fields.get("Balance").is_some() => {
+                        parse_token_balance(previous_fields)?
+                    }
+                    _ => final_balance,
+                };
+                let currency = Currency {
+                    currency_code: Cow::Owned(
+                        get_str(get_field(final_fields, "Balance")?, "currency")?.to_string(),
+                    ),
+                    issuer: Cow::Owned(issuer.to_string()),
+                    transfer_fee: 0.0,
+                };
+                self.add_balance_change(currency, sign * (final_balance - previous_balance));
+            }
+            "Offer" if node_type != "CreatedNode" => {
+                // Offers without previous amounts were removed without being consumed.
+                let Some(previous_fields) = previous_fields else {
+                    return Ok(());
+                };
+                if previous_fields.get("TakerGets").is_none() {
+                    return Ok(());
+                }
+                let get_consumed = |field: &str| -> Result<CurrencyAmount<'static>> {
+                    let previous = parse_amount(get_field(previous_fields, field)?)?;
+                    let last = parse_amount(get_field(final_fields, field)?)?;
+
+                    Ok(CurrencyAmount {
+                        quantity: previous.quantity - last.quantity,
+                        currency: previous.currency,
+                    })
+                };
+                self.offer_fills.push(OfferFill {
+                    owner: get_str(final_fields, "Account")?.to_string(),
+                    taker_gets: get_consumed("TakerGets")?,
+                    taker_pays: get_consumed("TakerPays")?,
+                });
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn add_balance_change(&mut self, currency: Currency<'static>, change: Decimal) {
+        match self
+            .balance_changes
+            .iter_mut()
+            .find(|balance_change| balance_change.currency.is_same_asset(&currency))
+        {
+            Some(balance_change) => balance_change.quantity += change,
+            None => self.balance_changes.push(CurrencyAmount {
+                currency,
+                quantity: change,
+            }),
+        }
+    }
+}
+
+impl TransactionOutcome<'_> {
+    pub fn is_success(&self) -> bool {
+        self.transaction_result == "tesSUCCESS"
+    }
+
+    /// Returns the balance change of the currency without the fee.
+    pub fn get_balance_change(&self, currency: &Currency<'_>) -> Decimal {
+        self.balance_changes
+            .iter()
+            .filter(|balance_change| balance_change.currency.is_same_asset(currency))
+            .map(|balance_change| balance_change.quantity)
+            .sum()
+    }
+}
+
+fn get_field<'v>(value: &'v Value, field: &str) -> Result<&'v Value> {
+    match value.get(field) {
+        Some(field) => Ok(field),
+        None => bail!(MetadataException::MissingField(field.to_string())),
+    }
+}
+
+fn get_str<'v>(value: &'v Value, field: &str) -> Result<&'v str> {
+    match get_field(value, field)?.as_str() {
+        Some(field) => Ok(field),
+        None => bail!(MetadataException::MissingField(field.to_string())),
+    }
+}
+
+fn parse_decimal(value: &str) -> Result<Decimal> {
+    match value.parse().or_else(|_| Decimal::from_scientific(value)) {
+        Ok(decimal) => Ok(decimal),
+        Err(_) => bail!(MetadataException::InvalidAmount(value.to_string())),
+    }
+}
+
+fn parse_drops(fields: &Value, field: &str) -> Result<Decimal> {
+    Ok(parse_decimal(get_str(fields, field)?)? / Decimal::from(DROPS_PER_XRP))
+}
+
+fn parse_token_balance(fields: &Value) -> Result<Decimal> {
+    parse_decimal(get_str(get_field(fields, "Balance")?, "value")?)
+}
+
+/// Parses an amount in drops (a string) or a token amount (an object).
+fn parse_amount(amount: &Value) -> Result<CurrencyAmount<'static>> {
+    if let Some(drops) = amount.as_str() {
+        return Ok(CurrencyAmount {
+            currency: Currency::xrp(),
+            quantity: parse_decimal(drops)? / Decimal::from(DROPS_PER_XRP),
+        });
+    }
+
+    Ok(CurrencyAmount {
+        currency: Currency {
+            currency_code: Cow::Owned(get_str(amount, "currency")?.to_string()),
+            issuer: Cow::Owned(get_str(amount, "issuer")?.to_string()),
+            transfer_fee: 0.0,
+        },
+        quantity: parse_decimal(get_str(amount, "value")?)?,
+    })
+}
@@ -1 +1,4 @@
+pub mod account;
 pub mod currency;
+pub mod exceptions;
+pub mod metadata;
@@ -0,0 +1,160 @@
+use std::{borrow::Cow, cmp::Reverse, collections::HashMap};
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use super::{order::Order, order_book::OrderBook};
+
+/// Issuer specific adjustments applied while building a composite order book.
+#[derive(Debug, Clone, Default)]
+pub struct IssuerAdjustments<'a> {
+    /// If set, only orders whose (non XRP) issuers are part of this list are merged.
+    pub allowed_issuers: Option<Vec<Cow<'a, str>>>,
+    /// Haircut per issuer as a fraction (e.g. `0.02` for 2%). The value of a token of the issuer
+    /// is reduced by the haircut when comparing levels of different issuers.
+    pub haircuts: HashMap<Cow<'a, str>, Decimal>,
+}
+
+impl IssuerAdjustments<'_> {
+    pub fn is_allowed_issuer(&self, issuer: &str) -> bool {
+        // XRP has no issuer and is always allowed.
+        if issuer.is_empty() {
+            return true;
+        }
+        match &self.allowed_issuers {
+            Some(allowed_issuers) => allowed_issuers.iter().any(|allowed| allowed == issuer),
+            None => true,
+        }
+    }
+
+    pub fn get_haircut(&self, issuer: &str) -> Decimal {
+        self.haircuts.get(issuer).copied().unwrap_or_default()
+    }
+
+    /// Returns the rate of the order after applying the haircuts of the base and counter issuer.
+    pub fn calculate_adjusted_rate(&self, order: &Order<'_>) -> Decimal {
+        let base_value = Decimal::ONE - self.get_haircut(&order.base.issuer);
+        let counter_value = Decimal::ONE - self.get_haircut(&order.counter.issuer);
+        if base_value.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        order.rate * counter_value / base_value
+    }
+}
+
+/// A single level of a composite order book keeping the attribution to its issuers.
+#[derive(Debug, Clone)]
+pub struct CompositeLevel<'a> {
+    pub order: Order<'a>,
+    /// The rate after applying the issuer haircuts. Levels are sorted by this rate.
+    pub adjusted_rate: Decimal,
+}
+
+impl CompositeLevel<'_> {
+    pub fn get_base_issuer(&self) -> &str {
+        &self.order.base.issuer
+    }
+
+    pub fn get_counter_issuer(&self) -> &str {
+        &self.order.counter.issuer
+    }
+}
+
+/// A view merging all order books that share the same base and counter currency codes,
+/// regardless of their issuers (e.g. XRP/USD:Bitstamp and XRP/USD:GateHub).
+#[derive(Debug, Clone)]
+pub struct CompositeOrderBook<'a> {
+    pub base_currency_code: Cow<'a, str>,
+    pub counter_currency_code: Cow<'a, str>,
+    /// Sorted in descending order of their adjusted rate.
+    pub bids: Vec<CompositeLevel<'a>>,
+    /// Sorted in ascending order of their adjusted rate.
+    pub asks: Vec<CompositeLevel<'a>>,
+}
+
+impl<'a> CompositeOrderBook<'a> {
+    pub fn new(base_currency_code: Cow<'a, str>, counter_currency_code: Cow<'a, str>) -> Self {
+        Self {
+            base_currency_code,
+            counter_currency_code,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    pub fn get_best_bid(&self) -> Option<&CompositeLevel<'a>> {
+        self.bids.first()
+    }
+
+    pub fn get_best_ask(&self) -> Option<&CompositeLevel<'a>> {
+        self.asks.first()
+    }
+
+    /// Returns true if the order book has the same currency codes, no matter the orientation.
+    pub fn is_order_book_for_composite(&self, order_book: &OrderBook<'_>) -> bool {
+        self.is_same_orientation(order_book) || self.is_flipped_orientation(order_book)
+    }
+
+    fn is_same_orientation(&self, order_book: &OrderBook<'_>) -> bool {
+        order_book.base.currency_code == self.base_currency_code
+            && order_book.counter.currency_code == self.counter_currency_code
+    }
+
+    fn is_flipped_orientation(&self, order_book: &OrderBook<'_>) -> bool {
+        order_book.base.currency_code == self.counter_currency_code
+            && order_book.counter.currency_code == self.base_currency_code
+    }
+
+    /// Merges the orders of the order book into the composite order book. Order books with the
+    /// flipped orientation get their orders flipped, so their bids become asks and vice versa.
+    pub fn merge_order_book(
+        &mut self,
+        order_book: &OrderBook<'a>,
+        adjustments: &IssuerAdjustments<'_>,
+    ) -> Result<()> {
+        let flipped = if self.is_same_orientation(order_book) {
+            false
+        } else if self.is_flipped_orientation(order_book) {
+            true
+        } else {
+            return Ok(());
+        };
+        let (bids, asks) = order_book.get_oriented_orders(flipped)?;
+        for order in bids {
+            if let Some(level) = Self::build_level(order, adjustments) {
+                self.bids.push(level);
+            }
+        }
+        for order in asks {
+            if let Some(level) = Self::build_level(order, adjustments) {
+                self.asks.push(level);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_level(
+        order: Order<'a>,
+        adjustments: &IssuerAdjustments<'_>,
+    ) -> Option<CompositeLevel<'a>> {
+        if !adjustments.is_allowed_issuer(&order.base.issuer)
+            || !adjustments.is_allowed_issuer(&order.counter.issuer)
+        {
+            return None;
+        }
+        let adjusted_rate = adjustments.calculate_adjusted_rate(&order);
+
+        Some(CompositeLevel {
+            order,
+            adjusted_rate,
+        })
+    }
+
+    /// Sorts the bids in descending order and the asks in ascending order of their adjusted rate.
+    pub fn sort(&mut self) {
+        self.bids.sort_by_key(|level| Reverse(level.adjusted_rate));
+        self.asks.sort_by_key(|level| level.adjusted_rate);
+    }
+}
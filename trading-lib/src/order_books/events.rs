@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::models::currency::Currency;
+
+use super::{
+    order::Order,
+    order_book::{OrderBook, OrderBookSideType},
+};
+
+/// A typed change between two states of an order book. Levels are identified by their rate and
+/// their quantity is the sum of the base quantities of all orders at that rate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderBookEvent {
+    LevelAdded {
+        side_type: OrderBookSideType,
+        rate: Decimal,
+        quantity: Decimal,
+    },
+    LevelRemoved {
+        side_type: OrderBookSideType,
+        rate: Decimal,
+        quantity: Decimal,
+    },
+    QuantityChanged {
+        side_type: OrderBookSideType,
+        rate: Decimal,
+        old_quantity: Decimal,
+        new_quantity: Decimal,
+    },
+    BestBidChanged {
+        old_rate: Option<Decimal>,
+        new_rate: Option<Decimal>,
+    },
+    BestAskChanged {
+        old_rate: Option<Decimal>,
+        new_rate: Option<Decimal>,
+    },
+    /// The best bid is greater than or equal to the best ask.
+    BookCrossed {
+        best_bid_rate: Decimal,
+        best_ask_rate: Decimal,
+    },
+}
+
+/// The events of a single order book update, published to the subscribers of `OrderBooks`.
+#[derive(Debug, Clone)]
+pub struct OrderBookUpdate<'a> {
+    pub base: Currency<'a>,
+    pub counter: Currency<'a>,
+    pub events: Vec<OrderBookEvent>,
+}
+
+type Levels = BTreeMap<Decimal, Decimal>;
+
+fn aggregate_levels(orders: &[Order<'_>]) -> Levels {
+    let mut levels = Levels::new();
+    for order in orders {
+        *levels.entry(order.rate).or_default() += order.base_quantity;
+    }
+
+    levels
+}
+
+fn diff_levels(side_type: OrderBookSideType, old: &Levels, new: &Levels) -> Vec<OrderBookEvent> {
+    let mut events = Vec::new();
+    for (rate, old_quantity) in old.iter() {
+        match new.get(rate) {
+            None => events.push(OrderBookEvent::LevelRemoved {
+                side_type: side_type.clone(),
+                rate: *rate,
+                quantity: *old_quantity,
+            }),
+            Some(new_quantity) if new_quantity != old_quantity => {
+                events.push(OrderBookEvent::QuantityChanged {
+                    side_type: side_type.clone(),
+                    rate: *rate,
+                    old_quantity: *old_quantity,
+                    new_quantity: *new_quantity,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (rate, new_quantity) in new.iter() {
+        if !old.contains_key(rate) {
+            events.push(OrderBookEvent::LevelAdded {
+                side_type: side_type.clone(),
+                rate: *rate,
+                quantity: *new_quantity,
+            });
+        }
+    }
+
+    events
+}
+
+fn is_crossed(best_bid_rate: Option<Decimal>, best_ask_rate: Option<Decimal>) -> bool {
+    matches!(
+        (best_bid_rate, best_ask_rate),
+        (Some(best_bid_rate), Some(best_ask_rate)) if best_bid_rate >= best_ask_rate
+    )
+}
+
+impl OrderBook<'_> {
+    /// Returns the events that turn this order book into the `new` order book.
+    pub fn diff(&self, new: &OrderBook<'_>) -> Result<Vec<OrderBookEvent>> {
+        let old_bids = aggregate_levels(&self.get_bids()?.orders);
+        let old_asks = aggregate_levels(&self.get_asks()?.orders);
+        let new_bids = aggregate_levels(&new.get_bids()?.orders);
+        let new_asks = aggregate_levels(&new.get_asks()?.orders);
+
+        let mut events = diff_levels(OrderBookSideType::Bids, &old_bids, &new_bids);
+        events.extend(diff_levels(OrderBookSideType::Asks, &old_asks, &new_asks));
+
+        let old_best_bid_rate = old_bids.keys().next_back().copied();
+        let new_best_bid_rate = new_bids.keys().next_back().copied();
+        let old_best_ask_rate = old_asks.keys().next().copied();
+        let new_best_ask_rate = new_asks.keys().next().copied();
+        if old_best_bid_rate != new_best_bid_rate {
+            events.push(OrderBookEvent::BestBidChanged {
+                old_rate: old_best_bid_rate,
+                new_rate: new_best_bid_rate,
+            });
+        }
+        if old_best_ask_rate != new_best_ask_rate {
+            events.push(OrderBookEvent::BestAskChanged {
+                old_rate: old_best_ask_rate,
+                new_rate: new_best_ask_rate,
+            });
+        }
+        if !is_crossed(old_best_bid_rate, old_best_ask_rate)
+            && is_crossed(new_best_bid_rate, new_best_ask_rate)
+        {
+            if let (Some(best_bid_rate), Some(best_ask_rate)) =
+                (new_best_bid_rate, new_best_ask_rate)
+            {
+                events.push(OrderBookEvent::BookCrossed {
+                    best_bid_rate,
+                    best_ask_rate,
+                });
+            }
+        }
+
+        Ok(events)
+    }
+}
@@ -4,4 +4,10 @@ use thiserror::Error;
 pub enum OrderBookException {
     #[error("Invalid order")]
     InvalidOrder,
+    #[error("Invalid order book")]
+    InvalidOrderBook,
+    #[error("Order book validation failed: {0}")]
+    ValidationFailed(String),
+    #[error("No order book reflects ledger {0}")]
+    NoOrderBooksAtLedger(u32),
 }
@@ -0,0 +1,148 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use anyhow::Result;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::models::currency::Currency;
+
+use super::{
+    order::Order,
+    order_book::{OrderBook, OrderBookSideType},
+};
+
+/// (owner, currency code, issuer)
+type BalanceKey = (String, String, String);
+
+fn build_balance_key(owner: &str, currency: &Currency<'_>) -> BalanceKey {
+    (
+        owner.to_string(),
+        currency.currency_code.to_string(),
+        currency.issuer.to_string(),
+    )
+}
+
+/// The balances of offer owners per currency. For XRP the balance should be the spendable amount
+/// above the reserve.
+#[derive(Debug, Clone, Default)]
+pub struct OwnerBalances {
+    balances: HashMap<BalanceKey, Decimal>,
+}
+
+impl OwnerBalances {
+    pub fn set_balance(&mut self, owner: &str, currency: &Currency<'_>, balance: Decimal) {
+        self.balances
+            .insert(build_balance_key(owner, currency), balance);
+    }
+
+    pub fn get_balance(&self, owner: &str, currency: &Currency<'_>) -> Option<Decimal> {
+        self.balances
+            .get(&build_balance_key(owner, currency))
+            .copied()
+    }
+}
+
+impl<'a> OrderBook<'a> {
+    /// Returns a copy of the order book without the orders owned by one of `own_accounts`.
+    /// Consuming them would cancel them as a self-cross instead of trading.
+    pub fn without_own_orders(&self, own_accounts: &HashSet<Cow<'_, str>>) -> Result<Self> {
+        let is_foreign = |order: &Order<'_>| {
+            order
+                .get_owner()
+                .is_none_or(|owner| !own_accounts.contains(owner))
+        };
+        let bids = self
+            .get_bids()?
+            .orders
+            .iter()
+            .filter(|order| is_foreign(order))
+            .cloned()
+            .collect();
+        let asks = self
+            .get_asks()?
+            .orders
+            .iter()
+            .filter(|order| is_foreign(order))
+            .cloned()
+            .collect();
+
+        Ok(self.with_orders(bids, asks))
+    }
+
+    /// Returns a copy of the order book where orders are reduced to the amount their owners can
+    /// fund, walking each side from the best order on. Orders that can't be funded at all are
+    /// removed. Orders without owner or with an unknown balance are kept as they are.
+    pub fn without_unfunded_orders(&self, owner_balances: &OwnerBalances) -> Result<Self> {
+        let bids = Self::fund_orders(
+            &self.get_bids()?.orders,
+            OrderBookSideType::Bids,
+            owner_balances,
+        );
+        let asks = Self::fund_orders(
+            &self.get_asks()?.orders,
+            OrderBookSideType::Asks,
+            owner_balances,
+        );
+
+        Ok(self.with_orders(bids, asks))
+    }
+
+    pub(super) fn with_orders(&self, bids: Vec<Order<'a>>, asks: Vec<Order<'a>>) -> Self {
+        let mut order_book = OrderBook::new(self.base.clone(), self.counter.clone(), bids, asks);
+        order_book.strict = self.strict;
+
+        order_book
+    }
+
+    fn fund_orders(
+        orders: &[Order<'a>],
+        side_type: OrderBookSideType,
+        owner_balances: &OwnerBalances,
+    ) -> Vec<Order<'a>> {
+        let mut remaining_balances: HashMap<String, Decimal> = HashMap::new();
+        let mut funded_orders = Vec::new();
+        for order in orders {
+            let Some(owner) = order.get_owner() else {
+                funded_orders.push(order.clone());
+                continue;
+            };
+            // Both sides are stored in the orientation of the order book. The owner of a bid
+            // gives the counter currency, the owner of an ask gives the base currency.
+            let (given_currency, given_amount) = match side_type {
+                OrderBookSideType::Bids => (&order.counter, order.base_quantity * order.rate),
+                OrderBookSideType::Asks => (&order.base, order.base_quantity),
+            };
+            // Issuers can always fund their own tokens.
+            if given_currency.issuer == owner {
+                funded_orders.push(order.clone());
+                continue;
+            }
+            let Some(balance) = owner_balances.get_balance(owner, given_currency) else {
+                funded_orders.push(order.clone());
+                continue;
+            };
+            let remaining_balance = remaining_balances
+                .entry(owner.to_string())
+                .or_insert(balance);
+            let transfer_rate =
+                Decimal::ONE + Decimal::from_f32(given_currency.transfer_fee).unwrap_or_default();
+            let required_amount = given_amount * transfer_rate;
+            if *remaining_balance <= Decimal::ZERO || required_amount <= Decimal::ZERO {
+                continue;
+            }
+            let mut funded_order = order.clone();
+            if required_amount > *remaining_balance {
+                let funded_share = *remaining_balance / required_amount;
+                funded_order.base_quantity *= funded_share;
+                *remaining_balance = Decimal::ZERO;
+            } else {
+                *remaining_balance -= required_amount;
+            }
+            funded_orders.push(funded_order);
+        }
+
+        funded_orders
+    }
+}
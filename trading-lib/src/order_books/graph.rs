@@ -0,0 +1,435 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
+
+use crate::models::currency::Currency;
+
+use super::{order::Order, order_book::OrderBook, OrderBooks};
+
+/// A directed edge of the [`CurrencyGraph`]: selling `from` for `to` at the best level of an
+/// order book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyEdge {
+    /// The index of the currency being sold.
+    pub from: usize,
+    /// The index of the currency being bought.
+    pub to: usize,
+    /// The amount of `to` received for one `from` after the transfer fee of `to`.
+    pub rate: Decimal,
+    /// The amount of `from` the best level takes at `rate`.
+    pub depth: Decimal,
+}
+
+/// A path through the [`CurrencyGraph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyPath<'a> {
+    pub currencies: Vec<Currency<'a>>,
+    /// The amount of the last currency received for one of the first currency.
+    pub rate: Decimal,
+    /// The amount of the first currency that can be sold along the path at `rate`.
+    pub depth: Decimal,
+}
+
+/// A directed graph of currencies (distinct by code and issuer) connected by the order books
+/// trading them.
+#[derive(Debug, Clone, Default)]
+pub struct CurrencyGraph<'a> {
+    pub currencies: Vec<Currency<'a>>,
+    pub edges: Vec<CurrencyEdge>,
+}
+
+impl<'a> CurrencyGraph<'a> {
+    /// Adds the edges of both sides of the order book: the bids sell base for counter and the
+    /// asks sell counter for base.
+    pub fn add_order_book(&mut self, order_book: &OrderBook<'a>) -> Result<()> {
+        let base = self.add_currency(&order_book.base);
+        let counter = self.add_currency(&order_book.counter);
+        let bids = order_book.get_bids()?;
+        if let Some((rate, quantity)) = get_best_level(&bids.orders, |a, b| a > b) {
+            self.edges.push(CurrencyEdge {
+                from: base,
+                to: counter,
+                rate: rate * get_fee_multiplier(&order_book.counter),
+                depth: quantity,
+            });
+        }
+        let asks = order_book.get_asks()?;
+        if let Some((rate, quantity)) = get_best_level(&asks.orders, |a, b| a < b) {
+            self.edges.push(CurrencyEdge {
+                from: counter,
+                to: base,
+                rate: Decimal::ONE / rate * get_fee_multiplier(&order_book.base),
+                depth: quantity * rate,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn add_currency(&mut self, currency: &Currency<'a>) -> usize {
+        match self.get_currency_index(currency) {
+            Some(index) => index,
+            None => {
+                self.currencies.push(currency.clone());
+                self.currencies.len() - 1
+            }
+        }
+    }
+
+    pub fn get_currency_index(&self, currency: &Currency<'_>) -> Option<usize> {
+        self.currencies
+            .iter()
+            .position(|existing| existing.is_same_asset(currency))
+    }
+
+    /// Returns the edges starting at the currency.
+    pub fn get_neighbors(&self, currency: &Currency<'_>) -> Vec<&CurrencyEdge> {
+        match self.get_currency_index(currency) {
+            Some(index) => self
+                .edges
+                .iter()
+                .filter(|edge| edge.from == index)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the path from `from` to `to` with the fewest hops.
+    pub fn get_shortest_path(
+        &self,
+        from: &Currency<'_>,
+        to: &Currency<'_>,
+    ) -> Option<CurrencyPath<'a>> {
+        let start = self.get_currency_index(from)?;
+        let end = self.get_currency_index(to)?;
+        if start == end {
+            return None;
+        }
+        let mut previous: Vec<Option<usize>> = vec![None; self.currencies.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(current) = queue.pop_front() {
+            if current == end {
+                break;
+            }
+            for (edge_index, edge) in self.edges.iter().enumerate() {
+                if edge.from == current && edge.to != start && previous[edge.to].is_none() {
+                    previous[edge.to] = Some(edge_index);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        let mut edges = Vec::new();
+        let mut current = end;
+        while current != start {
+            let edge_index = previous[current]?;
+            edges.push(edge_index);
+            current = self.edges[edge_index].from;
+        }
+        edges.reverse();
+
+        Some(self.get_path(&edges))
+    }
+
+    /// Returns the path from `from` to `to` with at most `max_hops` hops receiving the most `to`
+    /// for one `from`. Paths never visit a currency twice.
+    pub fn get_best_rate_path(
+        &self,
+        from: &Currency<'_>,
+        to: &Currency<'_>,
+        max_hops: usize,
+    ) -> Option<CurrencyPath<'a>> {
+        let start = self.get_currency_index(from)?;
+        let end = self.get_currency_index(to)?;
+        if start == end {
+            return None;
+        }
+        let mut best: Option<(Decimal, Vec<usize>)> = None;
+        let mut visited = vec![false; self.currencies.len()];
+        visited[start] = true;
+        self.search_best_rate_path(
+            start,
+            end,
+            max_hops,
+            Decimal::ONE,
+            &mut Vec::new(),
+            &mut visited,
+            &mut best,
+        );
+
+        best.map(|(_, edges)| self.get_path(&edges))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_best_rate_path(
+        &self,
+        current: usize,
+        end: usize,
+        hops_left: usize,
+        rate: Decimal,
+        edges: &mut Vec<usize>,
+        visited: &mut [bool],
+        best: &mut Option<(Decimal, Vec<usize>)>,
+    ) {
+        if current == end {
+            if best.as_ref().is_none_or(|(best_rate, _)| rate > *best_rate) {
+                *best = Some((rate, edges.clone()));
+            }
+            return;
+        }
+        if hops_left == 0 {
+            return;
+        }
+        for (edge_index, edge) in self.edges.iter().enumerate() {
+            if edge.from != current || visited[edge.to] {
+                continue;
+            }
+            visited[edge.to] = true;
+            edges.push(edge_index);
+            self.search_best_rate_path(
+                edge.to,
+                end,
+                hops_left - 1,
+                rate * edge.rate,
+                edges,
+                visited,
+                best,
+            );
+            edges.pop();
+            visited[edge.to] = false;
+        }
+    }
+
+    /// Returns the distinct cycles with at most `max_hops` hops receiving more of their first
+    /// currency than they give, best rate first. The edges are weighted with the negative
+    /// logarithm of their rate, so profitable cycles are the negative cycles of the graph.
+    ///
+    /// Each cycle starts and ends with `anchor` or, without one, its currency added to the graph
+    /// first, so rotations of a cycle are returned once. Per start currency a Bellman-Ford of
+    /// `max_hops` rounds towards the start gives the lowest weight left to close a cycle from
+    /// every currency. The simple cycles through the start are then walked, dropping every path
+    /// which can't end up negative within the remaining hops.
+    pub fn get_negative_cycles(
+        &self,
+        max_hops: usize,
+        anchor: Option<&Currency<'_>>,
+    ) -> Vec<CurrencyPath<'a>> {
+        let anchor = match anchor {
+            Some(anchor) => match self.get_currency_index(anchor) {
+                Some(index) => Some(index),
+                None => return Vec::new(),
+            },
+            None => None,
+        };
+        let weights: Vec<f64> = self
+            .edges
+            .iter()
+            .map(|edge| match edge.rate.to_f64() {
+                Some(rate) if rate > 0.0 => -rate.ln(),
+                _ => f64::INFINITY,
+            })
+            .collect();
+        let starts = match anchor {
+            Some(anchor) => vec![anchor],
+            None => (0..self.currencies.len()).collect(),
+        };
+        let mut search = CycleSearch {
+            weights,
+            max_hops,
+            start: 0,
+            // Without an anchor only currencies after the start are visited, so every cycle
+            // starts with its lowest index.
+            min_index: 0,
+            closing_weights: Vec::new(),
+            edges: Vec::new(),
+            visited: vec![false; self.currencies.len()],
+            cycles: Vec::new(),
+        };
+        for start in starts {
+            search.start = start;
+            search.min_index = if anchor.is_some() { 0 } else { start };
+            search.closing_weights = self.get_closing_weights(&search);
+            search.visited[start] = true;
+            self.search_negative_cycles(&mut search, start, 0.0);
+            search.visited[start] = false;
+        }
+        let mut known_cycles = HashSet::new();
+        let mut cycles = Vec::new();
+        for cycle in search.cycles.iter() {
+            // Order books trading the same currencies twice give the same cycle twice.
+            let currencies: Vec<usize> = cycle
+                .iter()
+                .map(|&edge_index| self.edges[edge_index].from)
+                .collect();
+            let path = self.get_path(cycle);
+            if path.rate > Decimal::ONE && known_cycles.insert(currencies) {
+                cycles.push(path);
+            }
+        }
+        cycles.sort_by_key(|cycle| Reverse(cycle.rate));
+
+        cycles
+    }
+
+    /// Runs Bellman-Ford for `max_hops` rounds towards the start of the search. Returns the
+    /// lowest weight of a walk from every currency to the start per number of hops left, which
+    /// no simple cycle can beat.
+    fn get_closing_weights(&self, search: &CycleSearch) -> Vec<Vec<f64>> {
+        let mut closing_weights = vec![vec![f64::INFINITY; self.currencies.len()]];
+        closing_weights[0][search.start] = 0.0;
+        for hops in 1..=search.max_hops {
+            let mut next_weights = closing_weights[hops - 1].clone();
+            for (edge_index, edge) in self.edges.iter().enumerate() {
+                if edge.from < search.min_index || edge.to < search.min_index {
+                    continue;
+                }
+                let weight = search.weights[edge_index] + closing_weights[hops - 1][edge.to];
+                if weight < next_weights[edge.from] {
+                    next_weights[edge.from] = weight;
+                }
+            }
+            closing_weights.push(next_weights);
+        }
+
+        closing_weights
+    }
+
+    fn search_negative_cycles(&self, search: &mut CycleSearch, current: usize, weight: f64) {
+        let hops = search.edges.len();
+        for (edge_index, edge) in self.edges.iter().enumerate() {
+            if edge.from != current || edge.to < search.min_index {
+                continue;
+            }
+            let next_weight = weight + search.weights[edge_index];
+            if edge.to == search.start {
+                if next_weight < NEGATIVE_WEIGHT_TOLERANCE {
+                    let mut cycle = search.edges.clone();
+                    cycle.push(edge_index);
+                    search.cycles.push(cycle);
+                }
+                continue;
+            }
+            if search.visited[edge.to]
+                || hops + 1 >= search.max_hops
+                || next_weight + search.closing_weights[search.max_hops - hops - 1][edge.to]
+                    >= NEGATIVE_WEIGHT_TOLERANCE
+            {
+                continue;
+            }
+            search.visited[edge.to] = true;
+            search.edges.push(edge_index);
+            self.search_negative_cycles(search, edge.to, next_weight);
+            search.edges.pop();
+            search.visited[edge.to] = false;
+        }
+    }
+
+    fn get_path(&self, edges: &[usize]) -> CurrencyPath<'a> {
+        let mut currencies = vec![self.currencies[self.edges[edges[0]].from].clone()];
+        let mut rate = Decimal::ONE;
+        let mut depth: Option<Decimal> = None;
+        for &edge_index in edges {
+            let edge = &self.edges[edge_index];
+            // The depth of the edge in units of the first currency. Nothing gets past an edge
+            // whose rate is eaten by transfer fees.
+            let edge_depth = if rate > Decimal::ZERO {
+                edge.depth / rate
+            } else {
+                Decimal::ZERO
+            };
+            depth = Some(depth.map_or(edge_depth, |depth| depth.min(edge_depth)));
+            rate *= edge.rate;
+            currencies.push(self.currencies[edge.to].clone());
+        }
+
+        CurrencyPath {
+            currencies,
+            rate,
+            depth: depth.unwrap_or_default(),
+        }
+    }
+
+    /// Exports the graph in the DOT format of GraphViz. Edges are labeled with their rate and
+    /// depth.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph currencies {\n");
+        for (index, currency) in self.currencies.iter().enumerate() {
+            let _ = writeln!(dot, "    {index} [label=\"{}\"];", currency.get_label());
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [label=\"{} ({})\"];",
+                edge.from,
+                edge.to,
+                edge.rate.normalize(),
+                edge.depth.normalize()
+            );
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Builds the currency graph of all order books.
+    pub fn get_currency_graph(&self) -> Result<CurrencyGraph<'a>> {
+        let mut graph = CurrencyGraph::default();
+        for order_book in self.order_books.iter() {
+            graph.add_order_book(order_book)?;
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Returns the best rate of the orders and the total quantity at that rate.
+fn get_best_level(
+    orders: &[Order<'_>],
+    is_better: impl Fn(Decimal, Decimal) -> bool,
+) -> Option<(Decimal, Decimal)> {
+    // Orders without a positive rate can't be inverted into an edge.
+    let best_rate = orders
+        .iter()
+        .map(|order| order.rate)
+        .filter(|rate| *rate > Decimal::ZERO)
+        .reduce(|best, rate| if is_better(rate, best) { rate } else { best })?;
+    let quantity = orders
+        .iter()
+        .filter(|order| order.rate == best_rate)
+        .map(|order| order.base_quantity)
+        .sum();
+
+    Some((best_rate, quantity))
+}
+
+fn get_fee_multiplier(currency: &Currency<'_>) -> Decimal {
+    Decimal::ONE - Decimal::from_f32(currency.transfer_fee).unwrap_or_default()
+}
+
+/// Weights below this are negative. It leaves room for the rounding of the logarithms, the
+/// exact rate of every cycle is checked afterwards.
+const NEGATIVE_WEIGHT_TOLERANCE: f64 = 1e-12;
+
+/// The state of [`CurrencyGraph::get_negative_cycles`] for one start currency.
+struct CycleSearch {
+    weights: Vec<f64>,
+    max_hops: usize,
+    start: usize,
+    /// The lowest index a currency of the cycles may have.
+    min_index: usize,
+    /// The lowest weight from every currency back to the start, per number of hops left.
+    closing_weights: Vec<Vec<f64>>,
+    /// The edges of the current path.
+    edges: Vec<usize>,
+    visited: Vec<bool>,
+    cycles: Vec<Vec<usize>>,
+}
@@ -0,0 +1,201 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::models::currency::Currency;
+
+use super::{
+    order::Order,
+    order_book::{OrderBook, OrderBookSideType},
+    OrderBooks,
+};
+
+/// Decides which issuers we trade tokens of. XRP has no issuer and is always allowed.
+#[derive(Debug, Clone, Default)]
+pub struct IssuerPolicy<'a> {
+    /// If set, only tokens of these issuers are traded.
+    pub allowed_issuers: Option<HashSet<Cow<'a, str>>>,
+    pub denied_issuers: HashSet<Cow<'a, str>>,
+    pub trust_scores: HashMap<Cow<'a, str>, f64>,
+    /// If set, issuers with a lower or without trust score are excluded.
+    pub min_trust_score: Option<f64>,
+    /// The maximum amount of an issuer's tokens we take in from one order book side.
+    pub exposure_caps: HashMap<Cow<'a, str>, Decimal>,
+}
+
+/// Why an order book is excluded from evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExclusionReason {
+    DeniedIssuer {
+        issuer: String,
+    },
+    IssuerNotAllowed {
+        issuer: String,
+    },
+    TrustScoreTooLow {
+        issuer: String,
+        trust_score: Option<f64>,
+        min_trust_score: f64,
+    },
+    /// The exposure cap of the issuer is zero.
+    NoExposure {
+        issuer: String,
+    },
+    /// The order book lags more than the maximum age behind the current ledger. `age` is `None`
+    /// if the order book has no ledger stamp.
+    Stale {
+        age: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBookExclusion<'a> {
+    pub base: Currency<'a>,
+    pub counter: Currency<'a>,
+    pub reasons: Vec<ExclusionReason>,
+}
+
+impl IssuerPolicy<'_> {
+    /// Returns why tokens of the issuer must not be traded or `None` if they can be.
+    pub fn check_issuer(&self, issuer: &str) -> Option<ExclusionReason> {
+        if issuer.is_empty() {
+            return None;
+        }
+        if self.denied_issuers.contains(issuer) {
+            return Some(ExclusionReason::DeniedIssuer {
+                issuer: issuer.to_string(),
+            });
+        }
+        if let Some(allowed_issuers) = &self.allowed_issuers {
+            if !allowed_issuers.contains(issuer) {
+                return Some(ExclusionReason::IssuerNotAllowed {
+                    issuer: issuer.to_string(),
+                });
+            }
+        }
+        if let Some(min_trust_score) = self.min_trust_score {
+            let trust_score = self.trust_scores.get(issuer).copied();
+            if trust_score.is_none_or(|trust_score| trust_score < min_trust_score) {
+                return Some(ExclusionReason::TrustScoreTooLow {
+                    issuer: issuer.to_string(),
+                    trust_score,
+                    min_trust_score,
+                });
+            }
+        }
+        if self.get_exposure_cap(issuer) == Some(Decimal::ZERO) {
+            return Some(ExclusionReason::NoExposure {
+                issuer: issuer.to_string(),
+            });
+        }
+
+        None
+    }
+
+    pub fn get_exposure_cap(&self, issuer: &str) -> Option<Decimal> {
+        self.exposure_caps.get(issuer).copied()
+    }
+}
+
+impl<'a> OrderBook<'a> {
+    /// Returns a copy of the order book where each side is cut off once the tokens we would take
+    /// in reach the exposure cap of their issuer. Consuming a bid gives us the counter currency,
+    /// consuming an ask the base currency.
+    pub fn with_exposure_caps(&self, issuer_policy: &IssuerPolicy<'_>) -> Result<Self> {
+        let bids = Self::cap_orders(
+            &self.get_bids()?.orders,
+            OrderBookSideType::Bids,
+            issuer_policy,
+        );
+        let asks = Self::cap_orders(
+            &self.get_asks()?.orders,
+            OrderBookSideType::Asks,
+            issuer_policy,
+        );
+
+        Ok(self.with_orders(bids, asks))
+    }
+
+    fn cap_orders(
+        orders: &[Order<'a>],
+        side_type: OrderBookSideType,
+        issuer_policy: &IssuerPolicy<'_>,
+    ) -> Vec<Order<'a>> {
+        let mut taken_amounts: HashMap<String, Decimal> = HashMap::new();
+        let mut capped_orders = Vec::new();
+        for order in orders {
+            let (taken_currency, taken_amount) = match side_type {
+                OrderBookSideType::Bids => (&order.counter, order.base_quantity * order.rate),
+                OrderBookSideType::Asks => (&order.base, order.base_quantity),
+            };
+            let Some(exposure_cap) = issuer_policy.get_exposure_cap(&taken_currency.issuer) else {
+                capped_orders.push(order.clone());
+                continue;
+            };
+            let taken = taken_amounts
+                .entry(taken_currency.issuer.to_string())
+                .or_default();
+            let remaining = exposure_cap - *taken;
+            if remaining <= Decimal::ZERO || taken_amount <= Decimal::ZERO {
+                continue;
+            }
+            let mut capped_order = order.clone();
+            if taken_amount > remaining {
+                capped_order.base_quantity *= remaining / taken_amount;
+                *taken = exposure_cap;
+            } else {
+                *taken += taken_amount;
+            }
+            capped_orders.push(capped_order);
+        }
+
+        capped_orders
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Returns why the order book is excluded from evaluation. An empty list means it is
+    /// evaluated.
+    pub fn get_exclusion_reasons(&self, order_book: &OrderBook<'_>) -> Vec<ExclusionReason> {
+        let mut reasons = Vec::new();
+        for currency in [&order_book.base, &order_book.counter] {
+            if let Some(reason) = self.issuer_policy.check_issuer(&currency.issuer) {
+                reasons.push(reason);
+            }
+        }
+        if self.is_stale(order_book) {
+            reasons.push(ExclusionReason::Stale {
+                age: self.get_age(order_book),
+            });
+        }
+
+        reasons
+    }
+
+    pub fn is_excluded(&self, order_book: &OrderBook<'_>) -> bool {
+        !self.get_exclusion_reasons(order_book).is_empty()
+    }
+
+    /// Reports every excluded order book together with the reasons.
+    pub fn get_exclusions(&self) -> Vec<OrderBookExclusion<'a>> {
+        self.order_books
+            .iter()
+            .filter_map(|order_book| {
+                let reasons = self.get_exclusion_reasons(order_book);
+                if reasons.is_empty() {
+                    return None;
+                }
+
+                Some(OrderBookExclusion {
+                    base: order_book.base.clone(),
+                    counter: order_book.counter.clone(),
+                    reasons,
+                })
+            })
+            .collect()
+    }
+}
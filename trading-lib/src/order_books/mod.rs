@@ -1,30 +1,183 @@
+pub mod composite;
+pub mod events;
 pub mod exceptions;
+pub mod funding;
+pub mod graph;
+pub mod issuer_policy;
 pub mod order;
 pub mod order_book;
+pub mod registry;
+pub mod report;
+pub mod rippling;
+pub mod snapshots;
+pub mod staleness;
+pub mod synthetic;
+pub mod validation;
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::mpsc::{channel, Receiver, Sender},
+};
 
 use anyhow::{Ok, Result};
+use composite::{CompositeOrderBook, IssuerAdjustments};
+use events::{OrderBookEvent, OrderBookUpdate};
+use funding::OwnerBalances;
+use issuer_policy::IssuerPolicy;
 use order_book::OrderBook;
+use registry::{OrderBookKey, OrderBookMetadata};
+use synthetic::SyntheticOrderBook;
+
+use crate::models::{account::AccountState, currency::Currency};
+#[cfg(feature = "arbitrage")]
+use crate::trading_types::arbitrage::filters::TradeFilters;
 
 #[derive(Debug, Clone)]
 pub struct OrderBooks<'a> {
     pub order_books: Cow<'a, [OrderBook<'a>]>,
     pub liquidity_spread: f64,
+    /// Receive an [`OrderBookUpdate`] whenever an order book is updated.
+    pub subscribers: Vec<Sender<OrderBookUpdate<'a>>>,
+    /// Our own accounts. Their orders are excluded from evaluation.
+    pub own_accounts: HashSet<Cow<'a, str>>,
+    /// The balances of the offer owners used to exclude unfunded orders from evaluation.
+    pub owner_balances: OwnerBalances,
+    /// Decides which issuers are traded. Order books of excluded issuers aren't evaluated.
+    pub issuer_policy: IssuerPolicy<'a>,
+    /// The latest validated ledger. Defaults to the latest ledger any order book was updated from.
+    pub current_ledger_index: Option<u32>,
+    /// The maximum number of ledgers an order book may lag behind the current ledger. Older order
+    /// books are stale and excluded from evaluation.
+    pub max_age: Option<u32>,
+    /// The account trading the opportunities. If set, trades are sized to what it can fund.
+    pub trading_account: Option<AccountState<'a>>,
+    /// The thresholds profitable trades have to meet by their starting currency.
+    #[cfg(feature = "arbitrage")]
+    pub trade_filters: TradeFilters,
+    /// The position of every order book in `order_books` by its market.
+    index: HashMap<OrderBookKey, usize>,
+    metadata: HashMap<OrderBookKey, OrderBookMetadata<'a>>,
 }
 
 impl<'a> OrderBooks<'a> {
-    pub fn sort(&'a mut self) -> Result<()> {
+    pub fn new(order_books: Cow<'a, [OrderBook<'a>]>, liquidity_spread: f64) -> Self {
+        let mut order_books = Self {
+            order_books,
+            liquidity_spread,
+            subscribers: Vec::new(),
+            own_accounts: HashSet::new(),
+            owner_balances: OwnerBalances::default(),
+            issuer_policy: IssuerPolicy::default(),
+            current_ledger_index: None,
+            max_age: None,
+            trading_account: None,
+            #[cfg(feature = "arbitrage")]
+            trade_filters: TradeFilters::default(),
+            index: HashMap::new(),
+            metadata: HashMap::new(),
+        };
+        order_books.reindex();
+
+        order_books
+    }
+
+    /// Returns a view of the order books which aren't excluded, without our own orders, with every
+    /// order reduced to the amount its owner can fund and each side cut off at the exposure caps.
+    pub fn get_evaluable_order_books(&self) -> Result<OrderBooks<'a>> {
+        let order_books = self
+            .order_books
+            .iter()
+            .filter(|order_book| !self.is_excluded(order_book))
+            .map(|order_book| self.get_evaluable_order_book(order_book))
+            .collect::<Result<Vec<_>>>()?;
+        let mut evaluable_order_books = OrderBooks::new(order_books.into(), self.liquidity_spread);
+        evaluable_order_books.own_accounts = self.own_accounts.clone();
+        evaluable_order_books.owner_balances = self.owner_balances.clone();
+        evaluable_order_books.issuer_policy = self.issuer_policy.clone();
+        evaluable_order_books.current_ledger_index = self.current_ledger_index;
+        evaluable_order_books.max_age = self.max_age;
+        evaluable_order_books.trading_account = self.trading_account.clone();
+        #[cfg(feature = "arbitrage")]
+        {
+            evaluable_order_books.trade_filters = self.trade_filters.clone();
+        }
+        evaluable_order_books.metadata = self.metadata.clone();
+
+        Ok(evaluable_order_books)
+    }
+
+    /// Returns a copy of the order book without our own orders, with every order reduced to the
+    /// amount its owner can fund and each side cut off at the exposure caps. This is what
+    /// arbitrage detection trades against.
+    pub fn get_evaluable_order_book(&self, order_book: &OrderBook<'a>) -> Result<OrderBook<'a>> {
+        order_book
+            .without_own_orders(&self.own_accounts)?
+            .without_unfunded_orders(&self.owner_balances)?
+            .with_exposure_caps(&self.issuer_policy)
+    }
+
+    /// Returns a receiver for the updates of all order books.
+    pub fn subscribe(&mut self) -> Receiver<OrderBookUpdate<'a>> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+
+        receiver
+    }
+
+    /// Replaces the order book trading the same currencies in any orientation or adds it if there
+    /// is none. The changes are published to all subscribers. Order books in strict mode are
+    /// rejected if they are invalid.
+    pub fn update_order_book(&mut self, order_book: OrderBook<'a>) -> Result<Vec<OrderBookEvent>> {
+        if order_book.strict {
+            order_book.ensure_valid()?;
+        }
+        // Compare against the existing order book in the orientation of the new one.
+        let (bids, asks) = match self.get_order_book(&order_book.base, &order_book.counter) {
+            Some(existing) => existing.get_oriented_orders(
+                existing.is_flipped_for(&order_book.base, &order_book.counter) == Some(true),
+            )?,
+            None => (Vec::new(), Vec::new()),
+        };
+        let existing = OrderBook::new(
+            order_book.base.clone(),
+            order_book.counter.clone(),
+            bids,
+            asks,
+        );
+        let events = existing.diff(&order_book)?;
+        self.insert_order_book(order_book.clone())?;
+        self.publish(OrderBookUpdate {
+            base: order_book.base,
+            counter: order_book.counter,
+            events: events.clone(),
+        });
+
+        Ok(events)
+    }
+
+    /// Sends the update to all subscribers and drops the ones whose receiver is gone.
+    fn publish(&mut self, update: OrderBookUpdate<'a>) {
+        if update.events.is_empty() {
+            return;
+        }
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+    }
+
+    pub fn sort(&mut self) -> Result<()> {
         self.order_books
             .to_mut()
             .iter_mut()
             .try_for_each(|order_book| order_book.sort())
     }
 
-    pub fn get_liquid_order_books(&'a self) -> Result<Vec<&OrderBook<'_>>> {
+    /// Returns the liquid order books which are not excluded (see
+    /// [`OrderBooks::get_exclusion_reasons`]).
+    pub fn get_liquid_order_books(&self) -> Result<Vec<&OrderBook<'a>>> {
         let mut liquid_order_books = Vec::new();
         for order_book in self.order_books.iter() {
-            if order_book.is_liquid(self.liquidity_spread)? {
+            if !self.is_excluded(order_book) && order_book.is_liquid(self.liquidity_spread)? {
                 liquid_order_books.push(order_book);
             }
         }
@@ -32,7 +185,7 @@ impl<'a> OrderBooks<'a> {
         Ok(liquid_order_books)
     }
 
-    pub fn get_illiquid_order_books(&'a self) -> Result<Vec<&OrderBook<'_>>> {
+    pub fn get_illiquid_order_books(&self) -> Result<Vec<&OrderBook<'a>>> {
         let mut illiquid_order_books = Vec::new();
         for order_book in self.order_books.iter() {
             if !order_book.is_liquid(self.liquidity_spread)? {
@@ -42,11 +195,59 @@ impl<'a> OrderBooks<'a> {
 
         Ok(illiquid_order_books)
     }
+
+    /// Builds a composite order book merging all order books with the given currency codes
+    /// across all issuers. Order books with the flipped orientation are merged as well. Excluded
+    /// order books are left out.
+    pub fn get_composite_order_book(
+        &self,
+        base_currency_code: Cow<'a, str>,
+        counter_currency_code: Cow<'a, str>,
+        adjustments: &IssuerAdjustments<'_>,
+    ) -> Result<CompositeOrderBook<'a>> {
+        let mut composite_order_book =
+            CompositeOrderBook::new(base_currency_code, counter_currency_code);
+        for order_book in self.order_books.iter() {
+            if composite_order_book.is_order_book_for_composite(order_book)
+                && !self.is_excluded(order_book)
+            {
+                composite_order_book.merge_order_book(order_book, adjustments)?;
+            }
+        }
+        composite_order_book.sort();
+
+        Ok(composite_order_book)
+    }
+
+    /// Builds the order book for `base`/`counter` as rippled would execute it: the direct order
+    /// book merged with the order book synthesized from the `base`/XRP and XRP/`counter` legs.
+    pub fn get_synthetic_order_book(
+        &self,
+        base: Currency<'a>,
+        counter: Currency<'a>,
+    ) -> Result<SyntheticOrderBook<'a>> {
+        let mut synthetic_order_book = SyntheticOrderBook::new(base.clone(), counter.clone());
+        if let Some(order_book) = self.get_order_book(&base, &counter) {
+            synthetic_order_book.merge_direct_order_book(order_book)?;
+        }
+        // Auto-bridging only applies to IOU/IOU order books.
+        if !base.is_xrp() && !counter.is_xrp() {
+            let xrp = Currency::xrp();
+            if let (Some(base_leg), Some(counter_leg)) = (
+                self.get_order_book(&base, &xrp),
+                self.get_order_book(&xrp, &counter),
+            ) {
+                synthetic_order_book.merge_bridged_order_books(base_leg, counter_leg)?;
+            }
+        }
+
+        Ok(synthetic_order_book)
+    }
 }
 
 pub trait IsLiquid<'a> {
     /// Returns true if the order book is liquid determained based on the provided `liquidity_spread`.
-    fn is_liquid(&'a self, liquidity_spread: f64) -> Result<bool>;
+    fn is_liquid(&self, liquidity_spread: f64) -> Result<bool>;
 }
 
 pub trait Flip {
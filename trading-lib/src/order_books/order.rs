@@ -1,7 +1,8 @@
-use std::{cmp::Ordering, mem::swap};
+use std::{borrow::Cow, cmp::Ordering, mem::swap};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "xrpl")]
 use xrpl::models::{
     amount::Amount,
@@ -13,20 +14,29 @@ use xrpl::models::{
 
 use crate::models::currency::Currency;
 
-use super::Flip;
+use super::{exceptions::OrderBookException, Flip};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order<'a> {
     pub base: Currency<'a>,
     pub counter: Currency<'a>,
     pub base_quantity: Decimal,
     pub rate: Decimal,
+    /// Identifies the offer on the ledger (`<account>:<sequence>`), if the order is backed by one.
+    pub offer_id: Option<Cow<'a, str>>,
 }
 
-impl<'a> Flip<'a> for Order<'a> {
-    fn flip(&'a mut self) -> Result<()> {
-        self.rate = Decimal::from(1) / self.rate;
+impl Flip for Order<'_> {
+    /// Fails without changing the order if its rate is not positive.
+    fn flip(&mut self) -> Result<()> {
+        if self.rate <= Decimal::ZERO {
+            bail!(OrderBookException::InvalidOrder);
+        }
+        let rate = Decimal::ONE
+            .checked_div(self.rate)
+            .ok_or(OrderBookException::InvalidOrder)?;
         self.base_quantity = self.base_quantity * self.rate;
+        self.rate = rate;
         swap(&mut self.base, &mut self.counter);
 
         Ok(())
@@ -54,6 +64,13 @@ impl Ord for Order<'_> {
 }
 
 impl Order<'_> {
+    /// Returns the account owning the offer backing the order, taken from the offer id.
+    pub fn get_owner(&self) -> Option<&str> {
+        self.offer_id
+            .as_deref()
+            .and_then(|offer_id| offer_id.split(':').next())
+    }
+
     pub fn calculate_counter_quantity_after_fee(&self) -> Decimal {
         (self.base_quantity * self.rate)
             * Decimal::from_f32(1.0 - self.counter.transfer_fee).unwrap()
@@ -87,6 +104,7 @@ impl<'a> From<Offer<'a>> for Order<'a> {
             counter: Currency::from_xrpl(taker_gets_currency, None),
             base_quantity: taker_pays_amount,
             rate: taker_gets_amount / taker_pays_amount,
+            offer_id: Some(format!("{}:{}", offer.account, offer.sequence).into()),
         }
     }
 }
@@ -161,6 +179,7 @@ mod order_tests {
                 ),
                 base_quantity: Decimal::from(20),
                 rate: Decimal::from_f32(0.5).unwrap(),
+                offer_id: Some("r:0".into()),
             }
         );
     }
@@ -1,11 +1,11 @@
 use std::{
-    borrow::{Borrow, Cow},
+    borrow::Cow,
     mem::swap,
     sync::{Arc, Mutex, MutexGuard},
 };
 
 use anyhow::{bail, Result};
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 
 use crate::{models::currency::Currency, utils::anyhow_mutex};
 
@@ -37,56 +37,103 @@ pub struct OrderBook<'a> {
     pub counter: Currency<'a>,
     pub bids: Arc<Mutex<OrderBookSide<'a>>>,
     pub asks: Arc<Mutex<OrderBookSide<'a>>>,
+    /// If true, every mutation is validated and rejected if it leaves the order book invalid.
+    pub strict: bool,
 }
 
 impl Flip for OrderBook<'_> {
+    /// Turns the order book into the counter/base order book. In strict mode the order book is
+    /// validated afterwards and flipped back if it became invalid.
     fn flip(&mut self) -> Result<()> {
-        let mut bids = self.get_bids()?;
-        for order in bids.orders.to_mut() {
-            order.flip()?;
+        let (bids, asks) = self.get_oriented_orders(true)?;
+        let previous_bids = std::mem::replace(&mut self.get_bids()?.orders, bids.into());
+        let previous_asks = std::mem::replace(&mut self.get_asks()?.orders, asks.into());
+        swap(&mut self.base, &mut self.counter);
+        if self.strict {
+            if let Err(error) = self.ensure_valid() {
+                self.get_bids()?.orders = previous_bids;
+                self.get_asks()?.orders = previous_asks;
+                swap(&mut self.base, &mut self.counter);
+                return Err(error);
+            }
         }
-        // for order in self.get_asks()?.orders.to_mut() {
-        //     order.flip()?;
-        // }
-        // swap(&mut self.bids, &mut self.asks);
-        // swap(&mut self.base, &mut self.counter);
+
         Ok(())
     }
 }
 
 impl<'a> IsLiquid<'a> for OrderBook<'a> {
-    fn is_liquid(&'a self, liquidity_spread: f64) -> Result<bool> {
+    /// Order books with an empty side are never liquid.
+    fn is_liquid(&self, liquidity_spread: f64) -> Result<bool> {
         let order_book_spread = self.calculate_spread_pct()?;
 
-        Ok(order_book_spread <= liquidity_spread)
+        Ok(order_book_spread.is_some_and(|spread_pct| spread_pct <= liquidity_spread))
     }
 }
 
 impl<'a> OrderBook<'a> {
-    pub fn get_asks(&'a self) -> Result<MutexGuard<'_, OrderBookSide<'_>>> {
+    pub fn new(
+        base: Currency<'a>,
+        counter: Currency<'a>,
+        bids: Vec<Order<'a>>,
+        asks: Vec<Order<'a>>,
+    ) -> Self {
+        Self {
+            base,
+            counter,
+            bids: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Bids,
+                orders: bids.into(),
+            })),
+            asks: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Asks,
+                orders: asks.into(),
+            })),
+            strict: false,
+        }
+    }
+
+    /// Returns an error with the validation report if the order book is invalid.
+    pub fn ensure_valid(&self) -> Result<()> {
+        let report = self.validate()?;
+        if !report.is_valid() {
+            bail!(OrderBookException::ValidationFailed(report.to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_asks(&self) -> Result<MutexGuard<'_, OrderBookSide<'a>>> {
         anyhow_mutex(&self.asks)
     }
 
-    pub fn get_bids(&'a self) -> Result<MutexGuard<'_, OrderBookSide<'_>>> {
+    pub fn get_bids(&self) -> Result<MutexGuard<'_, OrderBookSide<'a>>> {
         anyhow_mutex(&self.bids)
     }
 
     /// Sorts the bids in descending order and the asks in ascending order.
-    pub fn sort(&'a mut self) -> Result<()> {
+    pub fn sort(&mut self) -> Result<()> {
         self.get_bids()?.orders.to_mut().sort_by(|a, b| b.cmp(a));
         self.get_asks()?.orders.to_mut().sort();
 
         Ok(())
     }
 
-    pub fn calculate_spread_pct(&'a self) -> Result<f64> {
+    /// Returns the spread between the first bid and the first ask relative to the first bid or
+    /// `None` if a side is empty or the first bid has no positive rate.
+    pub fn calculate_spread_pct(&self) -> Result<Option<f64>> {
         let bids = &self.get_bids()?.orders;
-        let best_bid = bids.first().unwrap();
         let asks = &self.get_asks()?.orders;
-        let best_ask = asks.first().unwrap();
+        let (Some(best_bid), Some(best_ask)) = (bids.first(), asks.first()) else {
+            return Ok(None);
+        };
+        if best_bid.rate <= Decimal::ZERO {
+            return Ok(None);
+        }
         let spread = best_ask.rate - best_bid.rate;
         let spread_pct = spread / best_bid.rate;
-        Ok(spread_pct.to_f64().unwrap())
+
+        Ok(spread_pct.to_f64())
     }
 
     pub fn determain_order_book_side_type(&self, order: &Order<'_>) -> Option<OrderBookSideType> {
@@ -99,21 +146,69 @@ impl<'a> OrderBook<'a> {
         }
     }
 
+    /// Returns `Some(false)` if the order book trades `base`/`counter`, `Some(true)` if it trades
+    /// `counter`/`base` and `None` if it trades other currencies.
+    pub fn is_flipped_for(&self, base: &Currency<'_>, counter: &Currency<'_>) -> Option<bool> {
+        if self.base.is_same_asset(base) && self.counter.is_same_asset(counter) {
+            Some(false)
+        } else if self.base.is_same_asset(counter) && self.counter.is_same_asset(base) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Returns copies of the bids and asks. If `flipped` is true the orders are returned in the
+    /// orientation of the flipped order book (counter/base), so the asks become the bids and the
+    /// bids become the asks.
+    pub fn get_oriented_orders(&self, flipped: bool) -> Result<(Vec<Order<'a>>, Vec<Order<'a>>)> {
+        let bids = self.get_bids()?.orders.to_vec();
+        let asks = self.get_asks()?.orders.to_vec();
+        if !flipped {
+            return Ok((bids, asks));
+        }
+        let mut flipped_bids = asks;
+        for order in flipped_bids.iter_mut() {
+            order.flip()?;
+        }
+        let mut flipped_asks = bids;
+        for order in flipped_asks.iter_mut() {
+            order.flip()?;
+        }
+
+        Ok((flipped_bids, flipped_asks))
+    }
+
     pub fn is_order_for_order_book(&self, order: &Order<'_>) -> bool {
         self.determain_order_book_side_type(order).is_some()
     }
 
+    /// Adds the order to the side it belongs to. In strict mode the order book is validated
+    /// afterwards and the order is removed again if it made the order book invalid.
     pub fn add_order<'b: 'a>(&mut self, mut order: Order<'b>) -> Result<()> {
         let order_book_side = self.determain_order_book_side_type(&order);
-        match order_book_side {
-            Some(OrderBookSideType::Bids) => self.bids.orders.lock().unwrap().to_mut().push(order),
+        let side = match order_book_side {
+            Some(OrderBookSideType::Bids) => Arc::clone(&self.bids),
             Some(OrderBookSideType::Asks) => {
-                order.flip();
-                self.asks.orders.lock().unwrap().to_mut().push(order)
+                order.flip()?;
+                Arc::clone(&self.asks)
             }
             None => bail!(OrderBookException::InvalidOrder),
+        };
+        let previous_orders = {
+            let mut side = anyhow_mutex(&side)?;
+            let previous_orders = side.orders.clone();
+            side.orders.to_mut().push(order);
+            previous_orders
+        };
+        self.sort()?;
+        if self.strict {
+            if let Err(error) = self.ensure_valid() {
+                anyhow_mutex(&side)?.orders = previous_orders;
+                return Err(error);
+            }
         }
-        self.sort();
+
         Ok(())
     }
 }
@@ -0,0 +1,179 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+
+use crate::models::currency::Currency;
+
+use super::{order_book::OrderBook, OrderBooks};
+
+/// Identifies the market of an order book. Both orientations of a currency pair (e.g. XRP/USD and
+/// USD/XRP) have the same key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrderBookKey {
+    /// The (currency code, issuer) of both currencies in ascending order.
+    assets: [(String, String); 2],
+}
+
+impl OrderBookKey {
+    pub fn new(base: &Currency<'_>, counter: &Currency<'_>) -> Self {
+        let mut assets = [
+            (base.currency_code.to_string(), base.issuer.to_string()),
+            (
+                counter.currency_code.to_string(),
+                counter.issuer.to_string(),
+            ),
+        ];
+        assets.sort();
+
+        Self { assets }
+    }
+
+    pub fn contains(&self, currency: &Currency<'_>) -> bool {
+        self.assets.iter().any(|(currency_code, issuer)| {
+            *currency_code == currency.currency_code && *issuer == currency.issuer
+        })
+    }
+}
+
+impl From<&OrderBook<'_>> for OrderBookKey {
+    fn from(order_book: &OrderBook<'_>) -> Self {
+        Self::new(&order_book.base, &order_book.counter)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBookMetadata<'a> {
+    /// The index of the ledger the order book was last updated from.
+    pub last_update_ledger_index: Option<u32>,
+    /// The close time in seconds of the ledger the order book was last updated from.
+    pub last_update_close_time: Option<u64>,
+    /// Where the order book comes from (e.g. a node url or a snapshot file).
+    pub source: Option<Cow<'a, str>>,
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Rebuilds the index of the registry. Only needed if `order_books` was modified directly
+    /// instead of using the registry functions.
+    pub fn reindex(&mut self) {
+        self.index = self
+            .order_books
+            .iter()
+            .enumerate()
+            .map(|(position, order_book)| (OrderBookKey::from(order_book), position))
+            .collect();
+        let index = &self.index;
+        self.metadata.retain(|key, _| index.contains_key(key));
+    }
+
+    pub(super) fn get_position(
+        &self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+    ) -> Option<usize> {
+        let key = OrderBookKey::new(base, counter);
+        let position = *self.index.get(&key)?;
+        // The index may be stale if `order_books` was modified directly.
+        match self.order_books.get(position) {
+            Some(order_book) if OrderBookKey::from(order_book) == key => Some(position),
+            _ => self
+                .order_books
+                .iter()
+                .position(|order_book| OrderBookKey::from(order_book) == key),
+        }
+    }
+
+    /// Returns the order book trading `base` and `counter` in any orientation.
+    pub fn get_order_book(
+        &self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+    ) -> Option<&OrderBook<'a>> {
+        self.get_position(base, counter)
+            .map(|position| &self.order_books[position])
+    }
+
+    pub fn contains_order_book(&self, base: &Currency<'_>, counter: &Currency<'_>) -> bool {
+        self.get_position(base, counter).is_some()
+    }
+
+    /// Adds the order book or replaces the order book of the same market, no matter its
+    /// orientation. Returns the replaced order book. Order books in strict mode are rejected if
+    /// they are invalid.
+    pub fn insert_order_book(
+        &mut self,
+        order_book: OrderBook<'a>,
+    ) -> Result<Option<OrderBook<'a>>> {
+        if order_book.strict {
+            order_book.ensure_valid()?;
+        }
+
+        Ok(
+            match self.get_position(&order_book.base, &order_book.counter) {
+                Some(position) => Some(std::mem::replace(
+                    &mut self.order_books.to_mut()[position],
+                    order_book,
+                )),
+                None => {
+                    let key = OrderBookKey::from(&order_book);
+                    self.order_books.to_mut().push(order_book);
+                    self.index.insert(key, self.order_books.len() - 1);
+                    None
+                }
+            },
+        )
+    }
+
+    /// Removes the order book trading `base` and `counter` in any orientation and its metadata.
+    pub fn remove_order_book(
+        &mut self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+    ) -> Option<OrderBook<'a>> {
+        let position = self.get_position(base, counter)?;
+        let removed = self.order_books.to_mut().swap_remove(position);
+        let key = OrderBookKey::from(&removed);
+        self.index.remove(&key);
+        self.metadata.remove(&key);
+        // The last order book took the place of the removed one.
+        if let Some(moved) = self.order_books.get(position) {
+            self.index.insert(OrderBookKey::from(moved), position);
+        }
+
+        Some(removed)
+    }
+
+    pub fn get_metadata(
+        &self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+    ) -> Option<&OrderBookMetadata<'a>> {
+        self.metadata.get(&OrderBookKey::new(base, counter))
+    }
+
+    /// Sets the metadata of the order book trading `base` and `counter`. Returns false if there
+    /// is no such order book.
+    pub fn set_metadata(
+        &mut self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+        metadata: OrderBookMetadata<'a>,
+    ) -> bool {
+        if !self.contains_order_book(base, counter) {
+            return false;
+        }
+        self.metadata
+            .insert(OrderBookKey::new(base, counter), metadata);
+
+        true
+    }
+
+    /// Returns all order books trading the currency, either as base or as counter currency.
+    pub fn get_order_books_with_currency<'b>(
+        &'b self,
+        currency: &'b Currency<'_>,
+    ) -> impl Iterator<Item = &'b OrderBook<'a>> + 'b {
+        self.order_books.iter().filter(move |order_book| {
+            order_book.base.is_same_asset(currency) || order_book.counter.is_same_asset(currency)
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use std::{cmp::Ordering, collections::HashSet, fmt::Write};
+
+use anyhow::Result;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Serialize;
+
+use crate::models::currency::Currency;
+
+use super::{order_book::OrderBook, OrderBooks};
+
+/// The distances from the mid rate in percent the depth is reported at.
+pub const DEPTH_LEVELS_PCT: [u32; 3] = [1, 2, 5];
+
+/// The base quantity offered within `pct` percent of the mid rate on each side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarketDepth {
+    pub pct: u32,
+    pub bid_quantity: Decimal,
+    pub ask_quantity: Decimal,
+}
+
+impl MarketDepth {
+    pub fn get_total_quantity(&self) -> Decimal {
+        self.bid_quantity + self.ask_quantity
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderBookReport<'a> {
+    pub base: Currency<'a>,
+    pub counter: Currency<'a>,
+    /// `None` if a side is empty.
+    pub spread_pct: Option<f64>,
+    /// One entry per [`DEPTH_LEVELS_PCT`]. Empty if a side is empty.
+    pub depths: Vec<MarketDepth>,
+    pub order_count: usize,
+    /// The number of distinct offer owners. Orders without owner are not counted.
+    pub maker_count: usize,
+    pub base_transfer_fee: f32,
+    pub counter_transfer_fee: f32,
+    pub is_liquid: bool,
+}
+
+impl<'a> OrderBookReport<'a> {
+    pub fn new(order_book: &OrderBook<'a>, liquidity_spread: f64) -> Result<Self> {
+        let bids = order_book.get_bids()?;
+        let asks = order_book.get_asks()?;
+        let best_bid_rate = bids.orders.iter().map(|order| order.rate).max();
+        let best_ask_rate = asks.orders.iter().map(|order| order.rate).min();
+        let mut spread_pct = None;
+        let mut depths = Vec::new();
+        if let (Some(best_bid_rate), Some(best_ask_rate)) = (best_bid_rate, best_ask_rate) {
+            spread_pct = ((best_ask_rate - best_bid_rate) / best_bid_rate).to_f64();
+            let mid_rate = (best_bid_rate + best_ask_rate) / Decimal::TWO;
+            for pct in DEPTH_LEVELS_PCT {
+                let distance = mid_rate * Decimal::from(pct) / Decimal::ONE_HUNDRED;
+                depths.push(MarketDepth {
+                    pct,
+                    bid_quantity: bids
+                        .orders
+                        .iter()
+                        .filter(|order| order.rate >= mid_rate - distance)
+                        .map(|order| order.base_quantity)
+                        .sum(),
+                    ask_quantity: asks
+                        .orders
+                        .iter()
+                        .filter(|order| order.rate <= mid_rate + distance)
+                        .map(|order| order.base_quantity)
+                        .sum(),
+                });
+            }
+        }
+        let makers: HashSet<&str> = bids
+            .orders
+            .iter()
+            .chain(asks.orders.iter())
+            .filter_map(|order| order.get_owner())
+            .collect();
+
+        Ok(Self {
+            base: order_book.base.clone(),
+            counter: order_book.counter.clone(),
+            spread_pct,
+            depths,
+            order_count: bids.orders.len() + asks.orders.len(),
+            maker_count: makers.len(),
+            base_transfer_fee: order_book.base.transfer_fee,
+            counter_transfer_fee: order_book.counter.transfer_fee,
+            is_liquid: spread_pct.is_some_and(|spread_pct| spread_pct <= liquidity_spread),
+        })
+    }
+
+    /// Returns the depth on both sides within `pct` percent of the mid rate.
+    pub fn get_depth(&self, pct: u32) -> Option<&MarketDepth> {
+        self.depths.iter().find(|depth| depth.pct == pct)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityReportSortKey {
+    /// Tightest spread first. Order books without spread come last.
+    Spread,
+    /// Deepest order book within the percentage first.
+    Depth(u32),
+    /// Most orders first.
+    OrderCount,
+    /// Most makers first.
+    MakerCount,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LiquidityReport<'a> {
+    pub order_books: Vec<OrderBookReport<'a>>,
+}
+
+impl LiquidityReport<'_> {
+    pub fn sort(&mut self, sort_key: LiquidityReportSortKey) {
+        match sort_key {
+            LiquidityReportSortKey::Spread => {
+                self.order_books
+                    .sort_by(|a, b| match (a.spread_pct, b.spread_pct) {
+                        (Some(a), Some(b)) => a.total_cmp(&b),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    })
+            }
+            LiquidityReportSortKey::Depth(pct) => self.order_books.sort_by_key(|report| {
+                std::cmp::Reverse(
+                    report
+                        .get_depth(pct)
+                        .map(MarketDepth::get_total_quantity)
+                        .unwrap_or_default(),
+                )
+            }),
+            LiquidityReportSortKey::OrderCount => self
+                .order_books
+                .sort_by_key(|report| std::cmp::Reverse(report.order_count)),
+            LiquidityReportSortKey::MakerCount => self
+                .order_books
+                .sort_by_key(|report| std::cmp::Reverse(report.maker_count)),
+        }
+    }
+
+    fn get_header() -> Vec<String> {
+        let mut header = vec![
+            "base".to_string(),
+            "counter".to_string(),
+            "spread_pct".to_string(),
+        ];
+        for pct in DEPTH_LEVELS_PCT {
+            header.push(format!("bid_depth_{pct}pct"));
+            header.push(format!("ask_depth_{pct}pct"));
+        }
+        header.extend(
+            [
+                "order_count",
+                "maker_count",
+                "base_transfer_fee",
+                "counter_transfer_fee",
+                "liquid",
+            ]
+            .map(String::from),
+        );
+
+        header
+    }
+
+    fn get_rows(&self) -> Vec<Vec<String>> {
+        self.order_books
+            .iter()
+            .map(|report| {
+                let mut row = vec![
+                    report.base.get_label(),
+                    report.counter.get_label(),
+                    report
+                        .spread_pct
+                        .map(|spread_pct| format!("{spread_pct:.6}"))
+                        .unwrap_or_default(),
+                ];
+                for pct in DEPTH_LEVELS_PCT {
+                    match report.get_depth(pct) {
+                        Some(depth) => {
+                            row.push(depth.bid_quantity.normalize().to_string());
+                            row.push(depth.ask_quantity.normalize().to_string());
+                        }
+                        None => row.extend([String::new(), String::new()]),
+                    }
+                }
+                row.extend([
+                    report.order_count.to_string(),
+                    report.maker_count.to_string(),
+                    report.base_transfer_fee.to_string(),
+                    report.counter_transfer_fee.to_string(),
+                    report.is_liquid.to_string(),
+                ]);
+
+                row
+            })
+            .collect()
+    }
+
+    /// Renders the report as a plain text table with aligned columns.
+    pub fn to_table(&self) -> String {
+        let header = Self::get_header();
+        let rows = self.get_rows();
+        let widths: Vec<usize> = header
+            .iter()
+            .enumerate()
+            .map(|(column, title)| {
+                rows.iter()
+                    .map(|row| row[column].len())
+                    .chain([title.len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let mut table = String::new();
+        for row in [header].iter().chain(rows.iter()) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            let _ = writeln!(table, "{}", cells.join(" | ").trim_end());
+        }
+
+        table
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in [Self::get_header()].iter().chain(self.get_rows().iter()) {
+            let _ = writeln!(csv, "{}", row.join(","));
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Builds the liquidity report of all order books.
+    pub fn get_liquidity_report(&self) -> Result<LiquidityReport<'a>> {
+        let order_books = self
+            .order_books
+            .iter()
+            .map(|order_book| OrderBookReport::new(order_book, self.liquidity_spread))
+            .collect::<Result<_>>()?;
+
+        Ok(LiquidityReport { order_books })
+    }
+}
@@ -0,0 +1,194 @@
+use std::{borrow::Cow, cmp::Reverse};
+
+use anyhow::Result;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+
+use crate::models::currency::Currency;
+
+use super::{order::Order, OrderBooks};
+
+/// Trust line qualities are given in units per billion.
+const QUALITY_ONE: u32 = 1_000_000_000;
+
+/// A trust line of an account holding a token, e.g. taken from `account_lines`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RipplingTrustLine<'a> {
+    /// The account holding the token.
+    pub account: Cow<'a, str>,
+    /// The token with its issuer and transfer fee.
+    pub currency: Currency<'a>,
+    pub balance: Decimal,
+    pub limit: Decimal,
+    /// Incoming amounts are valued at `quality_in` per billion. Zero means at face value.
+    pub quality_in: u32,
+    /// Outgoing amounts are valued at `quality_out` per billion. Zero means at face value.
+    pub quality_out: u32,
+    /// Rippling through the account is disabled on this trust line.
+    pub no_ripple: bool,
+}
+
+impl RipplingTrustLine<'_> {
+    /// Returns how much more of the token the account can receive.
+    pub fn get_headroom(&self) -> Decimal {
+        (self.limit - self.balance).max(Decimal::ZERO)
+    }
+}
+
+/// A conversion of one token into another one with the same currency code by rippling through an
+/// account holding both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RipplingRoute<'a> {
+    pub from: Currency<'a>,
+    pub to: Currency<'a>,
+    /// The account rippled through.
+    pub intermediary: Cow<'a, str>,
+    /// The amount of `to` received for one `from` after the transfer fees and qualities.
+    pub rate: Decimal,
+    /// The amount of `from` that can be rippled at `rate`.
+    pub capacity: Decimal,
+}
+
+impl<'a> RipplingRoute<'a> {
+    /// Returns the route as an order from the perspective of the trader: `from` is given and
+    /// `to` received. The transfer fee of `to` is left out of the rate as it is applied to every
+    /// order.
+    pub fn to_order(&self) -> Order<'a> {
+        Order {
+            base: self.from.clone(),
+            counter: self.to.clone(),
+            base_quantity: self.capacity,
+            rate: self.rate / get_fee_multiplier(&self.to),
+            offer_id: None,
+        }
+    }
+}
+
+/// A rippling route converting at a better rate than the order books.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RipplingOpportunity<'a> {
+    pub route: RipplingRoute<'a>,
+    /// The best rate of the order books, `None` if they don't convert between the tokens.
+    pub order_book_rate: Option<Decimal>,
+}
+
+/// The trust lines rippling can go through. Rippling is invisible to the order books: an account
+/// holding the tokens of two issuers with the same currency code converts them 1:1, adjusted by
+/// the transfer fees of the issuers and the qualities of its trust lines.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RipplingNetwork<'a> {
+    pub trust_lines: Vec<RipplingTrustLine<'a>>,
+}
+
+impl<'a> RipplingNetwork<'a> {
+    pub fn new(trust_lines: Vec<RipplingTrustLine<'a>>) -> Self {
+        Self { trust_lines }
+    }
+
+    /// Returns the tokens held in the network, each once.
+    pub fn get_currencies(&self) -> Vec<Currency<'a>> {
+        let mut currencies: Vec<Currency<'a>> = Vec::new();
+        for trust_line in self.trust_lines.iter() {
+            if !currencies
+                .iter()
+                .any(|currency| currency.is_same_asset(&trust_line.currency))
+            {
+                currencies.push(trust_line.currency.clone());
+            }
+        }
+
+        currencies
+    }
+
+    /// Returns the routes converting `from` into `to`, best rate first. The intermediary receives
+    /// `from` up to the limit of its trust line and gives `to` up to its balance.
+    pub fn get_routes(&self, from: &Currency<'_>, to: &Currency<'_>) -> Vec<RipplingRoute<'a>> {
+        if !from.is_same_currency(to) || from.is_same_asset(to) {
+            return Vec::new();
+        }
+        let mut routes = Vec::new();
+        for incoming in self.trust_lines.iter() {
+            if incoming.no_ripple || !incoming.currency.is_same_asset(from) {
+                continue;
+            }
+            let Some(outgoing) = self.trust_lines.iter().find(|outgoing| {
+                outgoing.account == incoming.account && outgoing.currency.is_same_asset(to)
+            }) else {
+                continue;
+            };
+            if outgoing.no_ripple || outgoing.balance <= Decimal::ZERO {
+                continue;
+            }
+            // The amount leaving the intermediary for one `from` given.
+            let outgoing_rate = get_fee_multiplier(&incoming.currency)
+                * get_quality(incoming.quality_in)
+                / get_quality(outgoing.quality_out);
+            let capacity = (incoming.get_headroom() / get_fee_multiplier(&incoming.currency))
+                .min(outgoing.balance / outgoing_rate);
+            if capacity <= Decimal::ZERO {
+                continue;
+            }
+            routes.push(RipplingRoute {
+                from: incoming.currency.clone(),
+                to: outgoing.currency.clone(),
+                intermediary: incoming.account.clone(),
+                rate: outgoing_rate * get_fee_multiplier(&outgoing.currency),
+                capacity,
+            });
+        }
+        routes.sort_by_key(|route| Reverse(route.rate));
+
+        routes
+    }
+
+    /// Returns the routes of the network as orders (see [`RipplingRoute::to_order`]), best first.
+    pub fn get_orders(&self, from: &Currency<'_>, to: &Currency<'_>) -> Vec<Order<'a>> {
+        self.get_routes(from, to)
+            .iter()
+            .map(RipplingRoute::to_order)
+            .collect()
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Returns the best rippling route between every pair of tokens with the same currency code
+    /// which beats the best path of at most `max_hops` hops through the evaluable order books.
+    pub fn get_rippling_opportunities(
+        &self,
+        network: &RipplingNetwork<'a>,
+        max_hops: usize,
+    ) -> Result<Vec<RipplingOpportunity<'a>>> {
+        let graph = self.get_evaluable_order_books()?.get_currency_graph()?;
+        let currencies = network.get_currencies();
+        let mut opportunities = Vec::new();
+        for from in currencies.iter() {
+            for to in currencies.iter() {
+                let Some(route) = network.get_routes(from, to).into_iter().next() else {
+                    continue;
+                };
+                let order_book_rate = graph
+                    .get_best_rate_path(from, to, max_hops)
+                    .map(|path| path.rate);
+                if order_book_rate.is_none_or(|order_book_rate| route.rate > order_book_rate) {
+                    opportunities.push(RipplingOpportunity {
+                        route,
+                        order_book_rate,
+                    });
+                }
+            }
+        }
+
+        Ok(opportunities)
+    }
+}
+
+fn get_quality(quality: u32) -> Decimal {
+    match quality {
+        0 => Decimal::ONE,
+        quality => Decimal::from(quality) / Decimal::from(QUALITY_ONE),
+    }
+}
+
+fn get_fee_multiplier(currency: &Currency<'_>) -> Decimal {
+    Decimal::ONE - Decimal::from_f32(currency.transfer_fee).unwrap_or_default()
+}
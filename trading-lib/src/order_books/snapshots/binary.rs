@@ -0,0 +1,231 @@
+//! The compact binary encoding of order book snapshots.
+//!
+//! A stream starts with the magic bytes and the version, followed by the snapshots. All integers
+//! and floats are little endian, strings are prefixed with their length as `u16` and decimals are
+//! stored as their lossless 16 byte representation.
+
+use std::{
+    borrow::Cow,
+    io::{BufRead, Read, Write},
+};
+
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+
+use crate::{models::currency::Currency, order_books::order::Order};
+
+use super::{
+    exceptions::SnapshotException, OrderBookSnapshot, OrderBooksSnapshot, SNAPSHOT_VERSION,
+};
+
+pub const MAGIC: &[u8; 4] = b"XOBS";
+
+/// The order has the currencies of the order book.
+const ORDER_SAME_CURRENCIES: u8 = 0;
+/// The order has the currencies of the flipped order book.
+const ORDER_FLIPPED_CURRENCIES: u8 = 1;
+/// The order has other currencies, which are written explicitly.
+const ORDER_OTHER_CURRENCIES: u8 = 2;
+
+pub fn write_header<W: Write>(writer: &mut W) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+
+    Ok(())
+}
+
+pub fn read_header<R: Read>(reader: &mut R) -> Result<()> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        bail!(SnapshotException::InvalidMagic);
+    }
+    let version = read_u16(reader)?;
+    if version != SNAPSHOT_VERSION {
+        bail!(SnapshotException::UnsupportedVersion {
+            found: version,
+            supported: SNAPSHOT_VERSION,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn write_snapshot<W: Write>(writer: &mut W, snapshot: &OrderBooksSnapshot<'_>) -> Result<()> {
+    writer.write_all(&snapshot.ledger_index.to_le_bytes())?;
+    writer.write_all(&snapshot.timestamp.to_le_bytes())?;
+    writer.write_all(&snapshot.liquidity_spread.to_le_bytes())?;
+    write_len(writer, snapshot.order_books.len())?;
+    for order_book in snapshot.order_books.iter() {
+        write_currency(writer, &order_book.base)?;
+        write_currency(writer, &order_book.counter)?;
+        for orders in [&order_book.bids, &order_book.asks] {
+            write_len(writer, orders.len())?;
+            for order in orders.iter() {
+                write_order(writer, order, order_book)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the next snapshot or returns `None` if the end of the stream is reached.
+pub fn read_snapshot<R: BufRead>(reader: &mut R) -> Result<Option<OrderBooksSnapshot<'static>>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    let ledger_index = read_u32(reader)?;
+    let timestamp = u64::from_le_bytes(read_array(reader)?);
+    let liquidity_spread = f64::from_le_bytes(read_array(reader)?);
+    let num_order_books = read_u32(reader)?;
+    let mut order_books = Vec::new();
+    for _ in 0..num_order_books {
+        let mut order_book = OrderBookSnapshot {
+            base: read_currency(reader)?,
+            counter: read_currency(reader)?,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        };
+        let bids = read_orders(reader, &order_book)?;
+        let asks = read_orders(reader, &order_book)?;
+        order_book.bids = bids;
+        order_book.asks = asks;
+        order_books.push(order_book);
+    }
+
+    Ok(Some(OrderBooksSnapshot {
+        ledger_index,
+        timestamp,
+        liquidity_spread,
+        order_books,
+    }))
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| SnapshotException::InvalidEncoding("Too many entries".into()))?;
+    writer.write_all(&len.to_le_bytes())?;
+
+    Ok(())
+}
+
+fn write_str<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    let len = u16::try_from(value.len())
+        .map_err(|_| SnapshotException::InvalidEncoding(format!("String too long: {value}")))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(value.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_optional_str<W: Write>(writer: &mut W, value: Option<&str>) -> Result<()> {
+    match value {
+        Some(value) => {
+            writer.write_all(&[1])?;
+            write_str(writer, value)
+        }
+        None => Ok(writer.write_all(&[0])?),
+    }
+}
+
+fn write_currency<W: Write>(writer: &mut W, currency: &Currency<'_>) -> Result<()> {
+    write_str(writer, &currency.currency_code)?;
+    write_str(writer, &currency.issuer)?;
+    writer.write_all(&currency.transfer_fee.to_le_bytes())?;
+
+    Ok(())
+}
+
+fn write_order<W: Write>(
+    writer: &mut W,
+    order: &Order<'_>,
+    order_book: &OrderBookSnapshot<'_>,
+) -> Result<()> {
+    if order.base == order_book.base && order.counter == order_book.counter {
+        writer.write_all(&[ORDER_SAME_CURRENCIES])?;
+    } else if order.base == order_book.counter && order.counter == order_book.base {
+        writer.write_all(&[ORDER_FLIPPED_CURRENCIES])?;
+    } else {
+        writer.write_all(&[ORDER_OTHER_CURRENCIES])?;
+        write_currency(writer, &order.base)?;
+        write_currency(writer, &order.counter)?;
+    }
+    writer.write_all(&order.base_quantity.serialize())?;
+    writer.write_all(&order.rate.serialize())?;
+    write_optional_str(writer, order.offer_id.as_deref())?;
+
+    Ok(())
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> Result<u16> {
+    Ok(u16::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_str<R: Read>(reader: &mut R) -> Result<Cow<'static, str>> {
+    let len = read_u16(reader)?;
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    let value = String::from_utf8(bytes)
+        .map_err(|error| SnapshotException::InvalidEncoding(error.to_string()))?;
+
+    Ok(value.into())
+}
+
+fn read_optional_str<R: Read>(reader: &mut R) -> Result<Option<Cow<'static, str>>> {
+    let [is_some] = read_array(reader)?;
+    match is_some {
+        0 => Ok(None),
+        1 => Ok(Some(read_str(reader)?)),
+        other => bail!(SnapshotException::InvalidEncoding(format!(
+            "Unknown optional flag: {other}"
+        ))),
+    }
+}
+
+fn read_currency<R: Read>(reader: &mut R) -> Result<Currency<'static>> {
+    Ok(Currency {
+        currency_code: read_str(reader)?,
+        issuer: read_str(reader)?,
+        transfer_fee: f32::from_le_bytes(read_array(reader)?),
+    })
+}
+
+fn read_orders<R: Read>(
+    reader: &mut R,
+    order_book: &OrderBookSnapshot<'static>,
+) -> Result<Vec<Order<'static>>> {
+    let num_orders = read_u32(reader)?;
+    let mut orders = Vec::new();
+    for _ in 0..num_orders {
+        let [currencies] = read_array(reader)?;
+        let (base, counter) = match currencies {
+            ORDER_SAME_CURRENCIES => (order_book.base.clone(), order_book.counter.clone()),
+            ORDER_FLIPPED_CURRENCIES => (order_book.counter.clone(), order_book.base.clone()),
+            ORDER_OTHER_CURRENCIES => (read_currency(reader)?, read_currency(reader)?),
+            other => bail!(SnapshotException::InvalidEncoding(format!(
+                "Unknown order currencies flag: {other}"
+            ))),
+        };
+        orders.push(Order {
+            base,
+            counter,
+            base_quantity: Decimal::deserialize(read_array(reader)?),
+            rate: Decimal::deserialize(read_array(reader)?),
+            offer_id: read_optional_str(reader)?,
+        });
+    }
+
+    Ok(orders)
+}
@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SnapshotException {
+    #[error("Invalid snapshot file. The magic bytes don't match.")]
+    InvalidMagic,
+    #[error("Unsupported snapshot version {found}. Supported version is {supported}.")]
+    UnsupportedVersion { found: u16, supported: u16 },
+    #[error("Invalid snapshot encoding: {0}")]
+    InvalidEncoding(String),
+}
@@ -0,0 +1,211 @@
+pub mod binary;
+pub mod exceptions;
+
+use std::io::{BufRead, Write};
+
+use anyhow::{bail, Result};
+use exceptions::SnapshotException;
+use serde::{Deserialize, Serialize};
+
+use crate::models::currency::Currency;
+
+use super::{order::Order, order_book::OrderBook, OrderBooks};
+
+/// The version of the snapshot format. It has to be increased whenever the format changes.
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotEncoding {
+    /// Compact binary encoding. Decimals are stored as their 16 byte representation.
+    Binary,
+    /// One JSON object per line. Decimals are stored as strings.
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookSnapshot<'a> {
+    pub base: Currency<'a>,
+    pub counter: Currency<'a>,
+    pub bids: Vec<Order<'a>>,
+    pub asks: Vec<Order<'a>>,
+}
+
+impl<'a> TryFrom<&OrderBook<'a>> for OrderBookSnapshot<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(order_book: &OrderBook<'a>) -> Result<Self> {
+        Ok(Self {
+            base: order_book.base.clone(),
+            counter: order_book.counter.clone(),
+            bids: order_book.get_bids()?.orders.to_vec(),
+            asks: order_book.get_asks()?.orders.to_vec(),
+        })
+    }
+}
+
+impl<'a> From<OrderBookSnapshot<'a>> for OrderBook<'a> {
+    fn from(snapshot: OrderBookSnapshot<'a>) -> Self {
+        OrderBook::new(
+            snapshot.base,
+            snapshot.counter,
+            snapshot.bids,
+            snapshot.asks,
+        )
+    }
+}
+
+/// The state of all order books at a ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBooksSnapshot<'a> {
+    pub ledger_index: u32,
+    /// The close time of the ledger in seconds.
+    pub timestamp: u64,
+    pub liquidity_spread: f64,
+    pub order_books: Vec<OrderBookSnapshot<'a>>,
+}
+
+impl<'a> OrderBooksSnapshot<'a> {
+    pub fn new(order_books: &OrderBooks<'a>, ledger_index: u32, timestamp: u64) -> Result<Self> {
+        Ok(Self {
+            ledger_index,
+            timestamp,
+            liquidity_spread: order_books.liquidity_spread,
+            order_books: order_books
+                .order_books
+                .iter()
+                .map(OrderBookSnapshot::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Restores the order books, each stamped with the ledger of the snapshot.
+    pub fn into_order_books(self) -> OrderBooks<'a> {
+        let order_books: Vec<OrderBook<'a>> =
+            self.order_books.into_iter().map(OrderBook::from).collect();
+        let mut restored = OrderBooks::new(order_books.clone().into(), self.liquidity_spread);
+        for order_book in order_books.iter() {
+            restored.stamp_order_book(
+                &order_book.base,
+                &order_book.counter,
+                self.ledger_index,
+                self.timestamp,
+            );
+        }
+
+        restored
+    }
+}
+
+/// A line of the JSON-lines encoding. Every line carries its own version.
+#[derive(Serialize, Deserialize)]
+struct JsonLinesRecord<'a> {
+    version: u16,
+    #[serde(flatten)]
+    snapshot: OrderBooksSnapshot<'a>,
+}
+
+/// Writes a stream of snapshots.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    encoding: SnapshotEncoding,
+    is_header_written: bool,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    pub fn new(writer: W, encoding: SnapshotEncoding) -> Self {
+        Self {
+            writer,
+            encoding,
+            is_header_written: false,
+        }
+    }
+
+    pub fn write_snapshot(&mut self, snapshot: &OrderBooksSnapshot<'_>) -> Result<()> {
+        match self.encoding {
+            SnapshotEncoding::Binary => {
+                if !self.is_header_written {
+                    binary::write_header(&mut self.writer)?;
+                    self.is_header_written = true;
+                }
+                binary::write_snapshot(&mut self.writer, snapshot)
+            }
+            SnapshotEncoding::JsonLines => {
+                let record = JsonLinesRecord {
+                    version: SNAPSHOT_VERSION,
+                    snapshot: snapshot.clone(),
+                };
+                serde_json::to_writer(&mut self.writer, &record)?;
+                self.writer.write_all(b"\n")?;
+
+                Ok(())
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads a stream of snapshots written by a [`SnapshotWriter`].
+pub struct SnapshotReader<R: BufRead> {
+    reader: R,
+    encoding: SnapshotEncoding,
+    is_header_read: bool,
+}
+
+impl<R: BufRead> SnapshotReader<R> {
+    pub fn new(reader: R, encoding: SnapshotEncoding) -> Self {
+        Self {
+            reader,
+            encoding,
+            is_header_read: false,
+        }
+    }
+
+    /// Returns the next snapshot or `None` if the end of the stream is reached.
+    pub fn read_snapshot(&mut self) -> Result<Option<OrderBooksSnapshot<'static>>> {
+        match self.encoding {
+            SnapshotEncoding::Binary => {
+                if !self.is_header_read {
+                    binary::read_header(&mut self.reader)?;
+                    self.is_header_read = true;
+                }
+                binary::read_snapshot(&mut self.reader)
+            }
+            SnapshotEncoding::JsonLines => {
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    if self.reader.read_line(&mut line)? == 0 {
+                        return Ok(None);
+                    }
+                    if !line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let record: JsonLinesRecord<'static> = serde_json::from_str(&line)?;
+                if record.version != SNAPSHOT_VERSION {
+                    bail!(SnapshotException::UnsupportedVersion {
+                        found: record.version,
+                        supported: SNAPSHOT_VERSION,
+                    });
+                }
+
+                Ok(Some(record.snapshot))
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SnapshotReader<R> {
+    type Item = Result<OrderBooksSnapshot<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_snapshot().transpose()
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::{bail, Result};
+
+use crate::models::currency::Currency;
+
+use super::{
+    events::OrderBookEvent,
+    exceptions::OrderBookException,
+    order_book::OrderBook,
+    registry::OrderBookMetadata,
+    snapshots::{OrderBookSnapshot, OrderBooksSnapshot},
+    OrderBooks,
+};
+
+impl<'a> OrderBooks<'a> {
+    /// Stamps the order book with the ledger it reflects and advances the current ledger. Returns
+    /// false if there is no such order book.
+    pub fn stamp_order_book(
+        &mut self,
+        base: &Currency<'_>,
+        counter: &Currency<'_>,
+        ledger_index: u32,
+        close_time: u64,
+    ) -> bool {
+        let metadata = OrderBookMetadata {
+            last_update_ledger_index: Some(ledger_index),
+            last_update_close_time: Some(close_time),
+            ..self
+                .get_metadata(base, counter)
+                .cloned()
+                .unwrap_or_default()
+        };
+        if !self.set_metadata(base, counter, metadata) {
+            return false;
+        }
+        if self
+            .current_ledger_index
+            .is_none_or(|current_ledger_index| current_ledger_index < ledger_index)
+        {
+            self.current_ledger_index = Some(ledger_index);
+        }
+
+        true
+    }
+
+    /// Updates the order book like [`OrderBooks::update_order_book`] and stamps it with the
+    /// ledger it was fetched from.
+    pub fn update_order_book_at_ledger(
+        &mut self,
+        order_book: OrderBook<'a>,
+        ledger_index: u32,
+        close_time: u64,
+    ) -> Result<Vec<OrderBookEvent>> {
+        let (base, counter) = (order_book.base.clone(), order_book.counter.clone());
+        let events = self.update_order_book(order_book)?;
+        self.stamp_order_book(&base, &counter, ledger_index, close_time);
+
+        Ok(events)
+    }
+
+    /// Returns the number of ledgers the order book lags behind the current ledger or `None` if
+    /// either of them is unknown.
+    pub fn get_age(&self, order_book: &OrderBook<'_>) -> Option<u32> {
+        let ledger_index = self
+            .get_metadata(&order_book.base, &order_book.counter)?
+            .last_update_ledger_index?;
+
+        Some(self.current_ledger_index?.saturating_sub(ledger_index))
+    }
+
+    /// Returns true if the order book is older than `max_age`. Order books without a ledger stamp
+    /// are stale as soon as a `max_age` is configured.
+    pub fn is_stale(&self, order_book: &OrderBook<'_>) -> bool {
+        match self.max_age {
+            Some(max_age) => self.get_age(order_book).is_none_or(|age| age > max_age),
+            None => false,
+        }
+    }
+
+    /// Takes a snapshot of the order books reflecting exactly the given ledger. Order books
+    /// stamped with another ledger or without a stamp are left out.
+    pub fn get_ledger_snapshot(&self, ledger_index: u32) -> Result<OrderBooksSnapshot<'a>> {
+        let mut close_time = None;
+        let mut order_books = Vec::new();
+        for order_book in self.order_books.iter() {
+            let Some(metadata) = self.get_metadata(&order_book.base, &order_book.counter) else {
+                continue;
+            };
+            if metadata.last_update_ledger_index == Some(ledger_index) {
+                close_time = close_time.or(metadata.last_update_close_time);
+                order_books.push(OrderBookSnapshot::try_from(order_book)?);
+            }
+        }
+        if order_books.is_empty() {
+            bail!(OrderBookException::NoOrderBooksAtLedger(ledger_index));
+        }
+
+        Ok(OrderBooksSnapshot {
+            ledger_index,
+            timestamp: close_time.unwrap_or_default(),
+            liquidity_spread: self.liquidity_spread,
+            order_books,
+        })
+    }
+}
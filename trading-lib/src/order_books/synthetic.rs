@@ -0,0 +1,178 @@
+use std::cmp::Reverse;
+
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+
+use crate::models::currency::Currency;
+
+use super::{exceptions::OrderBookException, order::Order, order_book::OrderBook};
+
+/// Remaining quantities below this threshold are treated as consumed, so rounding errors of the
+/// rate conversions don't produce dust levels.
+const DUST_QUANTITY: Decimal = Decimal::from_parts(1, 0, 0, false, 15);
+
+/// Where the liquidity of a synthetic level comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelSource {
+    /// The level is an order of the direct order book (e.g. USD.A/EUR.B).
+    Direct,
+    /// The level is synthesized from two orders bridged through XRP (USD.A/XRP and XRP/EUR.B).
+    Bridged,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntheticLevel<'a> {
+    pub order: Order<'a>,
+    pub source: LevelSource,
+}
+
+/// An order book combining the direct order book with the order book bridged through XRP, the
+/// same way rippled auto-bridges IOU/IOU offers. At every level the better of both is consumed.
+#[derive(Debug, Clone)]
+pub struct SyntheticOrderBook<'a> {
+    pub base: Currency<'a>,
+    pub counter: Currency<'a>,
+    /// Sorted in descending order.
+    pub bids: Vec<SyntheticLevel<'a>>,
+    /// Sorted in ascending order.
+    pub asks: Vec<SyntheticLevel<'a>>,
+}
+
+impl<'a> SyntheticOrderBook<'a> {
+    pub fn new(base: Currency<'a>, counter: Currency<'a>) -> Self {
+        Self {
+            base,
+            counter,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    pub fn get_best_bid(&self) -> Option<&SyntheticLevel<'a>> {
+        self.bids.first()
+    }
+
+    pub fn get_best_ask(&self) -> Option<&SyntheticLevel<'a>> {
+        self.asks.first()
+    }
+
+    /// Adds the orders of the direct order book labeled as [`LevelSource::Direct`].
+    pub fn merge_direct_order_book(&mut self, order_book: &OrderBook<'a>) -> Result<()> {
+        let Some(flipped) = order_book.is_flipped_for(&self.base, &self.counter) else {
+            bail!(OrderBookException::InvalidOrderBook);
+        };
+        let (bids, asks) = order_book.get_oriented_orders(flipped)?;
+        self.push_levels(bids, asks, LevelSource::Direct);
+
+        Ok(())
+    }
+
+    /// Synthesizes the bridged order book from the `base`/XRP and XRP/`counter` legs and adds its
+    /// levels labeled as [`LevelSource::Bridged`]. The legs may be passed in any orientation.
+    pub fn merge_bridged_order_books(
+        &mut self,
+        base_leg: &OrderBook<'a>,
+        counter_leg: &OrderBook<'a>,
+    ) -> Result<()> {
+        let xrp = Currency::xrp();
+        let (Some(base_leg_flipped), Some(counter_leg_flipped)) = (
+            base_leg.is_flipped_for(&self.base, &xrp),
+            counter_leg.is_flipped_for(&xrp, &self.counter),
+        ) else {
+            bail!(OrderBookException::InvalidOrderBook);
+        };
+        let (mut base_leg_bids, mut base_leg_asks) =
+            base_leg.get_oriented_orders(base_leg_flipped)?;
+        let (mut counter_leg_bids, mut counter_leg_asks) =
+            counter_leg.get_oriented_orders(counter_leg_flipped)?;
+        base_leg_bids.sort_by(|a, b| b.cmp(a));
+        counter_leg_bids.sort_by(|a, b| b.cmp(a));
+        base_leg_asks.sort();
+        counter_leg_asks.sort();
+        // Selling the base currency means selling it for XRP and the XRP for the counter currency.
+        // Buying the base currency means buying XRP with the counter currency and the base
+        // currency with XRP. In both cases the legs are oriented as base/XRP and XRP/counter.
+        let bids = self.bridge_orders(&base_leg_bids, &counter_leg_bids);
+        let asks = self.bridge_orders(&base_leg_asks, &counter_leg_asks);
+        self.push_levels(bids, asks, LevelSource::Bridged);
+
+        Ok(())
+    }
+
+    /// Walks both legs level by level. Every synthetic order consumes as much of the current
+    /// orders of both legs as possible at the combined rate of both.
+    fn bridge_orders(
+        &self,
+        base_leg_orders: &[Order<'a>],
+        counter_leg_orders: &[Order<'a>],
+    ) -> Vec<Order<'a>> {
+        let mut bridged_orders = Vec::new();
+        let mut base_leg_iter = base_leg_orders.iter();
+        let mut counter_leg_iter = counter_leg_orders.iter();
+        let (Some(mut base_leg_order), Some(mut counter_leg_order)) =
+            (base_leg_iter.next(), counter_leg_iter.next())
+        else {
+            return bridged_orders;
+        };
+        let mut base_leg_quantity = base_leg_order.base_quantity;
+        let mut counter_leg_quantity = counter_leg_order.base_quantity;
+        loop {
+            if base_leg_order.rate > Decimal::ZERO {
+                let xrp_quantity =
+                    (base_leg_quantity * base_leg_order.rate).min(counter_leg_quantity);
+                let base_quantity = xrp_quantity / base_leg_order.rate;
+                if base_quantity > DUST_QUANTITY {
+                    bridged_orders.push(Order {
+                        base: self.base.clone(),
+                        counter: self.counter.clone(),
+                        base_quantity,
+                        rate: base_leg_order.rate * counter_leg_order.rate,
+                        offer_id: None,
+                    });
+                }
+                base_leg_quantity -= base_quantity;
+                counter_leg_quantity -= xrp_quantity;
+            } else {
+                base_leg_quantity = Decimal::ZERO;
+            }
+            if base_leg_quantity <= DUST_QUANTITY {
+                match base_leg_iter.next() {
+                    Some(order) => {
+                        base_leg_order = order;
+                        base_leg_quantity = order.base_quantity;
+                    }
+                    None => break,
+                }
+            }
+            if counter_leg_quantity <= DUST_QUANTITY {
+                match counter_leg_iter.next() {
+                    Some(order) => {
+                        counter_leg_order = order;
+                        counter_leg_quantity = order.base_quantity;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        bridged_orders
+    }
+
+    fn push_levels(&mut self, bids: Vec<Order<'a>>, asks: Vec<Order<'a>>, source: LevelSource) {
+        self.bids.extend(
+            bids.into_iter()
+                .map(|order| SyntheticLevel { order, source }),
+        );
+        self.asks.extend(
+            asks.into_iter()
+                .map(|order| SyntheticLevel { order, source }),
+        );
+        self.sort();
+    }
+
+    /// Sorts the bids in descending order and the asks in ascending order.
+    pub fn sort(&mut self) {
+        self.bids.sort_by_key(|level| Reverse(level.order.rate));
+        self.asks.sort_by_key(|level| level.order.rate);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::HashSet, fmt};
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use super::{
+    order::Order,
+    order_book::{OrderBook, OrderBookSideType},
+};
+
+/// A single problem found by [`OrderBook::validate`]. `index` is the position of the order in its
+/// order book side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The best bid is greater than or equal to the best ask.
+    CrossedBook {
+        best_bid_rate: Decimal,
+        best_ask_rate: Decimal,
+    },
+    /// The order at `index` breaks the sort order of its side (bids descending, asks ascending).
+    UnsortedSide {
+        side_type: OrderBookSideType,
+        index: usize,
+    },
+    DuplicateOfferId {
+        side_type: OrderBookSideType,
+        index: usize,
+        offer_id: String,
+    },
+    NonPositiveQuantity {
+        side_type: OrderBookSideType,
+        index: usize,
+    },
+    NonPositiveRate {
+        side_type: OrderBookSideType,
+        index: usize,
+    },
+    /// The currencies of the order are not the base and counter currency of the order book.
+    CurrencyMismatch {
+        side_type: OrderBookSideType,
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let issues: Vec<String> = self
+            .issues
+            .iter()
+            .map(|issue| format!("{:?}", issue))
+            .collect();
+        write!(f, "{}", issues.join(", "))
+    }
+}
+
+impl OrderBook<'_> {
+    /// Checks the order book for crossed sides, unsorted sides, duplicate offer ids, non positive
+    /// quantities or rates and orders whose currencies don't match the order book.
+    pub fn validate(&self) -> Result<ValidationReport> {
+        let bids = self.get_bids()?;
+        let asks = self.get_asks()?;
+        let mut report = ValidationReport::default();
+        let mut offer_ids = HashSet::new();
+        for (side_type, orders) in [
+            (OrderBookSideType::Bids, &bids.orders),
+            (OrderBookSideType::Asks, &asks.orders),
+        ] {
+            for (index, order) in orders.iter().enumerate() {
+                self.validate_order(&side_type, index, order, &mut offer_ids, &mut report);
+            }
+            for (index, orders) in orders.windows(2).enumerate() {
+                let is_sorted = match side_type {
+                    OrderBookSideType::Bids => orders[0].rate >= orders[1].rate,
+                    OrderBookSideType::Asks => orders[0].rate <= orders[1].rate,
+                };
+                if !is_sorted {
+                    report.issues.push(ValidationIssue::UnsortedSide {
+                        side_type: side_type.clone(),
+                        index: index + 1,
+                    });
+                }
+            }
+        }
+        let best_bid_rate = bids.orders.iter().map(|order| order.rate).max();
+        let best_ask_rate = asks.orders.iter().map(|order| order.rate).min();
+        if let (Some(best_bid_rate), Some(best_ask_rate)) = (best_bid_rate, best_ask_rate) {
+            if best_bid_rate >= best_ask_rate {
+                report.issues.push(ValidationIssue::CrossedBook {
+                    best_bid_rate,
+                    best_ask_rate,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn validate_order(
+        &self,
+        side_type: &OrderBookSideType,
+        index: usize,
+        order: &Order<'_>,
+        offer_ids: &mut HashSet<String>,
+        report: &mut ValidationReport,
+    ) {
+        if order.base_quantity <= Decimal::ZERO {
+            report.issues.push(ValidationIssue::NonPositiveQuantity {
+                side_type: side_type.clone(),
+                index,
+            });
+        }
+        if order.rate <= Decimal::ZERO {
+            report.issues.push(ValidationIssue::NonPositiveRate {
+                side_type: side_type.clone(),
+                index,
+            });
+        }
+        // Both sides are stored in the orientation of the order book.
+        if !order.base.is_same_asset(&self.base) || !order.counter.is_same_asset(&self.counter) {
+            report.issues.push(ValidationIssue::CurrencyMismatch {
+                side_type: side_type.clone(),
+                index,
+            });
+        }
+        if let Some(offer_id) = &order.offer_id {
+            if !offer_ids.insert(offer_id.to_string()) {
+                report.issues.push(ValidationIssue::DuplicateOfferId {
+                    side_type: side_type.clone(),
+                    index,
+                    offer_id: offer_id.to_string(),
+                });
+            }
+        }
+    }
+}
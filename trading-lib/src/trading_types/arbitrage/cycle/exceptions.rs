@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CycleArbitrageException {
+    #[error("The cycle trade needs at least two legs.")]
+    TooFewLegs,
+    #[error("A leg of the cycle trade has no orders.")]
+    EmptyLeg,
+    #[error("The legs of the cycle trade don't form a cycle. Each leg must give the currency the previous leg receives and the last leg must receive the starting currency.")]
+    DisconnectedLegs,
+    #[error("The cycle trade has no size to execute.")]
+    EmptyTradeSize,
+}
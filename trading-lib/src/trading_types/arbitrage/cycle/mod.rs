@@ -0,0 +1,264 @@
+pub mod exceptions;
+#[cfg(feature = "xrpl")]
+pub mod payment;
+pub mod sizing;
+
+use std::cmp::Reverse;
+
+use anyhow::{bail, Result};
+use exceptions::CycleArbitrageException;
+use rust_decimal::Decimal;
+use sizing::CycleSizing;
+
+use crate::{
+    models::currency::Currency,
+    order_books::{graph::CurrencyGraph, order::Order, OrderBooks},
+};
+
+use super::{
+    profit::{GetProfitEstimate, ProfitEstimate, DROPS_PER_XRP},
+    swap::sizing::{get_rate_after_fee, get_transfer_fee},
+};
+
+/// Represents a cycle through any number of order books from the perspective of the trader, e.g.
+/// XRP -> USD:GateHub -> EUR:Bitstamp -> USD:Bitstamp -> XRP. A cycle through three order books
+/// is a [`TriangularTrade`](super::triangular::TriangularTrade).
+///
+/// All orders are from the perspective of the trader: the base currency is given, the counter
+/// currency is received and the base quantity is the most the order takes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleTrade<'a> {
+    pub starting_currency: Currency<'a>,
+    /// All levels of each leg, best first. Each leg gives the currency the previous leg receives
+    /// and the last leg receives the starting currency.
+    pub legs: Vec<Vec<Order<'a>>>,
+}
+
+impl<'a> CycleTrade<'a> {
+    pub fn new(legs: Vec<Vec<Order<'a>>>) -> Result<Self> {
+        if legs.len() < 2 {
+            bail!(CycleArbitrageException::TooFewLegs);
+        }
+        let Some(orders) = legs
+            .iter()
+            .map(|levels| levels.first())
+            .collect::<Option<Vec<_>>>()
+        else {
+            bail!(CycleArbitrageException::EmptyLeg);
+        };
+        for (leg, order) in orders.iter().enumerate() {
+            let next_order = orders[(leg + 1) % orders.len()];
+            if !order.counter.is_same_asset(&next_order.base) {
+                bail!(CycleArbitrageException::DisconnectedLegs);
+            }
+        }
+
+        Ok(Self {
+            starting_currency: orders[0].base.clone(),
+            legs,
+        })
+    }
+
+    /// Returns the (given, received) currencies of every leg.
+    pub fn get_legs(&self) -> Vec<(&Currency<'a>, &Currency<'a>)> {
+        self.legs
+            .iter()
+            .map(|levels| (&levels[0].base, &levels[0].counter))
+            .collect()
+    }
+
+    /// Estimates the profit of the trade at the given size.
+    pub fn get_profit_estimate_for_size(
+        &self,
+        sizing: &CycleSizing<'_>,
+        network_fee_drops: u64,
+    ) -> ProfitEstimate<'a> {
+        self.get_profit_estimate_with_network_fee(
+            sizing,
+            network_fee_drops,
+            get_network_fee(&self.legs, network_fee_drops),
+        )
+    }
+
+    /// Estimates the profit of the trade at the given size with the network fee already
+    /// converted to the starting currency.
+    fn get_profit_estimate_with_network_fee(
+        &self,
+        sizing: &CycleSizing<'_>,
+        network_fee_drops: u64,
+        network_fee: Option<Decimal>,
+    ) -> ProfitEstimate<'a> {
+        let profit = sizing.get_profit() - network_fee.unwrap_or_default();
+        let input_quantity = sizing.get_input_quantity();
+        let profit_pct = if input_quantity > Decimal::ZERO {
+            profit / input_quantity * Decimal::ONE_HUNDRED
+        } else {
+            Decimal::ZERO
+        };
+
+        ProfitEstimate {
+            starting_currency: self.starting_currency.clone(),
+            input_quantity,
+            output_quantity: sizing.get_output_quantity(),
+            profit,
+            profit_pct,
+            transfer_fees: sizing
+                .consumed_levels
+                .iter()
+                .map(|levels| get_transfer_fee(levels))
+                .collect(),
+            network_fee_drops,
+            network_fee,
+            break_even_quantity: self.get_break_even_quantity(network_fee.unwrap_or_default()),
+            funding_cost: None,
+        }
+    }
+}
+
+impl<'a> GetProfitEstimate<'a> for CycleTrade<'a> {
+    fn get_profit_estimate(&self, network_fee_drops: u64) -> ProfitEstimate<'a> {
+        self.get_profit_estimate_for_size(&self.get_optimal_size(), network_fee_drops)
+    }
+}
+
+/// A cycle trade together with its size and profit estimate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfitableCycleTrade<'a> {
+    pub trade: CycleTrade<'a>,
+    /// The optimal size or the size the trade filter allows.
+    pub sizing: CycleSizing<'a>,
+    pub estimate: ProfitEstimate<'a>,
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Returns the profitable cycles with at most `max_hops` legs through the order books which
+    /// are not excluded, sorted by profit, most profitable first. The cycles are the negative
+    /// cycles of the currency graph, see [`CurrencyGraph::get_negative_cycles`], so rotations of
+    /// a cycle are returned once, starting with `anchor` if given. Trades are sized and filtered
+    /// like [`OrderBooks::get_triangular_trades`].
+    pub fn get_cycle_trades(
+        &self,
+        max_hops: usize,
+        anchor: Option<&Currency<'_>>,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ProfitableCycleTrade<'a>>> {
+        let mut graph = CurrencyGraph::default();
+        for order_book in self.order_books.iter() {
+            if !self.is_excluded(order_book) {
+                graph.add_order_book(&self.get_evaluable_order_book(order_book)?)?;
+            }
+        }
+        let mut profitable_trades = Vec::new();
+        for path in graph.get_negative_cycles(max_hops, anchor) {
+            let legs = path
+                .currencies
+                .windows(2)
+                .map(|currencies| self.get_leg_levels(&currencies[0], &currencies[1], None))
+                .collect::<Result<Vec<_>>>()?;
+            // A leg without levels only spoils its own cycle, not the whole search.
+            if legs.iter().any(Vec::is_empty) {
+                continue;
+            }
+            let trade = CycleTrade::new(legs)?;
+            if let Some(profitable_trade) =
+                self.get_profitable_cycle_trade(trade, network_fee_drops)?
+            {
+                profitable_trades.push(profitable_trade);
+            }
+        }
+        profitable_trades.sort_by_key(|profitable_trade| Reverse(profitable_trade.estimate.profit));
+
+        Ok(profitable_trades)
+    }
+
+    /// Sizes the trade within the trade filter of its starting currency. `None` if it isn't
+    /// profitable, the filter rejects it or its network fee can't be converted to the starting
+    /// currency.
+    pub(crate) fn get_profitable_cycle_trade(
+        &self,
+        trade: CycleTrade<'a>,
+        network_fee_drops: u64,
+    ) -> Result<Option<ProfitableCycleTrade<'a>>> {
+        let Some(network_fee) = self.get_cycle_network_fee(&trade, network_fee_drops)? else {
+            return Ok(None);
+        };
+        let estimate = trade.get_profit_estimate_with_network_fee(
+            &trade.get_optimal_size(),
+            network_fee_drops,
+            Some(network_fee),
+        );
+        if !estimate.is_profitable() {
+            return Ok(None);
+        }
+        let filter = self.trade_filters.get_filter(&trade.starting_currency);
+        let sizing = trade.get_size_within(filter.max_input_quantity);
+        let estimate = trade.get_profit_estimate_with_network_fee(
+            &sizing,
+            network_fee_drops,
+            Some(network_fee),
+        );
+        if !estimate.is_profitable()
+            || !filter
+                .get_rejection_reasons(&estimate, &trade.get_legs())
+                .is_empty()
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(ProfitableCycleTrade {
+            trade,
+            sizing,
+            estimate,
+        }))
+    }
+
+    /// Returns the network fee of the trade in its starting currency. A cycle not trading
+    /// through XRP converts it at the best level selling XRP for the starting currency. `None`
+    /// if there is no such level.
+    fn get_cycle_network_fee(
+        &self,
+        trade: &CycleTrade<'a>,
+        network_fee_drops: u64,
+    ) -> Result<Option<Decimal>> {
+        match get_network_fee(&trade.legs, network_fee_drops) {
+            Some(network_fee) => Ok(Some(network_fee)),
+            None => self.get_network_fee_in(&trade.starting_currency, network_fee_drops),
+        }
+    }
+}
+
+/// Returns the network fee of a cycle of legs in its starting currency. The network fee is paid
+/// in XRP. It is converted with the remaining legs if XRP is traded through, otherwise it is
+/// `None`.
+fn get_network_fee(legs: &[Vec<Order<'_>>], network_fee_drops: u64) -> Option<Decimal> {
+    let network_fee_xrp = Decimal::from(network_fee_drops) / Decimal::from(DROPS_PER_XRP);
+    if legs[0][0].base.is_xrp() {
+        return Some(network_fee_xrp);
+    }
+
+    legs.iter()
+        .position(|levels| levels[0].base.is_xrp())
+        .map(|leg| {
+            legs[leg..]
+                .iter()
+                .map(|levels| get_rate_after_fee(&levels[0]))
+                .fold(network_fee_xrp, |network_fee, rate| network_fee * rate)
+        })
+}
+
+/// Returns the position of the currency in the list, adding it if it is missing.
+pub(crate) fn get_position<'a>(
+    currencies: &mut Vec<Currency<'a>>,
+    currency: &Currency<'a>,
+) -> usize {
+    match currencies
+        .iter()
+        .position(|known_currency| known_currency.is_same_asset(currency))
+    {
+        Some(position) => position,
+        None => {
+            currencies.push(currency.clone());
+            currencies.len() - 1
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use xrpl::models::{
+    transactions::{Payment, PaymentFlag},
+    FlagCollection,
+};
+
+use crate::trading_types::{
+    arbitrage::swap::payment::{get_amount, get_offer},
+    execution::{GetTransactions, TradeTransaction},
+};
+
+use super::{
+    exceptions::CycleArbitrageException, sizing::CycleSizing, CycleTrade, ProfitableCycleTrade,
+};
+
+impl<'a> CycleTrade<'a> {
+    /// Returns the trade of the given size as one payment from the account to itself through all
+    /// order books of the cycle or, if it starts with XRP, as one offer per leg as XRP to XRP
+    /// payments can't have paths.
+    pub fn get_sized_transactions(
+        &self,
+        account: Cow<'a, str>,
+        sizing: &CycleSizing<'_>,
+    ) -> Result<Vec<TradeTransaction<'a>>> {
+        if sizing.get_input_quantity() <= Decimal::ZERO {
+            bail!(CycleArbitrageException::EmptyTradeSize);
+        }
+        let legs = self.get_legs();
+        if !self.starting_currency.is_xrp() {
+            let path = legs
+                .iter()
+                .map(|(_, received)| received.get_path_step())
+                .collect();
+
+            return Ok(vec![TradeTransaction::Payment(Payment::new(
+                account.clone(),
+                None,
+                None,
+                Some(FlagCollection::new(vec![PaymentFlag::TfNoDirectRipple])),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                get_amount(&self.starting_currency, sizing.get_output_quantity()),
+                account,
+                None,
+                None,
+                None,
+                Some(vec![path]),
+                Some(get_amount(
+                    &self.starting_currency,
+                    sizing.get_input_quantity(),
+                )),
+            ))]);
+        }
+
+        Ok(legs
+            .iter()
+            .enumerate()
+            .map(|(leg, (given, received))| {
+                TradeTransaction::OfferCreate(get_offer(
+                    account.clone(),
+                    (given, sizing.quantities[leg]),
+                    (received, sizing.quantities[leg + 1]),
+                ))
+            })
+            .collect())
+    }
+}
+
+impl<'a> GetTransactions<'a> for CycleTrade<'a> {
+    /// Returns the trade at its optimal size.
+    fn get_transactions(&self, account: Cow<'a, str>) -> Result<Vec<TradeTransaction<'a>>> {
+        self.get_sized_transactions(account, &self.get_optimal_size())
+    }
+}
+
+impl<'a> GetTransactions<'a> for ProfitableCycleTrade<'a> {
+    fn get_transactions(&self, account: Cow<'a, str>) -> Result<Vec<TradeTransaction<'a>>> {
+        self.trade.get_sized_transactions(account, &self.sizing)
+    }
+}
@@ -0,0 +1,192 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    order_books::order::Order,
+    trading_types::arbitrage::swap::sizing::{
+        add_consumed_level, get_rate_after_fee, ConsumedLevel,
+    },
+};
+
+use super::CycleTrade;
+
+/// The size of a cycle trade maximizing the net profit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CycleSizing<'a> {
+    /// The amount given to each leg followed by the amount of the starting currency received from
+    /// the last leg, all after the transfer fees.
+    pub quantities: Vec<Decimal>,
+    /// The levels consumed per leg.
+    pub consumed_levels: Vec<Vec<ConsumedLevel<'a>>>,
+    /// True if the maximum input cut the trade short of its optimal size.
+    pub is_capped: bool,
+}
+
+impl CycleSizing<'_> {
+    /// Returns the amount of the starting currency given to the first leg.
+    pub fn get_input_quantity(&self) -> Decimal {
+        self.quantities.first().copied().unwrap_or_default()
+    }
+
+    /// Returns the amount of the starting currency received from the last leg.
+    pub fn get_output_quantity(&self) -> Decimal {
+        self.quantities.last().copied().unwrap_or_default()
+    }
+
+    pub fn get_profit(&self) -> Decimal {
+        self.get_output_quantity() - self.get_input_quantity()
+    }
+}
+
+/// A stretch of a cycle where no leg moves to its next level.
+#[derive(Debug, Clone, PartialEq)]
+struct CycleSegment {
+    /// The level of each leg.
+    indices: Vec<usize>,
+    /// The amount given to each leg followed by the output of the last one.
+    quantities: Vec<Decimal>,
+}
+
+impl CycleSegment {
+    fn get_input_quantity(&self) -> Decimal {
+        self.quantities[0]
+    }
+
+    fn get_profit(&self) -> Decimal {
+        self.quantities[self.quantities.len() - 1] - self.quantities[0]
+    }
+
+    fn scale(&mut self, share: Decimal) {
+        for quantity in self.quantities.iter_mut() {
+            *quantity *= share;
+        }
+    }
+}
+
+impl<'a> CycleTrade<'a> {
+    /// Walks all legs level by level as long as the next unit of the starting currency returns
+    /// more than it costs, like the optimal size of a swap.
+    pub fn get_optimal_size(&self) -> CycleSizing<'a> {
+        self.get_size_within(None)
+    }
+
+    /// Returns the optimal size shrunk to at most `max_input_quantity` of the starting currency.
+    pub fn get_size_within(&self, max_input_quantity: Option<Decimal>) -> CycleSizing<'a> {
+        let mut sizing = CycleSizing {
+            quantities: vec![Decimal::ZERO; self.legs.len() + 1],
+            consumed_levels: vec![Vec::new(); self.legs.len()],
+            is_capped: false,
+        };
+        for mut segment in get_profitable_segments(&self.legs) {
+            if let Some(max_input_quantity) = max_input_quantity {
+                let left = max_input_quantity - sizing.get_input_quantity();
+                if segment.get_input_quantity() > left {
+                    segment.scale((left / segment.get_input_quantity()).max(Decimal::ZERO));
+                    sizing.is_capped = true;
+                }
+            }
+            if segment.get_input_quantity() > Decimal::ZERO {
+                for (leg, levels) in self.legs.iter().enumerate() {
+                    add_consumed_level(
+                        &mut sizing.consumed_levels[leg],
+                        segment.indices[leg],
+                        &levels[segment.indices[leg]],
+                        segment.quantities[leg],
+                    );
+                }
+                for (quantity, segment_quantity) in
+                    sizing.quantities.iter_mut().zip(segment.quantities.iter())
+                {
+                    *quantity += segment_quantity;
+                }
+            }
+            if sizing.is_capped {
+                break;
+            }
+        }
+
+        sizing
+    }
+
+    /// Returns the smallest input of the starting currency whose profit covers `fixed_cost` or
+    /// `None` if no size does.
+    pub fn get_break_even_quantity(&self, fixed_cost: Decimal) -> Option<Decimal> {
+        get_break_even_quantity(&get_profitable_segments(&self.legs), fixed_cost)
+    }
+}
+
+/// Splits a cycle of legs into segments as long as the product of the rates after the transfer
+/// fees is above one. Each leg gives the currency the previous one receives.
+fn get_profitable_segments(legs: &[Vec<Order<'_>>]) -> Vec<CycleSegment> {
+    let mut segments = Vec::new();
+    let mut indices = vec![0; legs.len()];
+    let get_quantity = |levels: &[Order<'_>], index: usize| {
+        levels
+            .get(index)
+            .map(|order| order.base_quantity)
+            .unwrap_or_default()
+    };
+    // The amounts the current level of each leg still takes in its base currency.
+    let mut left: Vec<Decimal> = legs.iter().map(|levels| get_quantity(levels, 0)).collect();
+    while let Some(orders) = legs
+        .iter()
+        .zip(indices.iter())
+        .map(|(levels, &index)| levels.get(index))
+        .collect::<Option<Vec<_>>>()
+    {
+        let rates: Vec<Decimal> = orders.into_iter().map(get_rate_after_fee).collect();
+        if rates.iter().product::<Decimal>() <= Decimal::ONE {
+            break;
+        }
+        // The input each leg takes at most, converted into the starting currency. The leg taking
+        // the least limits the segment and moves to its next level.
+        let mut rate_to_leg = Decimal::ONE;
+        let mut limiting_leg = 0;
+        let mut input = Decimal::MAX;
+        for (leg, rate) in rates.iter().enumerate() {
+            let leg_input = left[leg] / rate_to_leg;
+            if leg_input < input {
+                input = leg_input;
+                limiting_leg = leg;
+            }
+            rate_to_leg *= rate;
+        }
+        let mut quantities = vec![input];
+        for rate in rates.iter() {
+            quantities.push(quantities[quantities.len() - 1] * rate);
+        }
+        if input > Decimal::ZERO {
+            segments.push(CycleSegment {
+                indices: indices.clone(),
+                quantities: quantities.clone(),
+            });
+        }
+        for leg in 0..legs.len() {
+            if leg == limiting_leg {
+                indices[leg] += 1;
+                left[leg] = get_quantity(&legs[leg], indices[leg]);
+            } else {
+                left[leg] -= quantities[leg];
+            }
+        }
+    }
+
+    segments
+}
+
+fn get_break_even_quantity(segments: &[CycleSegment], fixed_cost: Decimal) -> Option<Decimal> {
+    let mut input_quantity = Decimal::ZERO;
+    let mut profit = Decimal::ZERO;
+    for segment in segments {
+        let segment_profit = segment.get_profit();
+        if profit + segment_profit >= fixed_cost {
+            let missing_profit = (fixed_cost - profit).max(Decimal::ZERO);
+            let profit_per_input = segment_profit / segment.get_input_quantity();
+
+            return Some(input_quantity + missing_profit / profit_per_input);
+        }
+        input_quantity += segment.get_input_quantity();
+        profit += segment_profit;
+    }
+
+    None
+}
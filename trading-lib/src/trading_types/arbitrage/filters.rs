@@ -0,0 +1,129 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+
+use crate::{models::currency::Currency, order_books::registry::OrderBookKey};
+
+use super::profit::ProfitEstimate;
+
+/// What a trade has to meet to be returned. `None` means no limit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeFilter {
+    pub min_profit: Option<Decimal>,
+    pub min_profit_pct: Option<Decimal>,
+    /// The smallest input of the starting currency worth executing.
+    pub min_input_quantity: Option<Decimal>,
+    /// Trades are sized down to this input of the starting currency.
+    pub max_input_quantity: Option<Decimal>,
+    pub max_legs: Option<usize>,
+    /// Markets which are not traded, no matter the orientation.
+    pub blacklisted_pairs: HashSet<OrderBookKey>,
+}
+
+/// Why a trade was rejected by its filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectionReason {
+    ProfitTooLow {
+        profit: Decimal,
+        min_profit: Decimal,
+    },
+    ProfitPctTooLow {
+        profit_pct: Decimal,
+        min_profit_pct: Decimal,
+    },
+    InputTooSmall {
+        input_quantity: Decimal,
+        min_input_quantity: Decimal,
+    },
+    TooManyLegs {
+        leg_count: usize,
+        max_legs: usize,
+    },
+    BlacklistedPair {
+        base: String,
+        counter: String,
+    },
+}
+
+/// The filters of the trades by their starting currency.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeFilters {
+    /// Applies to starting currencies without their own filter.
+    pub default_filter: TradeFilter,
+    /// The filters by the label of the starting currency (see [`Currency::get_label`]).
+    pub filters: HashMap<String, TradeFilter>,
+}
+
+impl TradeFilters {
+    pub fn get_filter(&self, starting_currency: &Currency<'_>) -> &TradeFilter {
+        self.filters
+            .get(&starting_currency.get_label())
+            .unwrap_or(&self.default_filter)
+    }
+
+    pub fn set_filter(&mut self, starting_currency: &Currency<'_>, filter: TradeFilter) {
+        self.filters.insert(starting_currency.get_label(), filter);
+    }
+}
+
+impl TradeFilter {
+    pub fn blacklist_pair(&mut self, base: &Currency<'_>, counter: &Currency<'_>) {
+        self.blacklisted_pairs
+            .insert(OrderBookKey::new(base, counter));
+    }
+
+    /// Returns why the trade doesn't pass the filter. `legs` are the (given, received) currencies
+    /// of every leg. An empty list means it passes.
+    pub fn get_rejection_reasons(
+        &self,
+        estimate: &ProfitEstimate<'_>,
+        legs: &[(&Currency<'_>, &Currency<'_>)],
+    ) -> Vec<RejectionReason> {
+        let mut reasons = Vec::new();
+        if let Some(min_profit) = self.min_profit {
+            if estimate.profit < min_profit {
+                reasons.push(RejectionReason::ProfitTooLow {
+                    profit: estimate.profit,
+                    min_profit,
+                });
+            }
+        }
+        if let Some(min_profit_pct) = self.min_profit_pct {
+            if estimate.profit_pct < min_profit_pct {
+                reasons.push(RejectionReason::ProfitPctTooLow {
+                    profit_pct: estimate.profit_pct,
+                    min_profit_pct,
+                });
+            }
+        }
+        if let Some(min_input_quantity) = self.min_input_quantity {
+            if estimate.input_quantity < min_input_quantity {
+                reasons.push(RejectionReason::InputTooSmall {
+                    input_quantity: estimate.input_quantity,
+                    min_input_quantity,
+                });
+            }
+        }
+        if let Some(max_legs) = self.max_legs {
+            if legs.len() > max_legs {
+                reasons.push(RejectionReason::TooManyLegs {
+                    leg_count: legs.len(),
+                    max_legs,
+                });
+            }
+        }
+        for (base, counter) in legs {
+            if self
+                .blacklisted_pairs
+                .contains(&OrderBookKey::new(base, counter))
+            {
+                reasons.push(RejectionReason::BlacklistedPair {
+                    base: base.get_label(),
+                    counter: counter.get_label(),
+                });
+            }
+        }
+
+        reasons
+    }
+}
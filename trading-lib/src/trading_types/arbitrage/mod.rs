@@ -1,6 +1,6 @@
+pub mod cycle;
+pub mod filters;
+pub mod opportunities;
+pub mod profit;
 pub mod swap;
 pub mod triangular;
-
-pub trait IsProfitable {
-    fn is_profitable(&self) -> bool;
-}
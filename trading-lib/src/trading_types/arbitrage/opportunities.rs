@@ -0,0 +1,478 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use anyhow::Result;
+
+use crate::{
+    models::currency::Currency,
+    order_books::{
+        events::OrderBookEvent, order_book::OrderBook, registry::OrderBookKey, OrderBooks,
+    },
+};
+
+use super::{
+    cycle::{get_position, CycleTrade, ProfitableCycleTrade},
+    profit::ProfitEstimate,
+    swap::{FilteredSwapTrades, ProfitableSwapTrade},
+};
+
+/// The most cycles an [`OpportunityIndex`] indexes by default.
+pub const DEFAULT_MAX_CYCLES: usize = 10_000;
+
+/// A profitable trade of any type passing the trade filters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Opportunity<'a> {
+    Swap(Box<ProfitableSwapTrade<'a>>),
+    Cycle(Box<ProfitableCycleTrade<'a>>),
+}
+
+impl<'a> Opportunity<'a> {
+    pub fn get_estimate(&self) -> &ProfitEstimate<'a> {
+        match self {
+            Opportunity::Swap(profitable_trade) => &profitable_trade.estimate,
+            Opportunity::Cycle(profitable_trade) => &profitable_trade.estimate,
+        }
+    }
+}
+
+/// A set of order books evaluated together by the [`OpportunityIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Candidate {
+    /// Two order books trading the same pair of currency codes, swapped like
+    /// [`GetProfitableTrades`](super::swap::GetProfitableTrades).
+    SwapPair([OrderBookKey; 2]),
+    /// A cycle through the currencies at these positions of
+    /// [`OpportunityIndex::currencies`], starting with the anchor or the currency with the lowest
+    /// position.
+    Cycle(Vec<usize>),
+}
+
+/// Keeps the opportunities of all swap pairs and cycles of the order books up to date. Each
+/// order book is indexed to the candidates containing it or converting their network fee, so an
+/// update of one order book only re-evaluates those. The result is the same as a full rescan as
+/// long as only the updated order book changes. Order books turning stale or fresh as the
+/// current ledger advances are re-evaluated with every update or by
+/// [`OpportunityIndex::update_staleness`]. Changes to anything else, e.g. the trading account,
+/// the trade filters or other exclusions of order books, need a [`OpportunityIndex::rescan`].
+///
+/// Unlike [`OrderBooks::get_cycle_trades`], every cycle with at most `max_hops` legs is a
+/// candidate, not only the ones found by the negative-cycle search. The number of cycles grows
+/// exponentially with the number of currencies, so the search stops at `max_cycles`.
+#[derive(Debug, Clone)]
+pub struct OpportunityIndex<'a> {
+    pub max_hops: usize,
+    /// The most cycles indexed as candidates, [`DEFAULT_MAX_CYCLES`] unless changed before a
+    /// [`OpportunityIndex::rescan`]. An anchor keeps the search to the cycles through it.
+    pub max_cycles: usize,
+    /// Cycles start with this currency and cycles without it are left out.
+    pub anchor: Option<Currency<'a>>,
+    pub network_fee_drops: u64,
+    /// Receive the ranked opportunities whenever they are re-evaluated.
+    pub subscribers: Vec<Sender<Vec<Opportunity<'a>>>>,
+    /// The currencies of all order books in the order they were first seen.
+    pub currencies: Vec<Currency<'a>>,
+    /// The positions of the base and the counter currency of every order book by its market.
+    markets: BTreeMap<OrderBookKey, (usize, usize)>,
+    /// The candidates containing each order book by its market.
+    candidates: HashMap<OrderBookKey, BTreeSet<Candidate>>,
+    /// The markets whose order books were stale when last evaluated.
+    stale_markets: BTreeSet<OrderBookKey>,
+    opportunities: BTreeMap<Candidate, Vec<Opportunity<'a>>>,
+}
+
+impl<'a> OpportunityIndex<'a> {
+    /// Indexes and evaluates all order books.
+    pub fn new(
+        order_books: &OrderBooks<'a>,
+        max_hops: usize,
+        anchor: Option<Currency<'a>>,
+        network_fee_drops: u64,
+    ) -> Result<Self> {
+        let mut opportunity_index = Self {
+            max_hops,
+            max_cycles: DEFAULT_MAX_CYCLES,
+            anchor,
+            network_fee_drops,
+            subscribers: Vec::new(),
+            currencies: Vec::new(),
+            markets: BTreeMap::new(),
+            candidates: HashMap::new(),
+            stale_markets: BTreeSet::new(),
+            opportunities: BTreeMap::new(),
+        };
+        opportunity_index.rescan(order_books)?;
+
+        Ok(opportunity_index)
+    }
+
+    /// Returns a receiver for the ranked opportunities.
+    pub fn subscribe(&mut self) -> Receiver<Vec<Opportunity<'a>>> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+
+        receiver
+    }
+
+    /// Re-indexes and re-evaluates all order books and publishes the result.
+    pub fn rescan(&mut self, order_books: &OrderBooks<'a>) -> Result<()> {
+        self.index_candidates(order_books);
+        self.stale_markets = get_stale_markets(order_books);
+        self.opportunities.clear();
+        let candidates: BTreeSet<Candidate> = self.candidates.values().flatten().cloned().collect();
+        for candidate in candidates {
+            self.evaluate(order_books, candidate)?;
+        }
+        self.publish();
+
+        Ok(())
+    }
+
+    /// Updates the order book like [`OrderBooks::update_order_book`] and re-evaluates the
+    /// candidates containing it and the ones of order books whose staleness changed. The ranked
+    /// opportunities are published if any of them were re-evaluated.
+    pub fn update_order_book(
+        &mut self,
+        order_books: &mut OrderBooks<'a>,
+        order_book: OrderBook<'a>,
+    ) -> Result<Vec<OrderBookEvent>> {
+        let key = OrderBookKey::from(&order_book);
+        let is_new = !self.markets.contains_key(&key);
+        let events = order_books.update_order_book(order_book)?;
+        self.apply_update(order_books, &key, is_new, &events)?;
+
+        Ok(events)
+    }
+
+    /// Updates the order book like [`OrderBooks::update_order_book_at_ledger`] and re-evaluates
+    /// like [`OpportunityIndex::update_order_book`]. Advancing the current ledger can turn other
+    /// order books stale.
+    pub fn update_order_book_at_ledger(
+        &mut self,
+        order_books: &mut OrderBooks<'a>,
+        order_book: OrderBook<'a>,
+        ledger_index: u32,
+        close_time: u64,
+    ) -> Result<Vec<OrderBookEvent>> {
+        let key = OrderBookKey::from(&order_book);
+        let is_new = !self.markets.contains_key(&key);
+        let events =
+            order_books.update_order_book_at_ledger(order_book, ledger_index, close_time)?;
+        self.apply_update(order_books, &key, is_new, &events)?;
+
+        Ok(events)
+    }
+
+    /// Re-evaluates the candidates of the order books which turned stale or fresh since they
+    /// were last evaluated, e.g. after [`OrderBooks::stamp_order_book`] advanced the current
+    /// ledger. The ranked opportunities are published if there are any.
+    pub fn update_staleness(&mut self, order_books: &OrderBooks<'a>) -> Result<()> {
+        let candidates = self.get_staleness_candidates(order_books);
+        self.reevaluate(order_books, candidates)
+    }
+
+    fn apply_update(
+        &mut self,
+        order_books: &OrderBooks<'a>,
+        key: &OrderBookKey,
+        is_new: bool,
+        events: &[OrderBookEvent],
+    ) -> Result<()> {
+        // All candidates of a new market contain it, the others stay the same.
+        if is_new {
+            self.index_candidates(order_books);
+        }
+        let mut candidates = self.get_staleness_candidates(order_books);
+        if is_new || !events.is_empty() {
+            candidates.extend(self.get_candidates(key));
+        }
+
+        self.reevaluate(order_books, candidates)
+    }
+
+    /// Returns the candidates of the markets whose staleness changed and remembers the stale
+    /// markets.
+    fn get_staleness_candidates(&mut self, order_books: &OrderBooks<'a>) -> BTreeSet<Candidate> {
+        let stale_markets = get_stale_markets(order_books);
+        let candidates = stale_markets
+            .symmetric_difference(&self.stale_markets)
+            .flat_map(|key| self.get_candidates(key))
+            .collect();
+        self.stale_markets = stale_markets;
+
+        candidates
+    }
+
+    /// Evaluates the candidates and publishes the result if there are any.
+    fn reevaluate(
+        &mut self,
+        order_books: &OrderBooks<'a>,
+        candidates: BTreeSet<Candidate>,
+    ) -> Result<()> {
+        if candidates.is_empty() {
+            return Ok(());
+        }
+        for candidate in candidates {
+            self.evaluate(order_books, candidate)?;
+        }
+        self.publish();
+
+        Ok(())
+    }
+
+    /// Returns the candidates containing the order book of the market.
+    pub fn get_candidates(&self, key: &OrderBookKey) -> Vec<Candidate> {
+        self.candidates
+            .get(key)
+            .map(|candidates| candidates.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns all opportunities sorted by profit, most profitable first.
+    pub fn get_opportunities(&self) -> Vec<Opportunity<'a>> {
+        let mut opportunities: Vec<Opportunity<'a>> =
+            self.opportunities.values().flatten().cloned().collect();
+        opportunities.sort_by_key(|opportunity| Reverse(opportunity.get_estimate().profit));
+
+        opportunities
+    }
+
+    fn index_candidates(&mut self, order_books: &OrderBooks<'a>) {
+        self.candidates.clear();
+        // The markets by their pair of currency codes, no matter the orientation.
+        let mut swap_groups: BTreeMap<(&str, &str), Vec<OrderBookKey>> = BTreeMap::new();
+        for order_book in order_books.order_books.iter() {
+            let key = OrderBookKey::from(order_book);
+            let base = get_position(&mut self.currencies, &order_book.base);
+            let counter = get_position(&mut self.currencies, &order_book.counter);
+            self.markets.insert(key.clone(), (base, counter));
+            let (base_code, counter_code) = (
+                order_book.base.currency_code.as_ref(),
+                order_book.counter.currency_code.as_ref(),
+            );
+            if base_code != counter_code {
+                let codes = if base_code < counter_code {
+                    (base_code, counter_code)
+                } else {
+                    (counter_code, base_code)
+                };
+                swap_groups.entry(codes).or_default().push(key);
+            }
+        }
+        for keys in swap_groups.values() {
+            for (index, first) in keys.iter().enumerate() {
+                for second in keys[index + 1..].iter() {
+                    let mut pair = [first.clone(), second.clone()];
+                    pair.sort();
+                    self.add_candidate(Candidate::SwapPair(pair));
+                }
+            }
+        }
+        for cycle in self.get_cycles() {
+            self.add_candidate(Candidate::Cycle(cycle));
+        }
+    }
+
+    fn add_candidate(&mut self, candidate: Candidate) {
+        for key in self.get_keys(&candidate) {
+            self.candidates
+                .entry(key)
+                .or_default()
+                .insert(candidate.clone());
+        }
+    }
+
+    /// Returns the markets of the order books the candidate is evaluated with: its own ones and,
+    /// if it doesn't trade XRP, the ones converting the network fee into its starting currencies
+    /// (see [`OrderBooks::get_network_fee_in`]).
+    fn get_keys(&self, candidate: &Candidate) -> Vec<OrderBookKey> {
+        match candidate {
+            Candidate::SwapPair(pair) => {
+                let currencies: BTreeSet<usize> = pair
+                    .iter()
+                    .flat_map(|key| {
+                        let (base, counter) = self.markets[key];
+                        [base, counter]
+                    })
+                    .collect();
+                let mut keys = pair.to_vec();
+                if !self.contains_xrp(currencies.iter()) {
+                    keys.extend(
+                        currencies
+                            .iter()
+                            .map(|&currency| self.get_network_fee_key(currency)),
+                    );
+                }
+
+                keys
+            }
+            Candidate::Cycle(cycle) => {
+                let mut keys: Vec<OrderBookKey> = (0..cycle.len())
+                    .map(|leg| {
+                        OrderBookKey::new(
+                            &self.currencies[cycle[leg]],
+                            &self.currencies[cycle[(leg + 1) % cycle.len()]],
+                        )
+                    })
+                    .collect();
+                if !self.contains_xrp(cycle.iter()) {
+                    keys.push(self.get_network_fee_key(cycle[0]));
+                }
+
+                keys
+            }
+        }
+    }
+
+    fn contains_xrp<'b>(&self, mut currencies: impl Iterator<Item = &'b usize>) -> bool {
+        currencies.any(|&currency| self.currencies[currency].is_xrp())
+    }
+
+    /// Returns the market selling XRP for the currency.
+    fn get_network_fee_key(&self, currency: usize) -> OrderBookKey {
+        OrderBookKey::new(&Currency::xrp(), &self.currencies[currency])
+    }
+
+    /// Returns the first `max_cycles` cycles through the markets with two to `max_hops` legs,
+    /// each visiting a currency once.
+    fn get_cycles(&self) -> Vec<Vec<usize>> {
+        let mut neighbors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); self.currencies.len()];
+        for &(base, counter) in self.markets.values() {
+            neighbors[base].insert(counter);
+            neighbors[counter].insert(base);
+        }
+        let anchor = self.anchor.as_ref().and_then(|anchor| {
+            self.currencies
+                .iter()
+                .position(|currency| currency.is_same_asset(anchor))
+        });
+        let starts = match (&self.anchor, anchor) {
+            (Some(_), Some(anchor)) => vec![anchor],
+            (Some(_), None) => Vec::new(),
+            (None, _) => (0..self.currencies.len()).collect(),
+        };
+        let mut cycles = Vec::new();
+        for start in starts {
+            if cycles.len() >= self.max_cycles {
+                break;
+            }
+            self.search_cycles(&neighbors, anchor.is_none(), &mut vec![start], &mut cycles);
+        }
+
+        cycles
+    }
+
+    /// Extends the path by every neighbor not visited yet. Without an anchor only currencies
+    /// after the start are visited, so every cycle starts with its lowest position.
+    fn search_cycles(
+        &self,
+        neighbors: &[BTreeSet<usize>],
+        is_lowest_start: bool,
+        path: &mut Vec<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        let start = path[0];
+        let current = path[path.len() - 1];
+        for &next in neighbors[current].iter() {
+            if cycles.len() >= self.max_cycles {
+                return;
+            }
+            if next == start {
+                if path.len() >= 2 {
+                    cycles.push(path.clone());
+                }
+                continue;
+            }
+            if path.len() == self.max_hops
+                || path.contains(&next)
+                || (is_lowest_start && next < start)
+            {
+                continue;
+            }
+            path.push(next);
+            self.search_cycles(neighbors, is_lowest_start, path, cycles);
+            path.pop();
+        }
+    }
+
+    /// Replaces the opportunities of the candidate with its current ones.
+    fn evaluate(&mut self, order_books: &OrderBooks<'a>, candidate: Candidate) -> Result<()> {
+        let opportunities = match &candidate {
+            Candidate::SwapPair(pair) => {
+                let order_books_of_pair = pair
+                    .iter()
+                    .map(|key| {
+                        let (base, counter) = self.markets[key];
+                        order_books
+                            .get_order_book(&self.currencies[base], &self.currencies[counter])
+                            .filter(|order_book| !order_books.is_excluded(order_book))
+                    })
+                    .collect::<Option<Vec<_>>>();
+                match order_books_of_pair {
+                    Some(order_books_of_pair) => {
+                        let mut filtered_trades = FilteredSwapTrades::default();
+                        for profitable_trade in order_books.get_swap_pair_trades(
+                            order_books_of_pair[0],
+                            order_books_of_pair[1],
+                            self.network_fee_drops,
+                        )? {
+                            filtered_trades.push(profitable_trade, &order_books.trade_filters);
+                        }
+                        filtered_trades
+                            .accepted
+                            .into_iter()
+                            .map(|profitable_trade| Opportunity::Swap(Box::new(profitable_trade)))
+                            .collect()
+                    }
+                    None => Vec::new(),
+                }
+            }
+            Candidate::Cycle(cycle) => {
+                let mut legs = Vec::with_capacity(cycle.len());
+                for leg in 0..cycle.len() {
+                    legs.push(order_books.get_leg_levels(
+                        &self.currencies[cycle[leg]],
+                        &self.currencies[cycle[(leg + 1) % cycle.len()]],
+                        None,
+                    )?);
+                }
+                // Legs without any level can't be traded.
+                match CycleTrade::new(legs) {
+                    Ok(trade) => order_books
+                        .get_profitable_cycle_trade(trade, self.network_fee_drops)?
+                        .map(|profitable_trade| Opportunity::Cycle(Box::new(profitable_trade)))
+                        .into_iter()
+                        .collect(),
+                    Err(_) => Vec::new(),
+                }
+            }
+        };
+        if opportunities.is_empty() {
+            self.opportunities.remove(&candidate);
+        } else {
+            self.opportunities.insert(candidate, opportunities);
+        }
+
+        Ok(())
+    }
+
+    /// Sends the ranked opportunities to all subscribers and drops the ones whose receiver is
+    /// gone.
+    fn publish(&mut self) {
+        let opportunities = self.get_opportunities();
+        self.subscribers
+            .retain(|subscriber| subscriber.send(opportunities.clone()).is_ok());
+    }
+}
+
+/// Returns the markets of the stale order books.
+fn get_stale_markets(order_books: &OrderBooks<'_>) -> BTreeSet<OrderBookKey> {
+    order_books
+        .order_books
+        .iter()
+        .filter(|order_book| order_books.is_stale(order_book))
+        .map(OrderBookKey::from)
+        .collect()
+}
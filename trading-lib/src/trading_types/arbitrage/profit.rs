@@ -0,0 +1,40 @@
+use rust_decimal::Decimal;
+
+use crate::models::currency::Currency;
+
+pub const DROPS_PER_XRP: u64 = 1_000_000;
+
+/// The expected outcome of a trade at a given size, usually its optimal size. All amounts are in
+/// the starting currency unless noted otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfitEstimate<'a> {
+    pub starting_currency: Currency<'a>,
+    pub input_quantity: Decimal,
+    pub output_quantity: Decimal,
+    /// The output minus the input and the network fee.
+    pub profit: Decimal,
+    /// The profit in percent of the input.
+    pub profit_pct: Decimal,
+    /// The transfer fees paid per leg in the currency received by the leg.
+    pub transfer_fees: Vec<Decimal>,
+    pub network_fee_drops: u64,
+    /// The network fee converted to the starting currency. `None` if there is no rate to convert
+    /// it, in which case it is not part of the profit.
+    pub network_fee: Option<Decimal>,
+    /// The smallest input covering the network fee. `None` if no size does.
+    pub break_even_quantity: Option<Decimal>,
+    /// The cost of acquiring the input first, part of the profit. `None` if the input is held.
+    pub funding_cost: Option<Decimal>,
+}
+
+impl ProfitEstimate<'_> {
+    pub fn is_profitable(&self) -> bool {
+        self.profit > Decimal::ZERO
+    }
+}
+
+pub trait GetProfitEstimate<'a> {
+    /// Estimates the profit of the trade. `network_fee_drops` is the fee of all transactions
+    /// needed to execute it.
+    fn get_profit_estimate(&self, network_fee_drops: u64) -> ProfitEstimate<'a>;
+}
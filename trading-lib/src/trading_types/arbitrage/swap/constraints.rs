@@ -0,0 +1,103 @@
+use rust_decimal::Decimal;
+
+use crate::models::{account::AccountState, currency::Currency};
+
+use super::{sizing::SwapSizing, SwapTrade};
+
+/// What keeps a swap from its optimal size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapConstraint {
+    /// The account doesn't hold enough of the token to give.
+    Balance,
+    /// The XRP to give would touch the reserve.
+    XrpReserve,
+    /// The trust line of the token to receive has no headroom left.
+    TrustLineLimit,
+    /// The path funding the starting currency has no more depth.
+    FundingDepth,
+    /// The token to receive would exceed the exposure limit of its issuer.
+    ExposureLimit,
+    /// The trade filter caps the input of the starting currency.
+    MaxInputQuantity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapLimit {
+    pub quantity: Decimal,
+    pub constraint: SwapConstraint,
+}
+
+/// The most a swap may move at each step. `None` means unlimited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwapLimits {
+    /// The amount of the starting currency given to the sell leg.
+    pub input: Option<SwapLimit>,
+    /// The amount received from the sell leg and given to the buy leg.
+    pub intermediate: Option<SwapLimit>,
+    /// The amount received from the buy leg.
+    pub output: Option<SwapLimit>,
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Returns how much the account can give and receive at each step of the swap.
+    pub fn get_limits(&self, account: &AccountState<'_>) -> SwapLimits {
+        let intermediate_received = &self.sell_order.counter;
+        let intermediate_given = &self.buy_order.base;
+        let mut intermediate = get_receive_limit(account, intermediate_received);
+        // The intermediate currency received isn't the one given if the issuers differ, so the
+        // buy leg has to be funded from the balance.
+        if !intermediate_given.is_same_asset(intermediate_received) {
+            intermediate = min_limit(intermediate, get_give_limit(account, intermediate_given));
+        }
+        // Receiving back what was given frees up the trust line.
+        let output = if self.buy_order.counter.is_same_asset(&self.sell_order.base) {
+            None
+        } else {
+            get_receive_limit(account, &self.buy_order.counter)
+        };
+
+        SwapLimits {
+            input: get_give_limit(account, &self.sell_order.base),
+            intermediate,
+            output,
+        }
+    }
+
+    /// Returns the optimal size of the swap capped by what the account can fund. The sizing
+    /// reports the constraint it was capped by.
+    pub fn get_funded_size(&self, account: &AccountState<'_>) -> SwapSizing<'a> {
+        self.get_size_within(&self.get_limits(account))
+    }
+}
+
+pub(super) fn get_give_limit(
+    account: &AccountState<'_>,
+    currency: &Currency<'_>,
+) -> Option<SwapLimit> {
+    Some(SwapLimit {
+        quantity: account.get_spendable(currency)?,
+        constraint: if currency.is_xrp() {
+            SwapConstraint::XrpReserve
+        } else {
+            SwapConstraint::Balance
+        },
+    })
+}
+
+pub(super) fn get_receive_limit(
+    account: &AccountState<'_>,
+    currency: &Currency<'_>,
+) -> Option<SwapLimit> {
+    Some(SwapLimit {
+        quantity: account.get_receivable(currency)?,
+        constraint: SwapConstraint::TrustLineLimit,
+    })
+}
+
+pub(super) fn min_limit(a: Option<SwapLimit>, b: Option<SwapLimit>) -> Option<SwapLimit> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.quantity < a.quantity => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
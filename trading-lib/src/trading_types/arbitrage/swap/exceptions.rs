@@ -4,4 +4,18 @@ use thiserror::Error;
 pub enum SwapArbitrageException {
     #[error("Invalid order book combination. The base currency of the first order book must be the counter currency of the second order book.")]
     InvalidOrderBookCombo,
+    #[error("An order book side of the swap has no orders.")]
+    EmptyOrderBookSide,
+    #[error("A swap starting with XRP can't be paid in one payment as XRP to XRP payments can't have paths.")]
+    XrpRoundTripPayment,
+    #[error(
+        "The intermediate currency received from the sell leg isn't the one given to the buy leg."
+    )]
+    DisconnectedLegs,
+    #[error("The swap has no size to pay.")]
+    EmptySwapSize,
+    #[error("The minimum output is above the output of the swap.")]
+    InvalidMinOutputQuantity,
+    #[error("No trading account is set.")]
+    NoTradingAccount,
 }
@@ -0,0 +1,241 @@
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+
+use crate::{
+    models::{account::AccountState, currency::Currency},
+    order_books::{
+        graph::{CurrencyGraph, CurrencyPath},
+        OrderBooks,
+    },
+    trading_types::arbitrage::profit::{ProfitEstimate, DROPS_PER_XRP},
+};
+
+use super::{
+    constraints::{get_give_limit, get_receive_limit, min_limit, SwapConstraint, SwapLimit},
+    exceptions::SwapArbitrageException,
+    sizing::SwapSizing,
+    SwapTrade,
+};
+
+/// Converts a held currency into the starting currency of a swap before the swap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingLeg<'a> {
+    /// Starts with the held currency and ends with the starting currency.
+    pub path: CurrencyPath<'a>,
+    /// The amount of the held currency given.
+    pub input_quantity: Decimal,
+    /// The amount of the starting currency received.
+    pub output_quantity: Decimal,
+    /// The amount of the held currency received for one starting currency along the best path
+    /// back.
+    pub unwind_rate: Decimal,
+}
+
+impl FundingLeg<'_> {
+    pub fn get_held_currency(&self) -> &Currency<'_> {
+        &self.path.currencies[0]
+    }
+
+    /// Returns the amount of the starting currency lost by converting the output back into the
+    /// held currency and again into the starting currency, i.e. the spreads and transfer fees
+    /// paid for holding the starting currency only during the swap.
+    pub fn get_cost(&self) -> Decimal {
+        self.output_quantity * (Decimal::ONE - self.unwind_rate * self.path.rate)
+    }
+}
+
+/// A swap with everything needed to execute it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapPlan<'a> {
+    /// `None` if the trading account holds the starting currency.
+    pub funding_leg: Option<FundingLeg<'a>>,
+    pub trade: SwapTrade<'a>,
+    pub sizing: SwapSizing<'a>,
+    /// Includes the cost and the network fee of the funding leg.
+    pub estimate: ProfitEstimate<'a>,
+}
+
+impl SwapPlan<'_> {
+    /// Returns the number of transactions executing the plan.
+    pub fn get_transaction_count(&self) -> u64 {
+        u64::from(self.funding_leg.is_some()) + self.trade.get_transaction_count()
+    }
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Returns true if both legs can be executed as one payment. Swaps starting with XRP and swaps
+    /// receiving another intermediate currency than they give need one offer per leg.
+    pub fn is_payable_at_once(&self) -> bool {
+        !(self.sell_order.base.is_xrp() && self.buy_order.counter.is_xrp())
+            && self.sell_order.counter.is_same_asset(&self.buy_order.base)
+    }
+
+    pub fn get_transaction_count(&self) -> u64 {
+        if self.is_payable_at_once() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Plans the swap for the account. If the account doesn't hold the starting currency, it is
+    /// funded from the held currency with the most profitable path of at most `max_hops` hops.
+    /// `network_fee_drops` is the fee of one transaction. Returns `None` if there is no
+    /// profitable size the account can fund or the network fee can't be converted into the
+    /// starting currency.
+    pub fn get_plan(
+        &self,
+        account: &AccountState<'_>,
+        graph: &CurrencyGraph<'a>,
+        max_hops: usize,
+        network_fee_drops: u64,
+    ) -> Option<SwapPlan<'a>> {
+        let starting_currency = &self.sell_order.base;
+        if account
+            .get_spendable(starting_currency)
+            .is_none_or(|spendable| spendable > Decimal::ZERO)
+        {
+            let sizing = self.get_funded_size(account);
+            let network_fee_drops = network_fee_drops * self.get_transaction_count();
+            let network_fee = self.get_plan_network_fee(graph, network_fee_drops)?;
+            let estimate = self.get_profit_estimate_with_network_fee(
+                &sizing,
+                network_fee_drops,
+                Some(network_fee),
+            );
+
+            return estimate.is_profitable().then(|| SwapPlan {
+                funding_leg: None,
+                trade: self.clone(),
+                sizing,
+                estimate,
+            });
+        }
+
+        let held_currencies = [Currency::xrp()]
+            .into_iter()
+            .chain(
+                account
+                    .trust_lines
+                    .iter()
+                    .filter(|trust_line| trust_line.balance > Decimal::ZERO)
+                    .map(|trust_line| trust_line.currency.clone()),
+            )
+            .filter(|currency| !currency.is_same_asset(starting_currency));
+        let mut best_plan: Option<SwapPlan<'a>> = None;
+        for held_currency in held_currencies {
+            let Some(plan) =
+                self.get_funded_plan(account, graph, &held_currency, max_hops, network_fee_drops)
+            else {
+                continue;
+            };
+            if best_plan
+                .as_ref()
+                .is_none_or(|best_plan| plan.estimate.profit > best_plan.estimate.profit)
+            {
+                best_plan = Some(plan);
+            }
+        }
+
+        best_plan.filter(|plan| plan.estimate.is_profitable())
+    }
+
+    fn get_funded_plan(
+        &self,
+        account: &AccountState<'_>,
+        graph: &CurrencyGraph<'a>,
+        held_currency: &Currency<'_>,
+        max_hops: usize,
+        network_fee_drops: u64,
+    ) -> Option<SwapPlan<'a>> {
+        let starting_currency = &self.sell_order.base;
+        let path = graph.get_best_rate_path(held_currency, starting_currency, max_hops)?;
+        let unwind_path = graph.get_best_rate_path(starting_currency, held_currency, max_hops)?;
+        let mut limits = self.get_limits(account);
+        // The input is limited by the held balance and the depth of the path instead.
+        limits.input = min_limit(
+            Some(SwapLimit {
+                quantity: path.depth * path.rate,
+                constraint: SwapConstraint::FundingDepth,
+            }),
+            get_give_limit(account, held_currency).map(|limit| SwapLimit {
+                quantity: limit.quantity * path.rate,
+                ..limit
+            }),
+        );
+        // The funded starting currency is received before the swap gives it.
+        limits.output = min_limit(limits.output, get_receive_limit(account, starting_currency));
+        let sizing = self.get_size_within(&limits);
+        if sizing.input_quantity <= Decimal::ZERO {
+            return None;
+        }
+        let funding_leg = FundingLeg {
+            input_quantity: sizing.input_quantity / path.rate,
+            output_quantity: sizing.input_quantity,
+            unwind_rate: unwind_path.rate,
+            path,
+        };
+        let network_fee_drops = network_fee_drops * (1 + self.get_transaction_count());
+        let network_fee = self.get_plan_network_fee(graph, network_fee_drops)?;
+        let mut estimate = self.get_profit_estimate_with_network_fee(
+            &sizing,
+            network_fee_drops,
+            Some(network_fee),
+        );
+        let funding_cost = funding_leg.get_cost();
+        estimate.profit -= funding_cost;
+        estimate.profit_pct = estimate.profit / sizing.input_quantity * Decimal::ONE_HUNDRED;
+        estimate.funding_cost = Some(funding_cost);
+        // The funding cost of the planned size is covered like the network fee.
+        estimate.break_even_quantity =
+            self.get_break_even_quantity(estimate.network_fee.unwrap_or_default() + funding_cost);
+
+        Some(SwapPlan {
+            funding_leg: Some(funding_leg),
+            trade: self.clone(),
+            sizing,
+            estimate,
+        })
+    }
+
+    /// Returns the network fee in the starting currency. Without XRP swapped through it is
+    /// converted at the best rate from XRP to the starting currency in the graph, like
+    /// [`OrderBooks::get_network_fee_in`]. `None` if there is no such rate.
+    fn get_plan_network_fee(
+        &self,
+        graph: &CurrencyGraph<'_>,
+        network_fee_drops: u64,
+    ) -> Option<Decimal> {
+        if let Some(network_fee) = self.get_network_fee(network_fee_drops) {
+            return Some(network_fee);
+        }
+        let xrp = graph.get_currency_index(&Currency::xrp())?;
+        let starting_currency = graph.get_currency_index(&self.starting_currency)?;
+        let rate = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.from == xrp && edge.to == starting_currency)
+            .map(|edge| edge.rate)
+            .max()?;
+
+        Some(Decimal::from(network_fee_drops) / Decimal::from(DROPS_PER_XRP) * rate)
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Plans the swap for the trading account, funding it through the evaluable order books if
+    /// needed (see [`SwapTrade::get_plan`]).
+    pub fn get_swap_plan(
+        &self,
+        trade: &SwapTrade<'a>,
+        max_hops: usize,
+        network_fee_drops: u64,
+    ) -> Result<Option<SwapPlan<'a>>> {
+        let Some(trading_account) = &self.trading_account else {
+            bail!(SwapArbitrageException::NoTradingAccount);
+        };
+        let graph = self.get_evaluable_order_books()?.get_currency_graph()?;
+
+        Ok(trade.get_plan(trading_account, &graph, max_hops, network_fee_drops))
+    }
+}
@@ -1,18 +1,37 @@
+pub mod constraints;
 pub mod exceptions;
+pub mod funding_leg;
+pub mod parity;
+#[cfg(feature = "xrpl")]
+pub mod payment;
+pub mod reconciliation;
+pub mod rippling;
+pub mod sizing;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, cmp::Reverse, collections::HashMap};
 
 use anyhow::{bail, Result};
+use constraints::{min_limit, SwapConstraint, SwapLimit, SwapLimits};
 use exceptions::SwapArbitrageException;
+use rust_decimal::Decimal;
+use sizing::{get_rate_after_fee, get_transfer_fee, SwapSizing};
 #[cfg(feature = "xrpl")]
 use xrpl::models::transactions::OfferCreate;
 
 use crate::{
     models::currency::Currency,
-    order_books::{order::Order, order_book::OrderBook, Flip, OrderBooks},
+    order_books::{
+        order::Order,
+        order_book::{OrderBook, OrderBookSideType},
+        rippling::RipplingNetwork,
+        Flip, OrderBooks,
+    },
 };
 
-use super::IsProfitable;
+use super::{
+    filters::{RejectionReason, TradeFilters},
+    profit::{GetProfitEstimate, ProfitEstimate, DROPS_PER_XRP},
+};
 
 /// Represents a profitable swap trade between two order books from the perspective of the trader.
 /// There are different combinations of how orders of two order books can be swapped:
@@ -51,29 +70,223 @@ use super::IsProfitable;
 /// *Order Book 2*: XRP/USD:Bitstamp <br>
 /// *Consuming Bid of USD:GateHub/XRP (counterparty wants to buy USD for XRP)*: USD:GateHub -> XRP (you sell USD for XRP) <br>
 /// *Consuming Bid of XRP/USD:Bitstamp (counterparty wants to buy XRP with USD)*: XRP -> USD:Bitstamp (you buy USD with XRP)
+///
+/// All orders are from the perspective of the trader: the base currency is given, the counter
+/// currency is received and the base quantity is the most the order takes.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SwapTrade<'a> {
     pub sell_order: Order<'a>,
     pub buy_order: Order<'a>,
     pub starting_currency: Currency<'a>,
+    /// All levels of the sell leg, best first. The first one is `sell_order`.
+    pub sell_levels: Vec<Order<'a>>,
+    /// All levels of the buy leg, best first. The first one is `buy_order`.
+    pub buy_levels: Vec<Order<'a>>,
 }
 
+/// Converts the swap at its optimal size into one offer per leg without an account, see
+/// [`SwapTrade::get_offers`].
 #[cfg(feature = "xrpl")]
 impl<'a> Into<(OfferCreate<'a>, OfferCreate<'a>)> for SwapTrade<'a> {
     fn into(self) -> (OfferCreate<'a>, OfferCreate<'a>) {
-        (self.sell_order.into(), self.buy_order.into())
+        self.get_offers("".into(), &self.get_optimal_size())
     }
 }
 
-impl IsProfitable for SwapTrade<'_> {
-    fn is_profitable(&self) -> bool {
-        let counter_quantity_after_fee = self.sell_order.calculate_counter_quantity_after_fee();
-        let mut other = self.buy_order.clone();
-        if counter_quantity_after_fee < other.base_quantity {
-            other.base_quantity = counter_quantity_after_fee;
+impl<'a> SwapTrade<'a> {
+    pub fn new(
+        sell_levels: Vec<Order<'a>>,
+        buy_levels: Vec<Order<'a>>,
+        starting_currency: Currency<'a>,
+    ) -> Result<Self> {
+        let (Some(sell_order), Some(buy_order)) = (sell_levels.first(), buy_levels.first()) else {
+            bail!(SwapArbitrageException::EmptyOrderBookSide);
+        };
+
+        Ok(Self {
+            sell_order: sell_order.clone(),
+            buy_order: buy_order.clone(),
+            starting_currency,
+            sell_levels,
+            buy_levels,
+        })
+    }
+
+    /// Estimates the profit of the swap at the given size, e.g. the size the trading account can
+    /// fund (see [`SwapTrade::get_funded_size`]).
+    pub fn get_profit_estimate_for_size(
+        &self,
+        sizing: &SwapSizing<'_>,
+        network_fee_drops: u64,
+    ) -> ProfitEstimate<'a> {
+        self.get_profit_estimate_with_network_fee(
+            sizing,
+            network_fee_drops,
+            self.get_network_fee(network_fee_drops),
+        )
+    }
+
+    /// Returns the network fee in the starting currency. The network fee is paid in XRP. It is
+    /// converted with the buy leg if XRP is swapped through, otherwise it is `None`.
+    fn get_network_fee(&self, network_fee_drops: u64) -> Option<Decimal> {
+        let network_fee_xrp = Decimal::from(network_fee_drops) / Decimal::from(DROPS_PER_XRP);
+        if self.starting_currency.is_xrp() {
+            Some(network_fee_xrp)
+        } else if self.buy_order.base.is_xrp() {
+            Some(network_fee_xrp * get_rate_after_fee(&self.buy_order))
+        } else {
+            None
+        }
+    }
+
+    /// Estimates the profit of the swap at the given size with the network fee already
+    /// converted to the starting currency.
+    fn get_profit_estimate_with_network_fee(
+        &self,
+        sizing: &SwapSizing<'_>,
+        network_fee_drops: u64,
+        network_fee: Option<Decimal>,
+    ) -> ProfitEstimate<'a> {
+        let profit = sizing.get_profit() - network_fee.unwrap_or_default();
+        let profit_pct = if sizing.input_quantity > Decimal::ZERO {
+            profit / sizing.input_quantity * Decimal::ONE_HUNDRED
+        } else {
+            Decimal::ZERO
+        };
+
+        ProfitEstimate {
+            starting_currency: self.starting_currency.clone(),
+            input_quantity: sizing.input_quantity,
+            output_quantity: sizing.output_quantity,
+            profit,
+            profit_pct,
+            transfer_fees: vec![
+                get_transfer_fee(&sizing.sell_levels),
+                get_transfer_fee(&sizing.buy_levels),
+            ],
+            network_fee_drops,
+            network_fee,
+            break_even_quantity: self.get_break_even_quantity(network_fee.unwrap_or_default()),
+            funding_cost: None,
         }
+    }
+}
+
+/// Returns the orders of the side from the perspective of the trader consuming them. Bids are
+/// consumed by giving the base currency, asks by giving the counter currency.
+fn get_levels<'a>(
+    order_book: &OrderBook<'a>,
+    side_type: OrderBookSideType,
+) -> Result<Vec<Order<'a>>> {
+    match side_type {
+        OrderBookSideType::Bids => Ok(order_book.get_bids()?.orders.to_vec()),
+        OrderBookSideType::Asks => {
+            let mut levels = order_book.get_asks()?.orders.to_vec();
+            for level in levels.iter_mut() {
+                level.flip()?;
+            }
+
+            Ok(levels)
+        }
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Returns the evaluable levels of the order book trading `from` and `to` from the perspective
+    /// of the trader giving `from`, best first. Empty if there is no such order book or it is
+    /// excluded. See [`OrderBooks::get_evaluable_order_book`]. With a rippling network its routes
+    /// from `from` into `to` are merged in as orders (see [`RipplingRoute::to_order`]).
+    ///
+    /// [`RipplingRoute::to_order`]: crate::order_books::rippling::RipplingRoute::to_order
+    pub(crate) fn get_leg_levels(
+        &self,
+        from: &Currency<'_>,
+        to: &Currency<'_>,
+        network: Option<&RipplingNetwork<'a>>,
+    ) -> Result<Vec<Order<'a>>> {
+        let mut levels = network
+            .map(|network| network.get_orders(from, to))
+            .unwrap_or_default();
+        if let Some(order_book) = self
+            .get_order_book(from, to)
+            .filter(|order_book| !self.is_excluded(order_book))
+        {
+            let side_type = if order_book.base.is_same_asset(from) {
+                OrderBookSideType::Bids
+            } else {
+                OrderBookSideType::Asks
+            };
+            levels.extend(get_levels(
+                &self.get_evaluable_order_book(order_book)?,
+                side_type,
+            )?);
+        }
+        levels.sort_by_key(|level| Reverse(level.rate));
+
+        Ok(levels)
+    }
+
+    /// Converts the network fee to the currency at the best level selling XRP for it. `None` if
+    /// there is no such level.
+    pub(crate) fn get_network_fee_in(
+        &self,
+        currency: &Currency<'_>,
+        network_fee_drops: u64,
+    ) -> Result<Option<Decimal>> {
+        let network_fee_xrp = Decimal::from(network_fee_drops) / Decimal::from(DROPS_PER_XRP);
+        if currency.is_xrp() || network_fee_drops == 0 {
+            return Ok(Some(network_fee_xrp));
+        }
+
+        Ok(self
+            .get_leg_levels(&Currency::xrp(), currency, None)?
+            .first()
+            .map(|order| network_fee_xrp * get_rate_after_fee(order)))
+    }
+
+    /// Returns the profitable swaps between the evaluable copies of the two order books in both
+    /// directions and with both of their currency codes as the trading currency, before the
+    /// trade filters.
+    pub(crate) fn get_swap_pair_trades(
+        &self,
+        first: &OrderBook<'a>,
+        second: &OrderBook<'a>,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ProfitableSwapTrade<'a>>> {
+        let first = self.get_evaluable_order_book(first)?;
+        let second = self.get_evaluable_order_book(second)?;
+        let trading_currencies = [
+            first.base.currency_code.clone(),
+            first.counter.currency_code.clone(),
+        ];
+        let mut profitable_trades = Vec::new();
+        for (sell, buy) in [(&first, &second), (&second, &first)] {
+            for trading_currency in trading_currencies.iter() {
+                let mut sell_order_book = sell.clone();
+                let mut buy_order_book = buy.clone();
+                // Order book sides without orders can't be swapped.
+                let Ok(trade) = SwapTrade::try_from((
+                    &mut sell_order_book,
+                    &mut buy_order_book,
+                    trading_currency.clone(),
+                )) else {
+                    continue;
+                };
+                if let Some(profitable_trade) =
+                    self.get_profitable_trade(trade, network_fee_drops)?
+                {
+                    profitable_trades.push(profitable_trade);
+                }
+            }
+        }
+
+        Ok(profitable_trades)
+    }
+}
 
-        counter_quantity_after_fee < other.calculate_counter_quantity_after_fee()
+impl<'a> GetProfitEstimate<'a> for SwapTrade<'a> {
+    fn get_profit_estimate(&self, network_fee_drops: u64) -> ProfitEstimate<'a> {
+        self.get_profit_estimate_for_size(&self.get_optimal_size(), network_fee_drops)
     }
 }
 
@@ -87,8 +300,8 @@ impl<'a> TryFrom<(&mut OrderBook<'a>, &mut OrderBook<'a>, Cow<'a, str>)> for Swa
             Cow<'a, str>,
         ),
     ) -> Result<Self> {
-        sell_order_book.sort();
-        buy_order_book.sort();
+        sell_order_book.sort()?;
+        buy_order_book.sort()?;
         if sell_order_book.base.is_same_currency(&buy_order_book.base)
             && sell_order_book
                 .counter
@@ -101,13 +314,11 @@ impl<'a> TryFrom<(&mut OrderBook<'a>, &mut OrderBook<'a>, Cow<'a, str>)> for Swa
             // *Order Book 2*: XRP/USD:Bitstamp <br>
             // *Consuming Bid of XRP/USD:GateHub*: XRP -> USD:GateHub (sell XRP) <br>
             // *Consuming Ask of XRP/USD:Bitstamp*: USD:Bitstamp -> XRP (buy XRP)
-            let mut buy_order = buy_order_book.get_asks()?.orders[0].clone();
-            buy_order.flip();
-            Ok(SwapTrade {
-                sell_order: sell_order_book.get_bids()?.orders[0].clone(),
-                buy_order,
-                starting_currency: sell_order_book.base.clone(),
-            })
+            SwapTrade::new(
+                get_levels(sell_order_book, OrderBookSideType::Bids)?,
+                get_levels(buy_order_book, OrderBookSideType::Asks)?,
+                sell_order_book.base.clone(),
+            )
         } else if sell_order_book.base.is_same_currency(&buy_order_book.base)
             && sell_order_book
                 .counter
@@ -120,13 +331,11 @@ impl<'a> TryFrom<(&mut OrderBook<'a>, &mut OrderBook<'a>, Cow<'a, str>)> for Swa
             // *Order Book 2*: XRP/USD:Bitstamp <br>
             // *Consuming Ask of XRP/USD:GateHub*: USD:GateHub -> XRP (sell USD) <br>
             // *Consuming Bid of XRP/USD:Bitstamp*: XRP -> USD:Bitstamp (buy USD)
-            let mut sell_order = sell_order_book.get_asks()?.orders[0].clone();
-            sell_order.flip();
-            Ok(SwapTrade {
-                sell_order,
-                buy_order: buy_order_book.get_bids()?.orders[0].clone(),
-                starting_currency: sell_order_book.counter.clone(),
-            })
+            SwapTrade::new(
+                get_levels(sell_order_book, OrderBookSideType::Asks)?,
+                get_levels(buy_order_book, OrderBookSideType::Bids)?,
+                sell_order_book.counter.clone(),
+            )
         } else if sell_order_book
             .base
             .is_same_currency(&buy_order_book.counter)
@@ -141,11 +350,17 @@ impl<'a> TryFrom<(&mut OrderBook<'a>, &mut OrderBook<'a>, Cow<'a, str>)> for Swa
             // *Order Book 2*: USD:Bitstamp/XRP <br>
             // *Consuming Bid of XRP/USD:GateHub*: XRP -> USD:GateHub (sell XRP) <br>
             // *Consuming Bid of USD:Bitstamp/XRP*: USD:Bitstamp -> XRP (buy XRP)
-            Ok(SwapTrade {
-                sell_order: sell_order_book.get_bids()?.orders[0].clone(),
-                buy_order: buy_order_book.get_bids()?.orders[0].clone(),
-                starting_currency: sell_order_book.base.clone(),
-            })
+            //
+            // ### 6. Example (Trade for more USD with first order book flipped)
+            // *Order Book 1*: USD:GateHub/XRP <br>
+            // *Order Book 2*: XRP/USD:Bitstamp <br>
+            // *Consuming Bid of USD:GateHub/XRP*: USD:GateHub -> XRP (sell USD) <br>
+            // *Consuming Bid of XRP/USD:Bitstamp*: XRP -> USD:Bitstamp (buy USD)
+            SwapTrade::new(
+                get_levels(sell_order_book, OrderBookSideType::Bids)?,
+                get_levels(buy_order_book, OrderBookSideType::Bids)?,
+                sell_order_book.base.clone(),
+            )
         } else if sell_order_book
             .base
             .is_same_currency(&buy_order_book.counter)
@@ -160,75 +375,212 @@ impl<'a> TryFrom<(&mut OrderBook<'a>, &mut OrderBook<'a>, Cow<'a, str>)> for Swa
             // *Order Book 2*: USD:Bitstamp/XRP <br>
             // *Consuming Ask of XRP/USD:GateHub*: USD:GateHub -> XRP (sell USD) <br>
             // *Consuming Ask of USD:Bitstamp/XRP*: XRP -> USD:Bitstamp (buy USD)
-            let mut sell_order = sell_order_book.get_asks()?.orders[0].clone();
-            sell_order.flip();
-            let mut buy_order = buy_order_book.get_asks()?.orders[0].clone();
-            buy_order.flip();
-            Ok(SwapTrade {
-                sell_order,
-                buy_order,
-                starting_currency: sell_order_book.counter.clone(),
-            })
+            //
+            // ### 5. Example (Trade for more XRP with first order book flipped)
+            // *Order Book 1*: USD:GateHub/XRP <br>
+            // *Order Book 2*: XRP/USD:Bitstamp <br>
+            // *Consuming Ask of USD:GateHub/XRP*: XRP -> USD:GateHub (sell XRP) <br>
+            // *Consuming Ask of XRP/USD:Bitstamp*: USD:Bitstamp -> XRP (buy XRP)
+            SwapTrade::new(
+                get_levels(sell_order_book, OrderBookSideType::Asks)?,
+                get_levels(buy_order_book, OrderBookSideType::Asks)?,
+                sell_order_book.counter.clone(),
+            )
         } else {
             bail!(SwapArbitrageException::InvalidOrderBookCombo)
         }
     }
 }
 
+/// A swap trade together with its profit estimate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfitableSwapTrade<'a> {
+    pub trade: SwapTrade<'a>,
+    /// The estimate at the optimal size or at the size the trading account can fund and the
+    /// trade filter allows.
+    pub estimate: ProfitEstimate<'a>,
+    /// The constraint which cut the trade short of its optimal size.
+    pub limited_by: Option<SwapConstraint>,
+    /// False if the trade is profitable at its optimal size but not at the size the trading
+    /// account can fund and the trade filter allows.
+    pub is_fundable: bool,
+}
+
+impl OrderBooks<'_> {
+    /// Groups the positions of the order books which are not excluded by their pair of currency
+    /// codes, no matter the orientation. Only order books within a group can be swapped. Order
+    /// books trading the same currency code on both sides are left out.
+    fn get_swap_candidates(&self) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+        for (position, order_book) in self.order_books.iter().enumerate() {
+            let (base_code, counter_code) = (
+                order_book.base.currency_code.as_ref(),
+                order_book.counter.currency_code.as_ref(),
+            );
+            if base_code == counter_code || self.is_excluded(order_book) {
+                continue;
+            }
+            let key = if base_code < counter_code {
+                (base_code, counter_code)
+            } else {
+                (counter_code, base_code)
+            };
+            groups.entry(key).or_default().push(position);
+        }
+
+        groups
+            .into_values()
+            .filter(|positions| positions.len() > 1)
+            .collect()
+    }
+
+    /// Estimates the trade and returns it if it is profitable at its optimal size. The estimate
+    /// is at the size the trading account can fund, if set, and the maximum input of the trade
+    /// filter allows. A swap not through XRP converts the network fee at the best level selling
+    /// XRP for the starting currency and is left out if there is no such level.
+    fn get_profitable_trade<'b>(
+        &self,
+        trade: SwapTrade<'b>,
+        network_fee_drops: u64,
+    ) -> Result<Option<ProfitableSwapTrade<'b>>> {
+        let network_fee = match trade.get_network_fee(network_fee_drops) {
+            Some(network_fee) => network_fee,
+            None => match self.get_network_fee_in(&trade.starting_currency, network_fee_drops)? {
+                Some(network_fee) => network_fee,
+                None => return Ok(None),
+            },
+        };
+        let estimate = trade.get_profit_estimate_with_network_fee(
+            &trade.get_optimal_size(),
+            network_fee_drops,
+            Some(network_fee),
+        );
+        if !estimate.is_profitable() {
+            return Ok(None);
+        }
+        let mut limits = match &self.trading_account {
+            Some(trading_account) => trade.get_limits(trading_account),
+            None => SwapLimits::default(),
+        };
+        let max_input_quantity = self
+            .trade_filters
+            .get_filter(&trade.starting_currency)
+            .max_input_quantity;
+        limits.input = min_limit(
+            limits.input,
+            max_input_quantity.map(|quantity| SwapLimit {
+                quantity,
+                constraint: SwapConstraint::MaxInputQuantity,
+            }),
+        );
+        let sizing = trade.get_size_within(&limits);
+        let estimate = trade.get_profit_estimate_with_network_fee(
+            &sizing,
+            network_fee_drops,
+            Some(network_fee),
+        );
+
+        Ok(Some(ProfitableSwapTrade {
+            limited_by: sizing.limited_by,
+            is_fundable: estimate.is_profitable(),
+            trade,
+            estimate,
+        }))
+    }
+}
+
+/// The profitable trades split by the trade filters, both sorted like
+/// [`GetProfitableTrades::get_profitable_trades`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilteredSwapTrades<'a> {
+    pub accepted: Vec<ProfitableSwapTrade<'a>>,
+    pub rejected: Vec<RejectedSwapTrade<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedSwapTrade<'a> {
+    pub profitable_trade: ProfitableSwapTrade<'a>,
+    pub reasons: Vec<RejectionReason>,
+}
+
+impl<'a> FilteredSwapTrades<'a> {
+    /// Adds the trade to the accepted or the rejected trades by the filter of its starting
+    /// currency.
+    pub fn push(
+        &mut self,
+        profitable_trade: ProfitableSwapTrade<'a>,
+        trade_filters: &TradeFilters,
+    ) {
+        let trade = &profitable_trade.trade;
+        let reasons = trade_filters
+            .get_filter(&trade.starting_currency)
+            .get_rejection_reasons(&profitable_trade.estimate, &trade.get_legs());
+        if reasons.is_empty() {
+            self.accepted.push(profitable_trade);
+        } else {
+            self.rejected.push(RejectedSwapTrade {
+                profitable_trade,
+                reasons,
+            });
+        }
+    }
+
+    /// Sorts both lists by profit, most profitable first.
+    pub fn sort(&mut self) {
+        self.accepted
+            .sort_by_key(|profitable_trade| Reverse(profitable_trade.estimate.profit));
+        self.rejected
+            .sort_by_key(|rejected_trade| Reverse(rejected_trade.profitable_trade.estimate.profit));
+    }
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Returns the (given, received) currencies of both legs.
+    pub fn get_legs(&self) -> [(&Currency<'a>, &Currency<'a>); 2] {
+        [
+            (&self.sell_order.base, &self.sell_order.counter),
+            (&self.buy_order.base, &self.buy_order.counter),
+        ]
+    }
+}
+
 impl GetProfitableTrades for OrderBooks<'_> {
-    fn get_profitable_trades(&self) -> Vec<SwapTrade<'_>> {
-        let mut profitable_trades = Vec::new();
-        for i in 0..self.order_books.len() {
-            for j in i + 1..self.order_books.len() {
-                let mut order_book_1 = self.order_books[i].clone();
-                order_book_1.sort();
-                let mut order_book_2 = self.order_books[j].clone();
-                order_book_2.sort();
-                let trading_base_currency = order_book_1.base.clone();
-                let trading_counter_currency = order_book_1.counter.clone();
-                let trade_1 = SwapTrade::try_from((
-                    &mut order_book_1,
-                    &mut order_book_2,
-                    trading_base_currency.currency_code.clone(),
-                ))
-                .unwrap();
-                let trade_2 = SwapTrade::try_from((
-                    &mut order_book_1,
-                    &mut order_book_2,
-                    trading_counter_currency.currency_code.clone(),
-                ))
-                .unwrap();
-                let trade_3 = SwapTrade::try_from((
-                    &mut order_book_2,
-                    &mut order_book_1,
-                    trading_base_currency.currency_code.clone(),
-                ))
-                .unwrap();
-                let trade_4 = SwapTrade::try_from((
-                    &mut order_book_2,
-                    &mut order_book_1,
-                    trading_counter_currency.currency_code.clone(),
-                ))
-                .unwrap();
-                if trade_1.is_profitable() {
-                    profitable_trades.push(trade_1);
-                }
-                if trade_2.is_profitable() {
-                    profitable_trades.push(trade_2);
-                }
-                if trade_3.is_profitable() {
-                    profitable_trades.push(trade_3);
-                }
-                if trade_4.is_profitable() {
-                    profitable_trades.push(trade_4);
+    fn get_filtered_trades(&self, network_fee_drops: u64) -> Result<FilteredSwapTrades<'_>> {
+        let mut filtered_trades = FilteredSwapTrades::default();
+        for positions in self.get_swap_candidates() {
+            for (index, &i) in positions.iter().enumerate() {
+                for &j in positions[index + 1..].iter() {
+                    for profitable_trade in self.get_swap_pair_trades(
+                        &self.order_books[i],
+                        &self.order_books[j],
+                        network_fee_drops,
+                    )? {
+                        filtered_trades.push(profitable_trade, &self.trade_filters);
+                    }
                 }
             }
         }
+        filtered_trades.sort();
 
-        profitable_trades
+        Ok(filtered_trades)
     }
 }
 
 pub trait GetProfitableTrades {
-    fn get_profitable_trades(&self) -> Vec<SwapTrade<'_>>;
+    /// Returns the profitable trades sorted by profit, most profitable first.
+    /// `network_fee_drops` is the fee of all transactions needed to execute one trade. If a
+    /// trading account is set, trades are sized to what it can fund and trades it can't fund
+    /// profitably are flagged and come last. Trades rejected by the trade filters are left out.
+    /// Order books are evaluated without our own orders, with unfunded orders reduced and with
+    /// the exposure caps applied, see [`OrderBooks::get_evaluable_order_book`].
+    fn get_profitable_trades(
+        &self,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ProfitableSwapTrade<'_>>> {
+        Ok(self.get_filtered_trades(network_fee_drops)?.accepted)
+    }
+
+    /// Returns the profitable trades split into the ones passing the trade filters and the
+    /// rejected ones with the reasons.
+    fn get_filtered_trades(&self, network_fee_drops: u64) -> Result<FilteredSwapTrades<'_>>;
 }
@@ -0,0 +1,186 @@
+use std::{borrow::Cow, cmp::Reverse, collections::HashMap};
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::{
+    models::{account::AccountState, currency::Currency},
+    order_books::OrderBooks,
+    trading_types::arbitrage::profit::ProfitEstimate,
+};
+
+use super::{
+    constraints::{min_limit, SwapConstraint, SwapLimit},
+    sizing::SwapSizing,
+    GetProfitableTrades, SwapTrade,
+};
+
+/// Where a swap exchanges a token for the token of another issuer with the same currency code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParityStep {
+    /// The sell leg receives another token than the buy leg gives, e.g. XRP -> USD:GateHub and
+    /// USD:Bitstamp -> XRP.
+    Intermediate,
+    /// The buy leg receives another token than the sell leg gives, e.g. USD:GateHub -> XRP and
+    /// XRP -> USD:Bitstamp.
+    Output,
+}
+
+/// Evaluates swaps between stablecoins of different issuers. Tokens with the same currency code
+/// are treated as near-par: each is worth its quantity less the haircut for the risk of its
+/// issuer.
+#[derive(Debug, Clone, Default)]
+pub struct ParityStrategy<'a> {
+    /// The share of the value deducted per issuer, e.g. 0.01 for 1 %.
+    pub haircuts: HashMap<Cow<'a, str>, Decimal>,
+    /// The haircut of issuers without their own.
+    pub default_haircut: Decimal,
+    /// The most of an issuer's tokens one trade may take in.
+    pub exposure_limits: HashMap<Cow<'a, str>, Decimal>,
+}
+
+/// A swap between stablecoins of different issuers with its edge after the haircuts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParityTrade<'a> {
+    pub trade: SwapTrade<'a>,
+    pub step: ParityStep,
+    /// The token given in exchange for `received`.
+    pub given: Currency<'a>,
+    pub received: Currency<'a>,
+    /// The size within the exposure limit of the issuer of `received`.
+    pub sizing: SwapSizing<'a>,
+    /// The estimate at par, i.e. without haircuts.
+    pub estimate: ProfitEstimate<'a>,
+    /// The amount of `received` taken in.
+    pub exposure: Decimal,
+    /// The haircuts of the exposure less the haircuts of what is given in exchange, in the
+    /// starting currency. Negative if `given` is riskier than `received`.
+    pub haircut_cost: Decimal,
+    /// The profit after the haircut cost.
+    pub net_edge: Decimal,
+    /// The net edge in percent of the input.
+    pub net_edge_pct: Decimal,
+}
+
+impl SwapTrade<'_> {
+    /// Returns where the swap exchanges tokens of different issuers with the same currency code
+    /// or `None` if it doesn't.
+    pub fn get_parity_step(&self) -> Option<ParityStep> {
+        let is_parity = |given: &Currency<'_>, received: &Currency<'_>| {
+            !given.is_xrp() && given.is_same_currency(received) && !given.is_same_asset(received)
+        };
+        if is_parity(&self.buy_order.base, &self.sell_order.counter) {
+            Some(ParityStep::Intermediate)
+        } else if is_parity(&self.sell_order.base, &self.buy_order.counter) {
+            Some(ParityStep::Output)
+        } else {
+            None
+        }
+    }
+}
+
+impl ParityStrategy<'_> {
+    /// Returns the haircut of the issuer. XRP has none.
+    pub fn get_haircut(&self, issuer: &str) -> Decimal {
+        if issuer.is_empty() {
+            return Decimal::ZERO;
+        }
+
+        self.haircuts
+            .get(issuer)
+            .copied()
+            .unwrap_or(self.default_haircut)
+    }
+
+    /// Sizes the swap within the exposure limit of the received issuer and, if given, what the
+    /// account can fund, and deducts the haircuts. Returns `None` if the swap doesn't exchange
+    /// stablecoins of different issuers or has no size within the limits.
+    pub fn evaluate<'b>(
+        &self,
+        trade: &SwapTrade<'b>,
+        account: Option<&AccountState<'_>>,
+        network_fee_drops: u64,
+    ) -> Option<ParityTrade<'b>> {
+        let step = trade.get_parity_step()?;
+        let (given, received) = match step {
+            ParityStep::Intermediate => (&trade.buy_order.base, &trade.sell_order.counter),
+            ParityStep::Output => (&trade.sell_order.base, &trade.buy_order.counter),
+        };
+        let mut limits = account
+            .map(|account| trade.get_limits(account))
+            .unwrap_or_default();
+        let exposure_limit = self
+            .exposure_limits
+            .get(received.issuer.as_ref())
+            .map(|&quantity| SwapLimit {
+                quantity,
+                constraint: SwapConstraint::ExposureLimit,
+            });
+        match step {
+            ParityStep::Intermediate => {
+                limits.intermediate = min_limit(limits.intermediate, exposure_limit)
+            }
+            ParityStep::Output => limits.output = min_limit(limits.output, exposure_limit),
+        }
+        let sizing = trade.get_size_within(&limits);
+        if sizing.input_quantity <= Decimal::ZERO {
+            return None;
+        }
+        let estimate = trade.get_profit_estimate_for_size(&sizing, network_fee_drops);
+        let (given_haircut, received_haircut) = (
+            self.get_haircut(&given.issuer),
+            self.get_haircut(&received.issuer),
+        );
+        let (exposure, haircut_cost) = match step {
+            // The intermediate tokens are converted into the starting currency at the rate of
+            // the buy leg.
+            ParityStep::Intermediate => (
+                sizing.intermediate_quantity,
+                sizing.output_quantity * (received_haircut - given_haircut),
+            ),
+            ParityStep::Output => (
+                sizing.output_quantity,
+                sizing.output_quantity * received_haircut - sizing.input_quantity * given_haircut,
+            ),
+        };
+        let net_edge = estimate.profit - haircut_cost;
+
+        Some(ParityTrade {
+            trade: trade.clone(),
+            step,
+            given: given.clone(),
+            received: received.clone(),
+            net_edge_pct: net_edge / sizing.input_quantity * Decimal::ONE_HUNDRED,
+            sizing,
+            estimate,
+            exposure,
+            haircut_cost,
+            net_edge,
+        })
+    }
+
+    /// Returns the swaps between stablecoins of different issuers with a positive net edge, best
+    /// first. Only swaps profitable at par are evaluated. If a trading account is set, swaps are
+    /// sized to what it can fund.
+    pub fn get_parity_trades<'b>(
+        &self,
+        order_books: &'b OrderBooks<'_>,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ParityTrade<'b>>> {
+        let mut parity_trades: Vec<ParityTrade<'b>> = order_books
+            .get_profitable_trades(network_fee_drops)?
+            .iter()
+            .filter_map(|profitable_trade| {
+                self.evaluate(
+                    &profitable_trade.trade,
+                    order_books.trading_account.as_ref(),
+                    network_fee_drops,
+                )
+            })
+            .filter(|parity_trade| parity_trade.net_edge > Decimal::ZERO)
+            .collect();
+        parity_trades.sort_by_key(|parity_trade| Reverse(parity_trade.net_edge));
+
+        Ok(parity_trades)
+    }
+}
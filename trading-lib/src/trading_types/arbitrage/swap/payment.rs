@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use xrpl::models::{
+    amount::Amount,
+    transactions::{OfferCreate, OfferCreateFlag, Payment, PaymentFlag},
+    FlagCollection,
+};
+
+use crate::{
+    models::currency::Currency,
+    trading_types::{
+        arbitrage::profit::DROPS_PER_XRP,
+        execution::{GetTransactions, TradeTransaction},
+    },
+};
+
+use super::{
+    exceptions::SwapArbitrageException,
+    funding_leg::{FundingLeg, SwapPlan},
+    sizing::SwapSizing,
+    SwapTrade,
+};
+
+/// How much of a swap paid as one payment has to be delivered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwapPaymentMode {
+    /// Delivers exactly the output of the sizing or fails.
+    Exact,
+    /// Delivers as much of the output as possible but at least `min_output_quantity`
+    /// (`tfPartialPayment` and `DeliverMin`). With `limit_quality` only liquidity at least as good
+    /// as the average rate of the sizing is taken (`tfLimitQuality`).
+    Partial {
+        min_output_quantity: Decimal,
+        limit_quality: bool,
+    },
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Builds one cross-currency payment from the account to itself executing both legs of the
+    /// swap through their order books. Unlike the two offers the payment is atomic: either all
+    /// of it executes or nothing, so the intermediate currency is never left over.
+    pub fn get_payment(
+        &self,
+        account: Cow<'a, str>,
+        sizing: &SwapSizing<'_>,
+        mode: SwapPaymentMode,
+    ) -> Result<Payment<'a>> {
+        if self.sell_order.base.is_xrp() && self.buy_order.counter.is_xrp() {
+            bail!(SwapArbitrageException::XrpRoundTripPayment);
+        }
+        if !self.sell_order.counter.is_same_asset(&self.buy_order.base) {
+            bail!(SwapArbitrageException::DisconnectedLegs);
+        }
+        if sizing.input_quantity <= Decimal::ZERO {
+            bail!(SwapArbitrageException::EmptySwapSize);
+        }
+        let mut flags = vec![PaymentFlag::TfNoDirectRipple];
+        let mut deliver_min = None;
+        if let SwapPaymentMode::Partial {
+            min_output_quantity,
+            limit_quality,
+        } = mode
+        {
+            if min_output_quantity > sizing.output_quantity {
+                bail!(SwapArbitrageException::InvalidMinOutputQuantity);
+            }
+            flags.push(PaymentFlag::TfPartialPayment);
+            if limit_quality {
+                flags.push(PaymentFlag::TfLimitQuality);
+            }
+            deliver_min = Some(get_amount(&self.buy_order.counter, min_output_quantity));
+        }
+        // Through the sell order book into the intermediate currency and through the buy order
+        // book back out of it.
+        let path = vec![
+            self.sell_order.counter.get_path_step(),
+            self.buy_order.counter.get_path_step(),
+        ];
+
+        Ok(Payment::new(
+            account.clone(),
+            None,
+            None,
+            Some(FlagCollection::new(flags)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            get_amount(&self.buy_order.counter, sizing.output_quantity),
+            account,
+            deliver_min,
+            None,
+            None,
+            Some(vec![path]),
+            Some(get_amount(&self.sell_order.base, sizing.input_quantity)),
+        ))
+    }
+}
+
+impl<'a> FundingLeg<'a> {
+    /// Builds the payment from the account to itself converting exactly the input of the held
+    /// currency into the output of the starting currency along the path.
+    pub fn get_payment(&self, account: Cow<'a, str>) -> Payment<'a> {
+        let path = self.path.currencies[1..]
+            .iter()
+            .map(Currency::get_path_step)
+            .collect();
+
+        Payment::new(
+            account.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            get_amount(
+                &self.path.currencies[self.path.currencies.len() - 1],
+                self.output_quantity,
+            ),
+            account,
+            None,
+            None,
+            None,
+            Some(vec![path]),
+            Some(get_amount(&self.path.currencies[0], self.input_quantity)),
+        )
+    }
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Returns the swap of the given size as one payment or, if it can't be paid at once, as one
+    /// offer per leg.
+    pub fn get_sized_transactions(
+        &self,
+        account: Cow<'a, str>,
+        sizing: &SwapSizing<'_>,
+    ) -> Result<Vec<TradeTransaction<'a>>> {
+        if self.is_payable_at_once() {
+            return Ok(vec![TradeTransaction::Payment(self.get_payment(
+                account,
+                sizing,
+                SwapPaymentMode::Exact,
+            )?)]);
+        }
+        if sizing.input_quantity <= Decimal::ZERO {
+            bail!(SwapArbitrageException::EmptySwapSize);
+        }
+        let (sell_offer, buy_offer) = self.get_offers(account, sizing);
+
+        Ok(vec![
+            TradeTransaction::OfferCreate(sell_offer),
+            TradeTransaction::OfferCreate(buy_offer),
+        ])
+    }
+
+    /// Returns the sell and the buy leg of the swap of the given size as one offer each. The
+    /// offers execute independently, see [`SwapTrade::get_payment`] for an atomic alternative.
+    pub fn get_offers(
+        &self,
+        account: Cow<'a, str>,
+        sizing: &SwapSizing<'_>,
+    ) -> (OfferCreate<'a>, OfferCreate<'a>) {
+        (
+            get_offer(
+                account.clone(),
+                (&self.sell_order.base, sizing.input_quantity),
+                (&self.sell_order.counter, sizing.intermediate_quantity),
+            ),
+            get_offer(
+                account,
+                (&self.buy_order.base, sizing.intermediate_quantity),
+                (&self.buy_order.counter, sizing.output_quantity),
+            ),
+        )
+    }
+}
+
+impl<'a> GetTransactions<'a> for SwapTrade<'a> {
+    /// Returns the swap at its optimal size.
+    fn get_transactions(&self, account: Cow<'a, str>) -> Result<Vec<TradeTransaction<'a>>> {
+        self.get_sized_transactions(account, &self.get_optimal_size())
+    }
+}
+
+impl<'a> GetTransactions<'a> for SwapPlan<'a> {
+    /// Returns the funding payment if there is a funding leg followed by the swap.
+    fn get_transactions(&self, account: Cow<'a, str>) -> Result<Vec<TradeTransaction<'a>>> {
+        let mut transactions = Vec::new();
+        if let Some(funding_leg) = &self.funding_leg {
+            transactions.push(TradeTransaction::Payment(
+                funding_leg.get_payment(account.clone()),
+            ));
+        }
+        transactions.extend(self.trade.get_sized_transactions(account, &self.sizing)?);
+
+        Ok(transactions)
+    }
+}
+
+/// Builds an offer giving exactly the first amount for at least the second one, immediately or
+/// not at all.
+pub(crate) fn get_offer<'a>(
+    account: Cow<'a, str>,
+    (given_currency, given_quantity): (&Currency<'a>, Decimal),
+    (received_currency, received_quantity): (&Currency<'a>, Decimal),
+) -> OfferCreate<'a> {
+    OfferCreate::new(
+        account,
+        None,
+        None,
+        Some(FlagCollection::new(vec![
+            OfferCreateFlag::TfImmediateOrCancel,
+            OfferCreateFlag::TfSell,
+        ])),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        get_amount(given_currency, given_quantity),
+        get_amount(received_currency, received_quantity),
+        None,
+        None,
+    )
+}
+
+/// XRP amounts are in drops, token amounts have at most 15 significant digits.
+pub(crate) fn get_amount<'a>(currency: &Currency<'a>, quantity: Decimal) -> Amount<'a> {
+    let quantity = if currency.is_xrp() {
+        (quantity * Decimal::from(DROPS_PER_XRP)).floor()
+    } else {
+        quantity.round_sf(15).unwrap_or(quantity).normalize()
+    };
+
+    currency.get_xrpl_amount(quantity.to_string().into())
+}
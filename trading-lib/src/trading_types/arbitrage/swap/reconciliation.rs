@@ -0,0 +1,224 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    models::{
+        currency::Currency,
+        metadata::{CurrencyAmount, TransactionOutcome},
+    },
+    trading_types::{arbitrage::profit::DROPS_PER_XRP, journal::JournalEntry},
+};
+
+use super::funding_leg::{FundingLeg, SwapPlan};
+
+/// What a leg was predicted to and actually did give and receive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LegFill {
+    pub given_quantity: Decimal,
+    pub received_quantity: Decimal,
+    pub predicted_given_quantity: Decimal,
+    pub predicted_received_quantity: Decimal,
+}
+
+impl LegFill {
+    /// Returns the realized rate in percent below the predicted one. `None` if either is unknown.
+    pub fn get_rate_slippage_pct(&self) -> Option<Decimal> {
+        if self.given_quantity.is_zero() || self.predicted_given_quantity.is_zero() {
+            return None;
+        }
+        let rate = self.received_quantity / self.given_quantity;
+        let predicted_rate = self.predicted_received_quantity / self.predicted_given_quantity;
+        if predicted_rate.is_zero() {
+            return None;
+        }
+
+        Some((predicted_rate - rate) / predicted_rate * Decimal::ONE_HUNDRED)
+    }
+}
+
+/// The realized outcome of an executed [`SwapPlan`] compared with its estimate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapReconciliation<'a> {
+    pub starting_currency: Currency<'a>,
+    pub hashes: Vec<String>,
+    pub is_success: bool,
+    /// `None` if the plan had no funding leg.
+    pub funding_fill: Option<LegFill>,
+    /// The sell and the buy leg.
+    pub leg_fills: [LegFill; 2],
+    /// The balance changes of all transactions without the fees.
+    pub balance_changes: Vec<CurrencyAmount<'a>>,
+    pub fee_drops: u64,
+    /// The realized cost of the funding leg, see [`FundingLeg::get_realized_cost`]. `None` if the
+    /// plan had no funding leg.
+    pub funding_cost: Option<Decimal>,
+    pub predicted_profit: Decimal,
+    /// The change of the starting currency by the swap minus the network fee and the funding cost,
+    /// comparable with the predicted profit.
+    pub realized_profit: Decimal,
+}
+
+impl<'a> SwapReconciliation<'a> {
+    /// Returns how much less profit was realized than predicted.
+    pub fn get_slippage(&self) -> Decimal {
+        self.predicted_profit - self.realized_profit
+    }
+
+    pub fn to_journal_entry(&self, timestamp: u64) -> JournalEntry<'a> {
+        let [sell_fill, buy_fill] = &self.leg_fills;
+
+        JournalEntry {
+            timestamp,
+            trade_type: "swap".to_string(),
+            starting_currency: self.starting_currency.clone(),
+            hashes: self.hashes.clone(),
+            is_success: self.is_success,
+            input_quantity: sell_fill.given_quantity,
+            output_quantity: buy_fill.received_quantity,
+            fee_drops: self.fee_drops,
+            predicted_profit: self.predicted_profit,
+            realized_profit: self.realized_profit,
+            slippage: self.get_slippage(),
+        }
+    }
+}
+
+impl FundingLeg<'_> {
+    /// Returns the cost of the funding leg like [`FundingLeg::get_cost`], but with the quantity
+    /// and the rate the funding payment actually delivered. Zero if nothing was funded.
+    pub fn get_realized_cost(&self, funding_fill: &LegFill) -> Decimal {
+        if funding_fill.given_quantity <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let rate = funding_fill.received_quantity / funding_fill.given_quantity;
+
+        funding_fill.received_quantity * (Decimal::ONE - self.unwind_rate * rate)
+    }
+}
+
+impl<'a> SwapPlan<'a> {
+    /// Reconciles the plan with the outcomes of its transactions, in the order of
+    /// `get_transactions`.
+    pub fn reconcile(&self, outcomes: &[TransactionOutcome<'a>]) -> SwapReconciliation<'a> {
+        let (funding_outcome, swap_outcomes) = match (&self.funding_leg, outcomes.split_first()) {
+            (Some(_), Some((funding_outcome, swap_outcomes))) => {
+                (Some(funding_outcome), swap_outcomes)
+            }
+            _ => (None, outcomes),
+        };
+        let funding_fill = self.funding_leg.as_ref().map(|funding_leg| LegFill {
+            given_quantity: -funding_outcome
+                .map(|outcome| outcome.get_balance_change(funding_leg.get_held_currency()))
+                .unwrap_or_default(),
+            received_quantity: funding_outcome
+                .and_then(|outcome| outcome.delivered_amount.as_ref())
+                .map(|delivered_amount| delivered_amount.quantity)
+                .unwrap_or_default(),
+            predicted_given_quantity: funding_leg.input_quantity,
+            predicted_received_quantity: funding_leg.output_quantity,
+        });
+        let funding_cost = self
+            .funding_leg
+            .as_ref()
+            .zip(funding_fill.as_ref())
+            .map(|(funding_leg, funding_fill)| funding_leg.get_realized_cost(funding_fill));
+        let get_leg_fill = |given: &Currency<'_>,
+                            received: &Currency<'_>,
+                            predicted_given_quantity: Decimal,
+                            predicted_received_quantity: Decimal| {
+            let mut leg_fill = LegFill {
+                predicted_given_quantity,
+                predicted_received_quantity,
+                ..Default::default()
+            };
+            // A leg between two tokens may be auto-bridged through XRP, filling given/XRP and
+            // XRP/received offers instead of or next to given/received ones.
+            let is_bridgeable = !given.is_xrp() && !received.is_xrp();
+            for offer_fill in swap_outcomes
+                .iter()
+                .flat_map(|outcome| outcome.offer_fills.iter())
+            {
+                let pays_given = offer_fill.taker_pays.currency.is_same_asset(given);
+                let gets_received = offer_fill.taker_gets.currency.is_same_asset(received);
+                if pays_given
+                    && (gets_received || is_bridgeable && offer_fill.taker_gets.currency.is_xrp())
+                {
+                    leg_fill.given_quantity += offer_fill.taker_pays.quantity;
+                }
+                if gets_received
+                    && (pays_given || is_bridgeable && offer_fill.taker_pays.currency.is_xrp())
+                {
+                    leg_fill.received_quantity += offer_fill.taker_gets.quantity;
+                }
+            }
+
+            leg_fill
+        };
+        let leg_fills = [
+            get_leg_fill(
+                &self.trade.sell_order.base,
+                &self.trade.sell_order.counter,
+                self.sizing.input_quantity,
+                self.sizing.intermediate_quantity,
+            ),
+            get_leg_fill(
+                &self.trade.buy_order.base,
+                &self.trade.buy_order.counter,
+                self.sizing.intermediate_quantity,
+                self.sizing.output_quantity,
+            ),
+        ];
+        let mut balance_changes: Vec<CurrencyAmount<'a>> = Vec::new();
+        for balance_change in outcomes
+            .iter()
+            .flat_map(|outcome| outcome.balance_changes.iter())
+        {
+            match balance_changes
+                .iter_mut()
+                .find(|existing| existing.currency.is_same_asset(&balance_change.currency))
+            {
+                Some(existing) => existing.quantity += balance_change.quantity,
+                None => balance_changes.push(balance_change.clone()),
+            }
+        }
+        let fee_drops = outcomes.iter().map(|outcome| outcome.fee_drops).sum();
+        // Issuers of the starting currency are interchangeable like in the estimate.
+        let swap_change: Decimal = swap_outcomes
+            .iter()
+            .flat_map(|outcome| outcome.balance_changes.iter())
+            .filter(|balance_change| {
+                balance_change
+                    .currency
+                    .is_same_currency(&self.trade.starting_currency)
+            })
+            .map(|balance_change| balance_change.quantity)
+            .sum();
+        // The network fee is converted like in the estimate.
+        let network_fee = if self.trade.starting_currency.is_xrp() {
+            Decimal::from(fee_drops) / Decimal::from(DROPS_PER_XRP)
+        } else {
+            match self.estimate.network_fee {
+                Some(network_fee) if self.estimate.network_fee_drops > 0 => {
+                    network_fee * Decimal::from(fee_drops)
+                        / Decimal::from(self.estimate.network_fee_drops)
+                }
+                _ => Decimal::ZERO,
+            }
+        };
+
+        SwapReconciliation {
+            starting_currency: self.trade.starting_currency.clone(),
+            hashes: outcomes
+                .iter()
+                .map(|outcome| outcome.hash.clone())
+                .collect(),
+            is_success: !outcomes.is_empty() && outcomes.iter().all(TransactionOutcome::is_success),
+            funding_fill,
+            leg_fills,
+            balance_changes,
+            fee_drops,
+            funding_cost,
+            predicted_profit: self.estimate.profit,
+            realized_profit: swap_change - network_fee - funding_cost.unwrap_or_default(),
+        }
+    }
+}
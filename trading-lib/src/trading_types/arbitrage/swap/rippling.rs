@@ -0,0 +1,117 @@
+use anyhow::Result;
+
+use crate::{
+    models::currency::Currency,
+    order_books::{
+        order::Order,
+        rippling::{RipplingNetwork, RipplingRoute},
+        OrderBooks,
+    },
+    trading_types::arbitrage::profit::ProfitEstimate,
+};
+
+use super::{constraints::SwapConstraint, FilteredSwapTrades, ProfitableSwapTrade, SwapTrade};
+
+/// A profitable swap between two tokens with the same currency code where at least one leg
+/// ripples through the network. Rippling routes aren't offers, so unlike a [`SwapTrade`] it
+/// can't be executed with offers or the order book paths of [`SwapTrade::get_payment`] and has
+/// no transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfitableRipplingTrade<'a> {
+    pub starting_currency: Currency<'a>,
+    /// The rippling routes and the order book levels of the sell leg as orders, best first.
+    pub sell_levels: Vec<Order<'a>>,
+    /// The rippling routes and the order book levels of the buy leg as orders, best first.
+    pub buy_levels: Vec<Order<'a>>,
+    /// The routes merged into the sell and the buy leg, best first.
+    pub routes: [Vec<RipplingRoute<'a>>; 2],
+    pub estimate: ProfitEstimate<'a>,
+    /// The constraint which cut the trade short of its optimal size.
+    pub limited_by: Option<SwapConstraint>,
+    /// See [`ProfitableSwapTrade::is_fundable`].
+    pub is_fundable: bool,
+}
+
+impl<'a> ProfitableRipplingTrade<'a> {
+    fn new(profitable_trade: ProfitableSwapTrade<'a>, routes: [Vec<RipplingRoute<'a>>; 2]) -> Self {
+        let ProfitableSwapTrade {
+            trade,
+            estimate,
+            limited_by,
+            is_fundable,
+        } = profitable_trade;
+
+        Self {
+            starting_currency: trade.starting_currency,
+            sell_levels: trade.sell_levels,
+            buy_levels: trade.buy_levels,
+            routes,
+            estimate,
+            limited_by,
+            is_fundable,
+        }
+    }
+}
+
+impl<'a> OrderBooks<'a> {
+    /// Returns the profitable swaps between two tokens with the same currency code where at least
+    /// one leg ripples through the network. Each leg merges the rippling routes with the levels
+    /// of the order book trading both tokens, if there is one, see [`OrderBooks::get_leg_levels`].
+    /// Sorted and filtered like
+    /// [`GetProfitableTrades::get_profitable_trades`](super::GetProfitableTrades).
+    pub fn get_rippling_trades(
+        &self,
+        network: &RipplingNetwork<'a>,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ProfitableRipplingTrade<'a>>> {
+        let currencies = network.get_currencies();
+        let mut filtered_trades = FilteredSwapTrades::default();
+        for (index, from) in currencies.iter().enumerate() {
+            for to in currencies[index + 1..].iter() {
+                if !from.is_same_currency(to) {
+                    continue;
+                }
+                if network.get_routes(from, to).is_empty()
+                    && network.get_routes(to, from).is_empty()
+                {
+                    continue;
+                }
+                let from_to_levels = self.get_leg_levels(from, to, Some(network))?;
+                let to_from_levels = self.get_leg_levels(to, from, Some(network))?;
+                for (sell_levels, buy_levels, starting_currency) in [
+                    (&from_to_levels, &to_from_levels, from),
+                    (&to_from_levels, &from_to_levels, to),
+                ] {
+                    // Legs without any level can't be swapped.
+                    let Ok(trade) = SwapTrade::new(
+                        sell_levels.clone(),
+                        buy_levels.clone(),
+                        starting_currency.clone(),
+                    ) else {
+                        continue;
+                    };
+                    if let Some(profitable_trade) =
+                        self.get_profitable_trade(trade, network_fee_drops)?
+                    {
+                        filtered_trades.push(profitable_trade, &self.trade_filters);
+                    }
+                }
+            }
+        }
+        filtered_trades.sort();
+
+        Ok(filtered_trades
+            .accepted
+            .into_iter()
+            .map(|profitable_trade| {
+                let trade = &profitable_trade.trade;
+                let routes = [
+                    network.get_routes(&trade.sell_order.base, &trade.sell_order.counter),
+                    network.get_routes(&trade.buy_order.base, &trade.buy_order.counter),
+                ];
+
+                ProfitableRipplingTrade::new(profitable_trade, routes)
+            })
+            .collect())
+    }
+}
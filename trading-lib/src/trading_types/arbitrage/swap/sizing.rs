@@ -0,0 +1,232 @@
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::order_books::order::Order;
+
+use super::{
+    constraints::{SwapConstraint, SwapLimit, SwapLimits},
+    SwapTrade,
+};
+
+/// The part of a level consumed by a swap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsumedLevel<'a> {
+    /// The position of the level in its leg, best first.
+    pub index: usize,
+    pub order: Order<'a>,
+    /// The amount given to the order in its base currency.
+    pub base_quantity: Decimal,
+}
+
+/// The size of a swap maximizing the net profit within its limits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwapSizing<'a> {
+    /// The amount of the starting currency given to the sell leg.
+    pub input_quantity: Decimal,
+    /// The amount received from the sell leg after the transfer fee and given to the buy leg.
+    pub intermediate_quantity: Decimal,
+    /// The amount of the starting currency received from the buy leg after the transfer fee.
+    pub output_quantity: Decimal,
+    pub sell_levels: Vec<ConsumedLevel<'a>>,
+    pub buy_levels: Vec<ConsumedLevel<'a>>,
+    /// The constraint which cut the swap short of its optimal size.
+    pub limited_by: Option<SwapConstraint>,
+}
+
+impl<'a> SwapSizing<'a> {
+    pub fn get_profit(&self) -> Decimal {
+        self.output_quantity - self.input_quantity
+    }
+
+    fn add_segment(&mut self, swap_trade: &SwapTrade<'a>, segment: &SwapSegment) {
+        add_consumed_level(
+            &mut self.sell_levels,
+            segment.sell_index,
+            &swap_trade.sell_levels[segment.sell_index],
+            segment.input_quantity,
+        );
+        add_consumed_level(
+            &mut self.buy_levels,
+            segment.buy_index,
+            &swap_trade.buy_levels[segment.buy_index],
+            segment.intermediate_quantity,
+        );
+        self.input_quantity += segment.input_quantity;
+        self.intermediate_quantity += segment.intermediate_quantity;
+        self.output_quantity += segment.output_quantity;
+    }
+}
+
+/// A stretch of a swap where neither leg moves to its next level.
+#[derive(Debug, Clone, PartialEq)]
+struct SwapSegment {
+    sell_index: usize,
+    buy_index: usize,
+    input_quantity: Decimal,
+    intermediate_quantity: Decimal,
+    output_quantity: Decimal,
+}
+
+impl<'a> SwapTrade<'a> {
+    /// Walks both legs level by level and consumes them as long as the next unit of the starting
+    /// currency returns more than it costs. As the levels get worse the deeper they are, this is
+    /// the size with the highest net profit. An unprofitable swap has a size of zero.
+    pub fn get_optimal_size(&self) -> SwapSizing<'a> {
+        self.get_size_within(&SwapLimits::default())
+    }
+
+    /// Returns the optimal size shrunk until no step of the swap exceeds its limit.
+    pub fn get_size_within(&self, limits: &SwapLimits) -> SwapSizing<'a> {
+        let mut sizing = SwapSizing::default();
+        for mut segment in self.get_profitable_segments() {
+            // The share of the segment left within all limits.
+            let mut share = Decimal::ONE;
+            for (limit, used, quantity) in [
+                (limits.input, sizing.input_quantity, segment.input_quantity),
+                (
+                    limits.intermediate,
+                    sizing.intermediate_quantity,
+                    segment.intermediate_quantity,
+                ),
+                (
+                    limits.output,
+                    sizing.output_quantity,
+                    segment.output_quantity,
+                ),
+            ] {
+                let Some(SwapLimit {
+                    quantity: max_quantity,
+                    constraint,
+                }) = limit
+                else {
+                    continue;
+                };
+                if used + quantity > max_quantity {
+                    let limit_share = ((max_quantity - used) / quantity).max(Decimal::ZERO);
+                    if limit_share < share {
+                        share = limit_share;
+                        sizing.limited_by = Some(constraint);
+                    }
+                }
+            }
+            if share < Decimal::ONE {
+                segment.input_quantity *= share;
+                segment.intermediate_quantity *= share;
+                segment.output_quantity *= share;
+            }
+            if segment.input_quantity > Decimal::ZERO {
+                sizing.add_segment(self, &segment);
+            }
+            if sizing.limited_by.is_some() {
+                break;
+            }
+        }
+
+        sizing
+    }
+
+    /// Returns the smallest input of the starting currency whose profit covers `fixed_cost`
+    /// (e.g. the network fee) or `None` if no size does.
+    pub fn get_break_even_quantity(&self, fixed_cost: Decimal) -> Option<Decimal> {
+        let mut input_quantity = Decimal::ZERO;
+        let mut profit = Decimal::ZERO;
+        for segment in self.get_profitable_segments() {
+            let segment_profit = segment.output_quantity - segment.input_quantity;
+            if profit + segment_profit >= fixed_cost {
+                let missing_profit = (fixed_cost - profit).max(Decimal::ZERO);
+                let profit_per_input = segment_profit / segment.input_quantity;
+
+                return Some(input_quantity + missing_profit / profit_per_input);
+            }
+            input_quantity += segment.input_quantity;
+            profit += segment_profit;
+        }
+
+        None
+    }
+
+    fn get_profitable_segments(&self) -> Vec<SwapSegment> {
+        let mut segments = Vec::new();
+        let (mut sell_index, mut buy_index) = (0, 0);
+        let get_quantity = |levels: &[Order<'_>], index: usize| {
+            levels
+                .get(index)
+                .map(|order| order.base_quantity)
+                .unwrap_or_default()
+        };
+        // The amounts the current level of each leg still takes in its base currency.
+        let mut sell_left = get_quantity(&self.sell_levels, 0);
+        let mut buy_left = get_quantity(&self.buy_levels, 0);
+        while let (Some(sell_order), Some(buy_order)) = (
+            self.sell_levels.get(sell_index),
+            self.buy_levels.get(buy_index),
+        ) {
+            let sell_rate = get_rate_after_fee(sell_order);
+            let buy_rate = get_rate_after_fee(buy_order);
+            if sell_rate * buy_rate <= Decimal::ONE {
+                break;
+            }
+            // The input is limited by the sell level or by what the buy level takes in return.
+            let is_sell_limited = sell_left * sell_rate <= buy_left;
+            let input = if is_sell_limited {
+                sell_left
+            } else {
+                buy_left / sell_rate
+            };
+            let intermediate = input * sell_rate;
+            if input > Decimal::ZERO {
+                segments.push(SwapSegment {
+                    sell_index,
+                    buy_index,
+                    input_quantity: input,
+                    intermediate_quantity: intermediate,
+                    output_quantity: intermediate * buy_rate,
+                });
+            }
+            if is_sell_limited {
+                sell_index += 1;
+                sell_left = get_quantity(&self.sell_levels, sell_index);
+                buy_left -= intermediate;
+            } else {
+                buy_index += 1;
+                buy_left = get_quantity(&self.buy_levels, buy_index);
+                sell_left -= input;
+            }
+        }
+
+        segments
+    }
+}
+
+/// The amount of counter currency received for one base currency after the transfer fee.
+pub(crate) fn get_rate_after_fee(order: &Order<'_>) -> Decimal {
+    order.rate * (Decimal::ONE - Decimal::from_f32(order.counter.transfer_fee).unwrap_or_default())
+}
+
+/// Returns the transfer fee paid for the consumed levels in the currency they receive.
+pub(crate) fn get_transfer_fee(levels: &[ConsumedLevel<'_>]) -> Decimal {
+    levels
+        .iter()
+        .map(|level| {
+            level.base_quantity
+                * level.order.rate
+                * Decimal::from_f32(level.order.counter.transfer_fee).unwrap_or_default()
+        })
+        .sum()
+}
+
+/// Adds the quantity to the last consumed level if it is the same level.
+pub(crate) fn add_consumed_level<'a>(
+    levels: &mut Vec<ConsumedLevel<'a>>,
+    index: usize,
+    order: &Order<'a>,
+    base_quantity: Decimal,
+) {
+    match levels.last_mut() {
+        Some(level) if level.index == index => level.base_quantity += base_quantity,
+        _ => levels.push(ConsumedLevel {
+            index,
+            order: order.clone(),
+            base_quantity,
+        }),
+    }
+}
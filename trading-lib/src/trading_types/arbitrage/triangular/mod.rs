@@ -1 +1,79 @@
+use std::{cmp::Reverse, collections::BTreeMap};
 
+use anyhow::Result;
+
+use crate::{
+    models::currency::Currency,
+    order_books::{order::Order, OrderBooks},
+};
+
+use super::cycle::{get_position, CycleTrade, ProfitableCycleTrade};
+
+/// Represents a cycle through three order books from the perspective of the trader, e.g.
+/// XRP -> USD:GateHub -> EUR:Bitstamp -> XRP:
+/// *Consuming Bid of XRP/USD:GateHub*: XRP -> USD:GateHub <br>
+/// *Consuming Bid of USD:GateHub/EUR:Bitstamp*: USD:GateHub -> EUR:Bitstamp <br>
+/// *Consuming Ask of XRP/EUR:Bitstamp*: EUR:Bitstamp -> XRP
+///
+/// It is a [`CycleTrade`] with three legs.
+pub type TriangularTrade<'a> = CycleTrade<'a>;
+
+/// A triangular trade together with its size and profit estimate.
+pub type ProfitableTriangularTrade<'a> = ProfitableCycleTrade<'a>;
+
+impl<'a> OrderBooks<'a> {
+    /// Returns the profitable cycles through three order books which are not excluded, sorted by
+    /// profit, most profitable first. Rotations of a cycle are returned once, starting with the
+    /// currency of the cycle traded first by the order books. Use
+    /// [`OrderBooks::get_cycle_trades`] with an anchor to start with another currency.
+    /// `network_fee_drops` is the fee of all transactions needed to execute one trade. Trades
+    /// are sized and filtered by the trade filter of their starting currency, rejected trades
+    /// are left out.
+    pub fn get_triangular_trades(
+        &self,
+        network_fee_drops: u64,
+    ) -> Result<Vec<ProfitableTriangularTrade<'a>>> {
+        let mut currencies: Vec<Currency<'a>> = Vec::new();
+        // The levels of every leg by the positions of its currencies in `currencies`.
+        let mut legs: BTreeMap<(usize, usize), Vec<Order<'a>>> = BTreeMap::new();
+        for order_book in self.order_books.iter() {
+            if self.is_excluded(order_book) {
+                continue;
+            }
+            let base = get_position(&mut currencies, &order_book.base);
+            let counter = get_position(&mut currencies, &order_book.counter);
+            for (from, to) in [(base, counter), (counter, base)] {
+                let levels = self.get_leg_levels(&currencies[from], &currencies[to], None)?;
+                if !levels.is_empty() {
+                    legs.insert((from, to), levels);
+                }
+            }
+        }
+        let mut profitable_trades = Vec::new();
+        for (&(first, second), first_levels) in legs.iter() {
+            for (&(_, third), second_levels) in legs.range((second, 0)..(second + 1, 0)) {
+                // Every rotation of the cycle is visited, only the one starting with its lowest
+                // position is kept.
+                if second < first || third <= first {
+                    continue;
+                }
+                let Some(third_levels) = legs.get(&(third, first)) else {
+                    continue;
+                };
+                let trade = TriangularTrade::new(vec![
+                    first_levels.clone(),
+                    second_levels.clone(),
+                    third_levels.clone(),
+                ])?;
+                if let Some(profitable_trade) =
+                    self.get_profitable_cycle_trade(trade, network_fee_drops)?
+                {
+                    profitable_trades.push(profitable_trade);
+                }
+            }
+        }
+        profitable_trades.sort_by_key(|profitable_trade| Reverse(profitable_trade.estimate.profit));
+
+        Ok(profitable_trades)
+    }
+}
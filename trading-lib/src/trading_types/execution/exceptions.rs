@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExecutionException {
+    #[error("The trade has no transactions to execute.")]
+    NoTransactions,
+    #[error("{transaction_count} transactions need as many tickets but only {ticket_count} are available.")]
+    NotEnoughTickets {
+        transaction_count: usize,
+        ticket_count: usize,
+    },
+    #[error("The {transaction_count} transactions of the trade depend on each other and can't use tickets, which don't enforce their order.")]
+    DependentTransactions { transaction_count: usize },
+    #[error("{transaction_count} consecutive sequences starting at {sequence} exceed the largest sequence.")]
+    SequenceOverflow {
+        sequence: u32,
+        transaction_count: usize,
+    },
+    #[error("The max ledger offset {max_ledger_offset} after ledger {current_ledger_index} exceeds the largest ledger index.")]
+    LedgerIndexOverflow {
+        current_ledger_index: u32,
+        max_ledger_offset: u32,
+    },
+}
@@ -0,0 +1,266 @@
+pub mod exceptions;
+
+use std::{borrow::Cow, fmt::Write};
+
+use anyhow::{bail, Result};
+use exceptions::ExecutionException;
+use rust_decimal::Decimal;
+use xrpl::models::{
+    amount::{Amount, XRPAmount},
+    transactions::{OfferCreate, Payment},
+};
+
+use super::arbitrage::profit::DROPS_PER_XRP;
+
+/// A transaction executing part of a trade.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeTransaction<'a> {
+    Payment(Payment<'a>),
+    OfferCreate(OfferCreate<'a>),
+}
+
+impl<'a> TradeTransaction<'a> {
+    pub fn get_transaction_type(&self) -> &'static str {
+        match self {
+            Self::Payment(_) => "Payment",
+            Self::OfferCreate(_) => "OfferCreate",
+        }
+    }
+
+    /// Describes what the transaction gives and receives.
+    pub fn get_description(&self) -> String {
+        match self {
+            Self::Payment(payment) => format!(
+                "send max {} to deliver {}",
+                payment
+                    .send_max
+                    .as_ref()
+                    .map(format_amount)
+                    .unwrap_or_else(|| "-".to_string()),
+                format_amount(&payment.amount)
+            ),
+            Self::OfferCreate(offer_create) => format!(
+                "give {} for {}",
+                format_amount(&offer_create.taker_gets),
+                format_amount(&offer_create.taker_pays)
+            ),
+        }
+    }
+
+    fn set_common_fields(
+        &mut self,
+        account: Cow<'a, str>,
+        fee_drops: u64,
+        slot: SequenceSlot,
+        last_ledger_sequence: u32,
+    ) {
+        let fee = Some(XRPAmount(fee_drops.to_string().into()));
+        // Transactions using a ticket have a sequence of zero.
+        let (sequence, ticket_sequence) = match slot {
+            SequenceSlot::Sequence(sequence) => (Some(sequence), None),
+            SequenceSlot::Ticket(ticket_sequence) => (Some(0), Some(ticket_sequence)),
+        };
+        match self {
+            Self::Payment(payment) => {
+                payment.account = account;
+                payment.fee = fee;
+                payment.sequence = sequence;
+                payment.ticket_sequence = ticket_sequence;
+                payment.last_ledger_sequence = Some(last_ledger_sequence);
+            }
+            Self::OfferCreate(offer_create) => {
+                offer_create.account = account;
+                offer_create.fee = fee;
+                offer_create.sequence = sequence;
+                offer_create.ticket_sequence = ticket_sequence;
+                offer_create.last_ledger_sequence = Some(last_ledger_sequence);
+            }
+        }
+    }
+}
+
+pub trait GetTransactions<'a> {
+    /// Returns the transactions executing the trade from the account in the order they depend on
+    /// each other.
+    fn get_transactions(&self, account: Cow<'a, str>) -> Result<Vec<TradeTransaction<'a>>>;
+}
+
+/// How the transactions of a plan are sequenced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sequencing {
+    /// The next sequence of the account. The transactions get consecutive sequences and only
+    /// validate in order.
+    Sequence(u32),
+    /// Tickets created beforehand, one per transaction. Tickets don't enforce an order, so they
+    /// are only used for trades executed with a single transaction.
+    Tickets(Vec<u32>),
+}
+
+/// The sequence or ticket a transaction uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceSlot {
+    Sequence(u32),
+    Ticket(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionSettings<'a> {
+    pub account: Cow<'a, str>,
+    pub sequencing: Sequencing,
+    /// The fee of every transaction.
+    pub fee_drops: u64,
+    /// The latest validated ledger.
+    pub current_ledger_index: u32,
+    /// The number of ledgers after the current one the transactions may validate in.
+    pub max_ledger_offset: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedTransaction<'a> {
+    pub transaction: TradeTransaction<'a>,
+    pub slot: SequenceSlot,
+    pub fee_drops: u64,
+}
+
+/// The transactions of a trade ready to be signed and submitted in order. Submitted together
+/// they can validate in the same ledger. All of them share the same `LastLedgerSequence`, so they
+/// either validate within the same few ledgers or expire together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionPlan<'a> {
+    pub transactions: Vec<PlannedTransaction<'a>>,
+    pub last_ledger_sequence: u32,
+}
+
+impl<'a> ExecutionPlan<'a> {
+    pub fn new(trade: &impl GetTransactions<'a>, settings: &ExecutionSettings<'a>) -> Result<Self> {
+        let mut transactions = trade.get_transactions(settings.account.clone())?;
+        if transactions.is_empty() {
+            bail!(ExecutionException::NoTransactions);
+        }
+        let slots: Vec<SequenceSlot> = match &settings.sequencing {
+            Sequencing::Sequence(sequence) => {
+                // The sequence following the plan must exist as well.
+                if u32::try_from(transactions.len())
+                    .ok()
+                    .and_then(|count| sequence.checked_add(count))
+                    .is_none()
+                {
+                    bail!(ExecutionException::SequenceOverflow {
+                        sequence: *sequence,
+                        transaction_count: transactions.len(),
+                    });
+                }
+                (*sequence..)
+                    .take(transactions.len())
+                    .map(SequenceSlot::Sequence)
+                    .collect()
+            }
+            Sequencing::Tickets(tickets) => {
+                // The transactions of a trade are in the order they depend on each other.
+                if transactions.len() > 1 {
+                    bail!(ExecutionException::DependentTransactions {
+                        transaction_count: transactions.len(),
+                    });
+                }
+                if tickets.len() < transactions.len() {
+                    bail!(ExecutionException::NotEnoughTickets {
+                        transaction_count: transactions.len(),
+                        ticket_count: tickets.len(),
+                    });
+                }
+                tickets
+                    .iter()
+                    .take(transactions.len())
+                    .copied()
+                    .map(SequenceSlot::Ticket)
+                    .collect()
+            }
+        };
+        let Some(last_ledger_sequence) = settings
+            .current_ledger_index
+            .checked_add(settings.max_ledger_offset)
+        else {
+            bail!(ExecutionException::LedgerIndexOverflow {
+                current_ledger_index: settings.current_ledger_index,
+                max_ledger_offset: settings.max_ledger_offset,
+            });
+        };
+        let transactions = transactions
+            .drain(..)
+            .zip(slots)
+            .map(|(mut transaction, slot)| {
+                transaction.set_common_fields(
+                    settings.account.clone(),
+                    settings.fee_drops,
+                    slot,
+                    last_ledger_sequence,
+                );
+
+                PlannedTransaction {
+                    transaction,
+                    slot,
+                    fee_drops: settings.fee_drops,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            transactions,
+            last_ledger_sequence,
+        })
+    }
+
+    pub fn get_total_fee_drops(&self) -> u64 {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.fee_drops)
+            .sum()
+    }
+
+    /// Returns the account sequence following the plan or `None` if it uses tickets.
+    pub fn get_next_sequence(&self) -> Option<u32> {
+        match self.transactions.last()?.slot {
+            SequenceSlot::Sequence(sequence) => sequence.checked_add(1),
+            SequenceSlot::Ticket(_) => None,
+        }
+    }
+
+    /// Describes the transactions without submitting them, one line each.
+    pub fn get_dry_run(&self) -> String {
+        let mut dry_run = String::new();
+        for (index, planned) in self.transactions.iter().enumerate() {
+            let slot = match planned.slot {
+                SequenceSlot::Sequence(sequence) => format!("sequence {sequence}"),
+                SequenceSlot::Ticket(ticket_sequence) => format!("ticket {ticket_sequence}"),
+            };
+            let _ = writeln!(
+                dry_run,
+                "{}. {} ({slot}, fee {} drops): {}",
+                index + 1,
+                planned.transaction.get_transaction_type(),
+                planned.fee_drops,
+                planned.transaction.get_description()
+            );
+        }
+        let _ = writeln!(
+            dry_run,
+            "Total fee {} drops, valid until ledger {}",
+            self.get_total_fee_drops(),
+            self.last_ledger_sequence
+        );
+
+        dry_run
+    }
+}
+
+fn format_amount(amount: &Amount<'_>) -> String {
+    match amount {
+        Amount::XRPAmount(XRPAmount(drops)) => match drops.parse::<Decimal>() {
+            Ok(drops) => format!("{} XRP", (drops / Decimal::from(DROPS_PER_XRP)).normalize()),
+            Err(_) => format!("{drops} drops"),
+        },
+        Amount::IssuedCurrencyAmount(amount) => {
+            format!("{} {}.{}", amount.value, amount.currency, amount.issuer)
+        }
+    }
+}
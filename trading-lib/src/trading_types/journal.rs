@@ -0,0 +1,84 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::currency::Currency;
+
+/// The realized outcome of an executed trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry<'a> {
+    /// The close time of the ledger the trade validated in, in seconds.
+    pub timestamp: u64,
+    /// The kind of trade, e.g. `swap`.
+    pub trade_type: String,
+    pub starting_currency: Currency<'a>,
+    pub hashes: Vec<String>,
+    pub is_success: bool,
+    pub input_quantity: Decimal,
+    pub output_quantity: Decimal,
+    pub fee_drops: u64,
+    pub predicted_profit: Decimal,
+    pub realized_profit: Decimal,
+    /// The predicted minus the realized profit.
+    pub slippage: Decimal,
+}
+
+/// Appends journal entries as one JSON object per line, e.g. to a file opened in append mode.
+pub struct TradeJournalWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TradeJournalWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes and flushes the entry so it survives a crash.
+    pub fn write_entry(&mut self, entry: &JournalEntry<'_>) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")?;
+
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads the entries written by a [`TradeJournalWriter`].
+pub struct TradeJournalReader<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> TradeJournalReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Returns the next entry or `None` if the end of the journal is reached.
+    pub fn read_entry(&mut self) -> Result<Option<JournalEntry<'static>>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        Ok(Some(serde_json::from_str(&line)?))
+    }
+}
+
+impl<R: BufRead> Iterator for TradeJournalReader<R> {
+    type Item = Result<JournalEntry<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry().transpose()
+    }
+}
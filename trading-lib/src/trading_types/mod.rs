@@ -2,5 +2,8 @@
 pub mod arbitrage;
 #[cfg(feature = "automated-market-maker")]
 pub mod automated_market_maker;
+#[cfg(feature = "xrpl")]
+pub mod execution;
+pub mod journal;
 #[cfg(feature = "market-maker")]
 pub mod market_maker;
@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+
+use rust_decimal::Decimal;
+use trading_lib::{
+    models::currency::Currency,
+    order_books::{order::Order, order_book::OrderBook, OrderBooks},
+};
+
+pub const GATEHUB: &str = "rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS";
+pub const BITSTAMP: &str = "rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy";
+pub const SNAPSWAP: &str = "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B";
+pub const ANODE: &str = "rAPKsP3tt7fV9Vj2QWzBk1r4Fg5vY1YhZ";
+
+pub fn xrp() -> Currency<'static> {
+    Currency::xrp()
+}
+
+pub fn token(currency_code: &'static str, issuer: &'static str) -> Currency<'static> {
+    Currency {
+        currency_code: Cow::Borrowed(currency_code),
+        issuer: Cow::Borrowed(issuer),
+        transfer_fee: 0.0,
+    }
+}
+
+/// Builds an order book from `(base quantity, rate in hundredths)` pairs.
+pub fn order_book(
+    base: Currency<'static>,
+    counter: Currency<'static>,
+    bids: &[(i64, i64)],
+    asks: &[(i64, i64)],
+) -> OrderBook<'static> {
+    let to_orders = |levels: &[(i64, i64)]| {
+        levels
+            .iter()
+            .map(|&(base_quantity, rate)| Order {
+                base: base.clone(),
+                counter: counter.clone(),
+                base_quantity: Decimal::from(base_quantity),
+                rate: Decimal::new(rate, 2),
+                offer_id: None,
+            })
+            .collect()
+    };
+
+    OrderBook::new(
+        base.clone(),
+        counter.clone(),
+        to_orders(bids),
+        to_orders(asks),
+    )
+}
+
+/// Order books of several markets in both orientations:
+/// 0. XRP/USD:GateHub
+/// 1. USD:Bitstamp/XRP - bids 4.80 XRP per USD, so XRP sold on GateHub is bought back cheaper
+/// 2. EUR:Anode/USD:GateHub
+/// 3. USD:Bitstamp/EUR:Anode
+/// 4. XRP/EUR:Anode - the only XRP/EUR market
+/// 5. XRP/USD:SnapSwap - without asks
+/// 6. BTC:GateHub/ETH:GateHub - unrelated market
+pub fn mixed_order_books_list() -> Vec<OrderBook<'static>> {
+    vec![
+        order_book(
+            xrp(),
+            token("USD", GATEHUB),
+            &[(100, 24), (80, 23)],
+            &[(100, 26), (100, 28)],
+        ),
+        order_book(
+            token("USD", BITSTAMP),
+            xrp(),
+            &[(10, 480), (20, 470)],
+            &[(20, 500), (20, 520)],
+        ),
+        order_book(
+            token("EUR", ANODE),
+            token("USD", GATEHUB),
+            &[(100, 104)],
+            &[(100, 106)],
+        ),
+        order_book(
+            token("USD", BITSTAMP),
+            token("EUR", ANODE),
+            &[(100, 90)],
+            &[(100, 92)],
+        ),
+        order_book(xrp(), token("EUR", ANODE), &[(100, 22)], &[(100, 24)]),
+        order_book(xrp(), token("USD", SNAPSWAP), &[(100, 25)], &[]),
+        order_book(
+            token("BTC", GATEHUB),
+            token("ETH", GATEHUB),
+            &[(1, 1500)],
+            &[(1, 1600)],
+        ),
+    ]
+}
+
+pub fn mixed_order_books() -> OrderBooks<'static> {
+    OrderBooks::new(mixed_order_books_list().into(), 0.05)
+}
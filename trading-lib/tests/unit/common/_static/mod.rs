@@ -1 +1,3 @@
+pub mod mixed_markets;
 pub mod order_books;
+pub mod trust_lines;
@@ -1,4 +1,7 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
 
 use rust_decimal::Decimal;
 use trading_lib::{
@@ -28,6 +31,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(80, 0, 0, false, 0),
                 rate: Decimal::from_parts(23, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -42,6 +46,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(24, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
         // 1.2 XRP/USD asks
@@ -59,6 +64,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(26, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -73,6 +79,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(28, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
     ],
@@ -93,6 +100,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(80, 0, 0, false, 0),
                 rate: Decimal::from_parts(103, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -107,6 +115,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(104, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
         // 2.2 EUR/USD asks
@@ -124,6 +133,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(106, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -138,6 +148,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(108, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
     ],
@@ -158,6 +169,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(80, 0, 0, false, 0),
                 rate: Decimal::from_parts(23, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -172,6 +184,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(24, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
         // 3.2 XRP/EUR asks
@@ -189,6 +202,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(26, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -203,6 +217,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(28, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
     ],
@@ -223,6 +238,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(80, 0, 0, false, 0),
                 rate: Decimal::from_parts(23, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -237,6 +253,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(24, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
         // 4.2 XRP/USD asks
@@ -254,6 +271,7 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(26, 0, 0, false, 2),
+                offer_id: None,
             },
             Order {
                 base: Currency {
@@ -268,95 +286,101 @@ pub static ORDERS: &[&[&[Order<'_>]]] = &[
                 },
                 base_quantity: Decimal::from_parts(100, 0, 0, false, 0),
                 rate: Decimal::from_parts(28, 0, 0, false, 2),
+                offer_id: None,
             },
         ],
     ],
 ];
 
-pub static ORDER_BOOKS_LIST: &[OrderBook<'_>] = &[
-    OrderBook {
-        base: Currency {
-            currency_code: Cow::Borrowed("XRP"),
-            issuer: Cow::Borrowed(""),
-            transfer_fee: 0.0,
-        },
-        counter: Currency {
-            currency_code: Cow::Borrowed("USD"),
-            issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
-            transfer_fee: 0.1,
-        },
-        bids: OrderBookSide {
-            side_type: OrderBookSideType::Bids,
-            orders: Cow::Borrowed(ORDERS[0][0]),
-        },
-        asks: OrderBookSide {
-            side_type: OrderBookSideType::Asks,
-            orders: Cow::Borrowed(ORDERS[0][1]),
-        },
-    },
-    OrderBook {
-        base: Currency {
-            currency_code: Cow::Borrowed("EUR"),
-            issuer: Cow::Borrowed("rAPKsP3tt7fV9Vj2QWzBk1r4Fg5vY1YhZ"),
-            transfer_fee: 0.1,
-        },
-        counter: Currency {
-            currency_code: Cow::Borrowed("USD"),
-            issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
-            transfer_fee: 0.1,
-        },
-        bids: OrderBookSide {
-            side_type: OrderBookSideType::Bids,
-            orders: Cow::Borrowed(ORDERS[1][0]),
-        },
-        asks: OrderBookSide {
-            side_type: OrderBookSideType::Asks,
-            orders: Cow::Borrowed(ORDERS[1][1]),
-        },
-    },
-    OrderBook {
-        base: Currency {
-            currency_code: Cow::Borrowed("XRP"),
-            issuer: Cow::Borrowed(""),
-            transfer_fee: 0.0,
-        },
-        counter: Currency {
-            currency_code: Cow::Borrowed("EUR"),
-            issuer: Cow::Borrowed("r5m7tZjQoEzD7dZSdNfjXxK9z4r7zgA8v"),
-            transfer_fee: 0.1,
-        },
-        bids: OrderBookSide {
-            side_type: OrderBookSideType::Bids,
-            orders: Cow::Borrowed(ORDERS[2][0]),
-        },
-        asks: OrderBookSide {
-            side_type: OrderBookSideType::Asks,
-            orders: Cow::Borrowed(ORDERS[2][1]),
-        },
-    },
-    OrderBook {
-        base: Currency {
-            currency_code: Cow::Borrowed("XRP"),
-            issuer: Cow::Borrowed(""),
-            transfer_fee: 0.0,
+pub fn order_books_list() -> Vec<OrderBook<'static>> {
+    vec![
+        OrderBook {
+            base: Currency {
+                currency_code: Cow::Borrowed("XRP"),
+                issuer: Cow::Borrowed(""),
+                transfer_fee: 0.0,
+            },
+            counter: Currency {
+                currency_code: Cow::Borrowed("USD"),
+                issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
+                transfer_fee: 0.1,
+            },
+            bids: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Bids,
+                orders: Cow::Borrowed(ORDERS[0][0]),
+            })),
+            asks: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Asks,
+                orders: Cow::Borrowed(ORDERS[0][1]),
+            })),
+            strict: false,
         },
-        counter: Currency {
-            currency_code: Cow::Borrowed("USD"),
-            issuer: Cow::Borrowed("rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy"),
-            transfer_fee: 0.1,
+        OrderBook {
+            base: Currency {
+                currency_code: Cow::Borrowed("EUR"),
+                issuer: Cow::Borrowed("rAPKsP3tt7fV9Vj2QWzBk1r4Fg5vY1YhZ"),
+                transfer_fee: 0.1,
+            },
+            counter: Currency {
+                currency_code: Cow::Borrowed("USD"),
+                issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
+                transfer_fee: 0.1,
+            },
+            bids: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Bids,
+                orders: Cow::Borrowed(ORDERS[1][0]),
+            })),
+            asks: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Asks,
+                orders: Cow::Borrowed(ORDERS[1][1]),
+            })),
+            strict: false,
         },
-        bids: OrderBookSide {
-            side_type: OrderBookSideType::Bids,
-            orders: Cow::Borrowed(ORDERS[3][0]),
+        OrderBook {
+            base: Currency {
+                currency_code: Cow::Borrowed("XRP"),
+                issuer: Cow::Borrowed(""),
+                transfer_fee: 0.0,
+            },
+            counter: Currency {
+                currency_code: Cow::Borrowed("EUR"),
+                issuer: Cow::Borrowed("r5m7tZjQoEzD7dZSdNfjXxK9z4r7zgA8v"),
+                transfer_fee: 0.1,
+            },
+            bids: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Bids,
+                orders: Cow::Borrowed(ORDERS[2][0]),
+            })),
+            asks: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Asks,
+                orders: Cow::Borrowed(ORDERS[2][1]),
+            })),
+            strict: false,
         },
-        asks: OrderBookSide {
-            side_type: OrderBookSideType::Asks,
-            orders: Cow::Borrowed(ORDERS[3][1]),
+        OrderBook {
+            base: Currency {
+                currency_code: Cow::Borrowed("XRP"),
+                issuer: Cow::Borrowed(""),
+                transfer_fee: 0.0,
+            },
+            counter: Currency {
+                currency_code: Cow::Borrowed("USD"),
+                issuer: Cow::Borrowed("rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy"),
+                transfer_fee: 0.1,
+            },
+            bids: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Bids,
+                orders: Cow::Borrowed(ORDERS[3][0]),
+            })),
+            asks: Arc::new(Mutex::new(OrderBookSide {
+                side_type: OrderBookSideType::Asks,
+                orders: Cow::Borrowed(ORDERS[3][1]),
+            })),
+            strict: false,
         },
-    },
-];
+    ]
+}
 
-pub static ORDER_BOOKS: OrderBooks<'_> = OrderBooks {
-    order_books: Cow::Borrowed(ORDER_BOOKS_LIST),
-    liquidity_spread: 0.05,
-};
+pub fn order_books() -> OrderBooks<'static> {
+    OrderBooks::new(order_books_list().into(), 0.05)
+}
@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+
+use rust_decimal::Decimal;
+use trading_lib::{
+    models::currency::Currency,
+    order_books::rippling::{RipplingNetwork, RipplingTrustLine},
+};
+
+use super::mixed_markets::{token, BITSTAMP, GATEHUB};
+
+pub const MARKET_MAKER: &str = "rLHzPsX6oXkzU2qL12kHCH8G8cnZv1rBJh";
+pub const DISCOUNTING_MAKER: &str = "rsA2LpzuawewSBQXkiju3YQTMzW13pAAdW";
+pub const NO_RIPPLE_HOLDER: &str = "r3kmLJN5D28dHuH8vZNUZpMC43pEHpaocV";
+
+fn trust_line(
+    account: &'static str,
+    currency: Currency<'static>,
+    balance: i64,
+    limit: i64,
+    quality_in: u32,
+    no_ripple: bool,
+) -> RipplingTrustLine<'static> {
+    RipplingTrustLine {
+        account: Cow::Borrowed(account),
+        currency,
+        balance: Decimal::from(balance),
+        limit: Decimal::from(limit),
+        quality_in,
+        quality_out: 0,
+        no_ripple,
+    }
+}
+
+/// Accounts holding USD of both GateHub and Bitstamp:
+/// - `MARKET_MAKER` ripples at face value: 500 USD:Bitstamp and 100 USD:GateHub can leave it
+/// - `DISCOUNTING_MAKER` values incoming USD:GateHub at 0.99 and holds no USD:GateHub
+/// - `NO_RIPPLE_HOLDER` disabled rippling on its USD:Bitstamp line
+pub fn rippling_network() -> RipplingNetwork<'static> {
+    RipplingNetwork::new(vec![
+        trust_line(MARKET_MAKER, token("USD", GATEHUB), 100, 1000, 0, false),
+        trust_line(MARKET_MAKER, token("USD", BITSTAMP), 500, 1000, 0, false),
+        trust_line(
+            DISCOUNTING_MAKER,
+            token("USD", GATEHUB),
+            0,
+            200,
+            990_000_000,
+            false,
+        ),
+        trust_line(
+            DISCOUNTING_MAKER,
+            token("USD", BITSTAMP),
+            300,
+            1000,
+            0,
+            false,
+        ),
+        trust_line(NO_RIPPLE_HOLDER, token("USD", GATEHUB), 0, 1000, 0, false),
+        trust_line(
+            NO_RIPPLE_HOLDER,
+            token("USD", BITSTAMP),
+            1000,
+            1000,
+            0,
+            true,
+        ),
+    ])
+}
@@ -1,4 +1,7 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
 
 use trading_lib::order_books::{
     order::Order,
@@ -32,6 +35,7 @@ fn generate_orders<'a>(
             counter: counter.clone(),
             base_quantity: generate_random_decimal(1.0, 100.0),
             rate,
+            offer_id: None,
         });
     }
 
@@ -57,11 +61,16 @@ fn generate_order_books_with_same_currency_codes<'a>(
         let mut order_book = OrderBook {
             base: base_currency,
             counter: counter_currency,
-            bids: (bids.to_owned().into(), OrderBookSideType::Bids).into(),
-            asks: (asks.to_owned().into(), OrderBookSideType::Asks).into(),
+            bids: Arc::new(Mutex::new(
+                (bids.to_owned().into(), OrderBookSideType::Bids).into(),
+            )),
+            asks: Arc::new(Mutex::new(
+                (asks.to_owned().into(), OrderBookSideType::Asks).into(),
+            )),
+            strict: false,
         };
         if rand::random() {
-            order_book.flip();
+            order_book.flip().unwrap();
         }
         order_books.push(order_book);
     }
@@ -80,8 +89,5 @@ pub fn generate_order_books<'a>(
         order_books.extend(order_books_with_same_currency_codes.to_vec());
     }
 
-    OrderBooks {
-        order_books: order_books.into(),
-        liquidity_spread: 0.1,
-    }
+    OrderBooks::new(order_books.into(), 0.1)
 }
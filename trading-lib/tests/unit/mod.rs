@@ -1,3 +1,4 @@
 mod common;
 
 mod order_books;
+mod trading_types;
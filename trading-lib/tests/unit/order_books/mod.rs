@@ -1,27 +1,32 @@
+mod report;
+mod snapshots;
+
 #[cfg(test)]
 mod test_sorting {
-    use crate::common::_static::order_books::{ORDER_BOOKS, ORDER_BOOKS_LIST};
+    use crate::common::_static::order_books::{order_books, order_books_list};
 
     #[test]
     fn test_order_book_sorting() {
-        let mut order_book = ORDER_BOOKS_LIST[0].clone();
-        assert!(order_book.bids.orders[0].rate < order_book.bids.orders[1].rate);
-        order_book.sort();
-        assert!(order_book.bids.orders[0].rate > order_book.bids.orders[1].rate);
+        let mut order_book = order_books_list()[0].clone();
+        {
+            let bids = order_book.get_bids().unwrap();
+            assert!(bids.orders[0].rate < bids.orders[1].rate);
+        }
+        order_book.sort().unwrap();
+        let bids = order_book.get_bids().unwrap();
+        assert!(bids.orders[0].rate > bids.orders[1].rate);
     }
 
     #[test]
     fn test_order_books_sorting() {
-        let mut order_books = ORDER_BOOKS.clone();
-        assert!(
-            order_books.order_books[0].bids.orders[0].rate
-                < order_books.order_books[0].bids.orders[1].rate
-        );
-        order_books.sort();
-        assert!(
-            order_books.order_books[0].bids.orders[0].rate
-                > order_books.order_books[0].bids.orders[1].rate
-        );
+        let mut order_books = order_books();
+        {
+            let bids = order_books.order_books[0].get_bids().unwrap();
+            assert!(bids.orders[0].rate < bids.orders[1].rate);
+        }
+        order_books.sort().unwrap();
+        let bids = order_books.order_books[0].get_bids().unwrap();
+        assert!(bids.orders[0].rate > bids.orders[1].rate);
     }
 }
 
@@ -29,20 +34,1167 @@ mod test_sorting {
 mod test_liquidity {
     use trading_lib::order_books::IsLiquid;
 
-    use crate::common::_static::order_books::{ORDER_BOOKS, ORDER_BOOKS_LIST};
+    use crate::common::_static::order_books::{order_books, order_books_list};
 
     #[test]
     fn test_order_book_liquidity() {
-        let mut order_book = ORDER_BOOKS_LIST[0].clone();
-        order_book.sort();
+        let mut order_book = order_books_list()[0].clone();
+        order_book.sort().unwrap();
         dbg!(&order_book.calculate_spread_pct());
-        assert!(order_book.is_liquid(ORDER_BOOKS.liquidity_spread));
+        // The best bid is 0.24 and the best ask 0.26, a spread of 8.33 %.
+        assert!(!order_book
+            .is_liquid(order_books().liquidity_spread)
+            .unwrap());
+        assert!(order_book.is_liquid(0.1).unwrap());
+    }
+
+    #[test]
+    fn test_empty_side_is_illiquid() {
+        let order_book = order_books_list()[0].clone();
+        order_book.get_asks().unwrap().orders.to_mut().clear();
+        assert_eq!(order_book.calculate_spread_pct().unwrap(), None);
+        assert!(!order_book.is_liquid(1.0).unwrap());
     }
 
     #[test]
     fn test_get_liquid_order_books() {
-        let order_books = ORDER_BOOKS.get_liquid_order_books();
-        dbg!(&order_books[0].calculate_spread_pct());
-        assert_eq!(order_books.len(), 1);
+        let mut order_books = order_books();
+        order_books.sort().unwrap();
+        let liquid_order_books = order_books.get_liquid_order_books().unwrap();
+        dbg!(&liquid_order_books[0].calculate_spread_pct());
+        assert_eq!(liquid_order_books.len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_composite {
+    use std::collections::HashMap;
+
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::composite::IssuerAdjustments;
+
+    use crate::common::_static::order_books::order_books;
+
+    const GATEHUB: &str = "rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS";
+    const BITSTAMP: &str = "rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy";
+
+    #[test]
+    fn test_composite_order_book() {
+        let composite_order_book = order_books()
+            .get_composite_order_book("XRP".into(), "USD".into(), &IssuerAdjustments::default())
+            .unwrap();
+        assert_eq!(composite_order_book.bids.len(), 4);
+        assert_eq!(composite_order_book.asks.len(), 4);
+        let best_bid = composite_order_book.get_best_bid().unwrap();
+        assert_eq!(best_bid.adjusted_rate, Decimal::new(24, 2));
+        assert!(composite_order_book
+            .bids
+            .iter()
+            .any(|level| level.get_counter_issuer() == GATEHUB));
+        assert!(composite_order_book
+            .bids
+            .iter()
+            .any(|level| level.get_counter_issuer() == BITSTAMP));
+    }
+
+    #[test]
+    fn test_composite_order_book_flipped() {
+        let composite_order_book = order_books()
+            .get_composite_order_book("USD".into(), "XRP".into(), &IssuerAdjustments::default())
+            .unwrap();
+        assert_eq!(composite_order_book.bids.len(), 4);
+        let best_bid = composite_order_book.get_best_bid().unwrap();
+        assert_eq!(best_bid.order.base.currency_code, "USD");
+        assert_eq!(best_bid.adjusted_rate, Decimal::ONE / Decimal::new(26, 2));
+    }
+
+    #[test]
+    fn test_composite_order_book_adjustments() {
+        let adjustments = IssuerAdjustments {
+            allowed_issuers: Some(vec![GATEHUB.into()]),
+            haircuts: HashMap::new(),
+        };
+        let composite_order_book = order_books()
+            .get_composite_order_book("XRP".into(), "USD".into(), &adjustments)
+            .unwrap();
+        assert_eq!(composite_order_book.bids.len(), 2);
+        assert!(composite_order_book
+            .bids
+            .iter()
+            .all(|level| level.get_counter_issuer() == GATEHUB));
+
+        let adjustments = IssuerAdjustments {
+            allowed_issuers: None,
+            haircuts: HashMap::from([(GATEHUB.into(), Decimal::new(1, 1))]),
+        };
+        let composite_order_book = order_books()
+            .get_composite_order_book("XRP".into(), "USD".into(), &adjustments)
+            .unwrap();
+        let best_bid = composite_order_book.get_best_bid().unwrap();
+        assert_eq!(best_bid.get_counter_issuer(), BITSTAMP);
+        assert_eq!(
+            composite_order_book.bids[1].adjusted_rate,
+            Decimal::new(23, 2)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_synthetic {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{models::currency::Currency, order_books::synthetic::LevelSource};
+
+    use crate::common::_static::order_books::order_books;
+
+    fn usd<'a>() -> Currency<'a> {
+        Currency {
+            currency_code: Cow::Borrowed("USD"),
+            issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
+            transfer_fee: 0.1,
+        }
+    }
+
+    fn eur<'a>() -> Currency<'a> {
+        Currency {
+            currency_code: Cow::Borrowed("EUR"),
+            issuer: Cow::Borrowed("r5m7tZjQoEzD7dZSdNfjXxK9z4r7zgA8v"),
+            transfer_fee: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_direct_order_book() {
+        let synthetic_order_book = order_books()
+            .get_synthetic_order_book(Currency::xrp(), usd())
+            .unwrap();
+        assert_eq!(synthetic_order_book.bids.len(), 2);
+        assert_eq!(synthetic_order_book.asks.len(), 2);
+        assert!(synthetic_order_book
+            .bids
+            .iter()
+            .chain(synthetic_order_book.asks.iter())
+            .all(|level| level.source == LevelSource::Direct));
+        assert_eq!(
+            synthetic_order_book.get_best_bid().unwrap().order.rate,
+            Decimal::new(24, 2)
+        );
+    }
+
+    #[test]
+    fn test_bridged_order_book() {
+        // EUR/XRP is the flipped XRP/EUR order book and XRP/USD is used as it is.
+        let synthetic_order_book = order_books()
+            .get_synthetic_order_book(eur(), usd())
+            .unwrap();
+        let best_bid = synthetic_order_book.get_best_bid().unwrap();
+        assert_eq!(best_bid.source, LevelSource::Bridged);
+        assert_eq!(
+            best_bid.order.rate,
+            (Decimal::ONE / Decimal::new(26, 2)) * Decimal::new(24, 2)
+        );
+        assert_eq!(best_bid.order.base_quantity.round_dp(10), Decimal::from(26));
+        let best_ask = synthetic_order_book.get_best_ask().unwrap();
+        assert_eq!(best_ask.source, LevelSource::Bridged);
+        assert_eq!(
+            best_ask.order.rate,
+            (Decimal::ONE / Decimal::new(24, 2)) * Decimal::new(26, 2)
+        );
+        assert!(synthetic_order_book
+            .bids
+            .windows(2)
+            .all(|levels| levels[0].order.rate >= levels[1].order.rate));
+    }
+}
+
+#[cfg(test)]
+mod test_events {
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::{
+        events::OrderBookEvent,
+        order::Order,
+        order_book::{OrderBook, OrderBookSideType},
+    };
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    fn copy_order_book<'a>(order_book: &OrderBook<'a>) -> OrderBook<'a> {
+        OrderBook::new(
+            order_book.base.clone(),
+            order_book.counter.clone(),
+            order_book.get_bids().unwrap().orders.to_vec(),
+            order_book.get_asks().unwrap().orders.to_vec(),
+        )
+    }
+
+    fn order_at<'a>(order_book: &OrderBook<'a>, base_quantity: i64, rate: Decimal) -> Order<'a> {
+        Order {
+            base: order_book.base.clone(),
+            counter: order_book.counter.clone(),
+            base_quantity: Decimal::from(base_quantity),
+            rate,
+            offer_id: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let order_book = order_books_list()[0].clone();
+        let events = order_book.diff(&copy_order_book(&order_book)).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = order_books_list()[0].clone();
+        let new = copy_order_book(&old);
+        {
+            let mut bids = new.get_bids().unwrap();
+            let orders = bids.orders.to_mut();
+            // 0.23 is removed, 0.24 changes its quantity and 0.25 is added.
+            orders.remove(0);
+            orders[0].base_quantity = Decimal::from(50);
+            orders.push(order_at(&old, 10, Decimal::new(25, 2)));
+        }
+        let events = old.diff(&new).unwrap();
+        assert!(events.contains(&OrderBookEvent::LevelRemoved {
+            side_type: OrderBookSideType::Bids,
+            rate: Decimal::new(23, 2),
+            quantity: Decimal::from(80),
+        }));
+        assert!(events.contains(&OrderBookEvent::QuantityChanged {
+            side_type: OrderBookSideType::Bids,
+            rate: Decimal::new(24, 2),
+            old_quantity: Decimal::from(100),
+            new_quantity: Decimal::from(50),
+        }));
+        assert!(events.contains(&OrderBookEvent::LevelAdded {
+            side_type: OrderBookSideType::Bids,
+            rate: Decimal::new(25, 2),
+            quantity: Decimal::from(10),
+        }));
+        assert!(events.contains(&OrderBookEvent::BestBidChanged {
+            old_rate: Some(Decimal::new(24, 2)),
+            new_rate: Some(Decimal::new(25, 2)),
+        }));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, OrderBookEvent::BestAskChanged { .. })));
+    }
+
+    #[test]
+    fn test_diff_crossed() {
+        let old = order_books_list()[0].clone();
+        let new = copy_order_book(&old);
+        new.get_bids()
+            .unwrap()
+            .orders
+            .to_mut()
+            .push(order_at(&old, 10, Decimal::new(27, 2)));
+        let events = old.diff(&new).unwrap();
+        assert!(events.contains(&OrderBookEvent::BookCrossed {
+            best_bid_rate: Decimal::new(27, 2),
+            best_ask_rate: Decimal::new(26, 2),
+        }));
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let mut order_books = order_books();
+        let receiver = order_books.subscribe();
+        let new = copy_order_book(&order_books.order_books[0]);
+        new.get_asks().unwrap().orders.to_mut().remove(0);
+        let events = order_books.update_order_book(new).unwrap();
+        let update = receiver.try_recv().unwrap();
+        assert_eq!(update.events, events);
+        assert_eq!(update.base.currency_code, "XRP");
+        assert!(update.events.contains(&OrderBookEvent::BestAskChanged {
+            old_rate: Some(Decimal::new(26, 2)),
+            new_rate: Some(Decimal::new(28, 2)),
+        }));
+        assert_eq!(order_books.order_books.len(), 4);
+        assert_eq!(
+            order_books.order_books[0].get_asks().unwrap().orders.len(),
+            1
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_validation {
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::{
+        order::Order,
+        order_book::{OrderBook, OrderBookSideType},
+        validation::ValidationIssue,
+        Flip,
+    };
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    fn sorted_order_book() -> OrderBook<'static> {
+        let mut order_book = order_books_list()[0].clone();
+        order_book.sort().unwrap();
+        order_book
+    }
+
+    fn bid<'a>(order_book: &OrderBook<'a>, base_quantity: i64, rate: Decimal) -> Order<'a> {
+        Order {
+            base: order_book.base.clone(),
+            counter: order_book.counter.clone(),
+            base_quantity: Decimal::from(base_quantity),
+            rate,
+            offer_id: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_order_book() {
+        assert!(sorted_order_book().validate().unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_unsorted_order_book() {
+        let report = order_books_list()[0].validate().unwrap();
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::UnsortedSide {
+                side_type: OrderBookSideType::Bids,
+                index: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_orders() {
+        let order_book = sorted_order_book();
+        {
+            let mut bids = order_book.get_bids().unwrap();
+            let orders = bids.orders.to_mut();
+            orders[0].offer_id = Some("rOwner:1".into());
+            orders[1].offer_id = Some("rOwner:1".into());
+            orders[1].base_quantity = Decimal::ZERO;
+            let mut invalid = bid(&order_book, 10, Decimal::new(-1, 2));
+            std::mem::swap(&mut invalid.base, &mut invalid.counter);
+            orders.push(invalid);
+        }
+        let issues = order_book.validate().unwrap().issues;
+        assert!(issues.contains(&ValidationIssue::DuplicateOfferId {
+            side_type: OrderBookSideType::Bids,
+            index: 1,
+            offer_id: "rOwner:1".into(),
+        }));
+        assert!(issues.contains(&ValidationIssue::NonPositiveQuantity {
+            side_type: OrderBookSideType::Bids,
+            index: 1,
+        }));
+        assert!(issues.contains(&ValidationIssue::NonPositiveRate {
+            side_type: OrderBookSideType::Bids,
+            index: 2,
+        }));
+        assert!(issues.contains(&ValidationIssue::CurrencyMismatch {
+            side_type: OrderBookSideType::Bids,
+            index: 2,
+        }));
+    }
+
+    #[test]
+    fn test_crossed_order_book() {
+        let order_book = sorted_order_book();
+        let order = bid(&order_book, 10, Decimal::new(27, 2));
+        order_book
+            .get_bids()
+            .unwrap()
+            .orders
+            .to_mut()
+            .insert(0, order);
+        let issues = order_book.validate().unwrap().issues;
+        assert_eq!(
+            issues,
+            vec![ValidationIssue::CrossedBook {
+                best_bid_rate: Decimal::new(27, 2),
+                best_ask_rate: Decimal::new(26, 2),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strict_add_order() {
+        let mut order_book = sorted_order_book();
+        order_book.strict = true;
+        order_book
+            .add_order(bid(&order_book, 10, Decimal::new(25, 2)))
+            .unwrap();
+        assert_eq!(order_book.get_bids().unwrap().orders.len(), 3);
+        assert!(order_book
+            .add_order(bid(&order_book, 10, Decimal::new(27, 2)))
+            .is_err());
+        assert_eq!(order_book.get_bids().unwrap().orders.len(), 3);
+        assert!(order_book.validate().unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_strict_add_zero_rate_ask() {
+        let mut order_book = sorted_order_book();
+        order_book.strict = true;
+        let asks = order_book.get_asks().unwrap().orders.to_vec();
+        let mut ask = bid(&order_book, 10, Decimal::ZERO);
+        std::mem::swap(&mut ask.base, &mut ask.counter);
+        assert!(order_book.add_order(ask).is_err());
+        assert_eq!(order_book.get_asks().unwrap().orders.to_vec(), asks);
+        assert!(order_book.validate().unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_strict_flip() {
+        let mut order_book = sorted_order_book();
+        order_book.strict = true;
+        let (base, counter) = (order_book.base.clone(), order_book.counter.clone());
+        order_book.flip().unwrap();
+        assert_eq!(order_book.base, counter);
+        assert_eq!(order_book.counter, base);
+        assert!(order_book.validate().unwrap().is_valid());
+
+        // An invalid order book is flipped back.
+        let mut order_book = order_books_list()[0].clone();
+        order_book.strict = true;
+        let bids = order_book.get_bids().unwrap().orders.to_vec();
+        assert!(order_book.flip().is_err());
+        assert_eq!(order_book.base, base);
+        assert_eq!(order_book.get_bids().unwrap().orders.to_vec(), bids);
+    }
+
+    #[test]
+    fn test_strict_insert_order_book() {
+        let mut order_books = order_books();
+        let mut order_book = order_books_list()[0].clone();
+        order_book.strict = true;
+        // The fixture bids are unsorted.
+        assert!(order_books.insert_order_book(order_book.clone()).is_err());
+        let existing = order_books
+            .get_order_book(&order_book.base, &order_book.counter)
+            .unwrap();
+        assert!(!existing.strict);
+        order_book.sort().unwrap();
+        assert!(order_books.insert_order_book(order_book).unwrap().is_some());
+    }
+}
+
+#[cfg(test)]
+mod test_funding {
+    use rust_decimal::Decimal;
+    use trading_lib::models::currency::Currency;
+
+    use crate::common::_static::order_books::order_books;
+
+    #[test]
+    fn test_exclude_own_and_unfunded_orders() {
+        let mut order_books = order_books();
+        order_books.sort().unwrap();
+        {
+            let order_book = &order_books.order_books[0];
+            let mut bids = order_book.get_bids().unwrap();
+            let orders = bids.orders.to_mut();
+            orders[0].offer_id = Some("rMaker:2".into());
+            orders[1].offer_id = Some("rOwn:1".into());
+            let mut asks = order_book.get_asks().unwrap();
+            asks.orders.to_mut()[0].offer_id = Some("rMaker:3".into());
+        }
+        let usd = order_books.order_books[0].counter.clone();
+        order_books.own_accounts.insert("rOwn".into());
+        order_books
+            .owner_balances
+            .set_balance("rMaker", &usd, Decimal::from(12));
+        order_books
+            .owner_balances
+            .set_balance("rMaker", &Currency::xrp(), Decimal::ZERO);
+
+        let evaluable_order_books = order_books.get_evaluable_order_books().unwrap();
+        let order_book = &evaluable_order_books.order_books[0];
+        let bids = order_book.get_bids().unwrap();
+        // The own order is excluded and the order of rMaker (24 USD plus 10% transfer fee) is
+        // reduced to what 12 USD can fund.
+        assert_eq!(bids.orders.len(), 1);
+        assert_eq!(bids.orders[0].get_owner(), Some("rMaker"));
+        let funded_quantity =
+            Decimal::from(100) * Decimal::from(12) / (Decimal::from(24) * Decimal::new(11, 1));
+        assert_eq!(
+            bids.orders[0].base_quantity.round_dp(20),
+            funded_quantity.round_dp(20)
+        );
+        let asks = order_book.get_asks().unwrap();
+        assert_eq!(asks.orders.len(), 1);
+        assert_eq!(asks.orders[0].get_owner(), None);
+        // The original order books are untouched.
+        assert_eq!(
+            order_books.order_books[0].get_bids().unwrap().orders.len(),
+            2
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_registry {
+    use trading_lib::{
+        models::currency::Currency,
+        order_books::{order_book::OrderBook, registry::OrderBookMetadata},
+    };
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    #[test]
+    fn test_get_order_book_any_orientation() {
+        let order_books = order_books();
+        let order_book = &order_books_list()[1];
+        let found = order_books
+            .get_order_book(&order_book.base, &order_book.counter)
+            .unwrap();
+        assert_eq!(found.base, order_book.base);
+        let found = order_books
+            .get_order_book(&order_book.counter, &order_book.base)
+            .unwrap();
+        assert_eq!(found.base, order_book.base);
+        assert!(order_books
+            .get_order_book(&order_book.base, &Currency::xrp())
+            .is_none());
+    }
+
+    #[test]
+    fn test_insert_order_book() {
+        let mut order_books = order_books();
+        let order_book = order_books_list()[0].clone();
+        let (bids, asks) = order_book.get_oriented_orders(true).unwrap();
+        let flipped = OrderBook::new(
+            order_book.counter.clone(),
+            order_book.base.clone(),
+            bids,
+            asks,
+        );
+        // The flipped order book replaces the existing one.
+        let replaced = order_books.insert_order_book(flipped).unwrap().unwrap();
+        assert_eq!(replaced.base, order_book.base);
+        assert_eq!(order_books.order_books.len(), 4);
+        let found = order_books
+            .get_order_book(&order_book.base, &order_book.counter)
+            .unwrap();
+        assert_eq!(found.base, order_book.counter);
+
+        let usd = order_books_list()[0].counter.clone();
+        let eur = order_books_list()[2].counter.clone();
+        let new = OrderBook::new(eur.clone(), usd.clone(), Vec::new(), Vec::new());
+        assert!(order_books.insert_order_book(new).unwrap().is_none());
+        assert_eq!(order_books.order_books.len(), 5);
+        assert!(order_books.contains_order_book(&usd, &eur));
+    }
+
+    #[test]
+    fn test_remove_order_book() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books.set_metadata(
+            &list[0].base,
+            &list[0].counter,
+            OrderBookMetadata::default(),
+        );
+        let removed = order_books
+            .remove_order_book(&list[0].counter, &list[0].base)
+            .unwrap();
+        assert_eq!(removed.counter, list[0].counter);
+        assert_eq!(order_books.order_books.len(), 3);
+        assert!(!order_books.contains_order_book(&list[0].base, &list[0].counter));
+        assert!(order_books
+            .get_metadata(&list[0].base, &list[0].counter)
+            .is_none());
+        // The order book moved into the freed position is still found.
+        for order_book in list[1..].iter() {
+            let found = order_books
+                .get_order_book(&order_book.base, &order_book.counter)
+                .unwrap();
+            assert_eq!(found.counter, order_book.counter);
+        }
+        assert!(order_books
+            .remove_order_book(&list[0].base, &list[0].counter)
+            .is_none());
+    }
+
+    #[test]
+    fn test_get_order_books_with_currency() {
+        let order_books = order_books();
+        let xrp = Currency::xrp();
+        assert_eq!(order_books.get_order_books_with_currency(&xrp).count(), 3);
+        let usd = order_books_list()[0].counter.clone();
+        assert_eq!(order_books.get_order_books_with_currency(&usd).count(), 2);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        let metadata = OrderBookMetadata {
+            last_update_ledger_index: Some(42),
+            last_update_close_time: Some(1_700_000_000),
+            source: Some("wss://xrplcluster.com".into()),
+        };
+        assert!(order_books.set_metadata(&list[1].counter, &list[1].base, metadata.clone()));
+        assert_eq!(
+            order_books.get_metadata(&list[1].base, &list[1].counter),
+            Some(&metadata)
+        );
+        assert!(!order_books.set_metadata(&list[1].base, &Currency::xrp(), metadata));
+    }
+
+    #[test]
+    fn test_reindex() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books.order_books.to_mut().remove(0);
+        // Lookups still work on a stale index.
+        let found = order_books
+            .get_order_book(&list[3].base, &list[3].counter)
+            .unwrap();
+        assert_eq!(found.counter, list[3].counter);
+        order_books.reindex();
+        assert!(!order_books.contains_order_book(&list[0].base, &list[0].counter));
+        assert!(order_books.contains_order_book(&list[3].base, &list[3].counter));
+    }
+}
+
+#[cfg(test)]
+mod test_graph {
+    use rust_decimal::{prelude::FromPrimitive, Decimal};
+    use trading_lib::{models::currency::Currency, order_books::graph::CurrencyGraph};
+
+    use crate::common::_static::{
+        mixed_markets::{order_book, token, BITSTAMP, GATEHUB},
+        order_books::{order_books, order_books_list},
+    };
+
+    fn fee_multiplier(currency: &Currency<'_>) -> Decimal {
+        Decimal::ONE - Decimal::from_f32(currency.transfer_fee).unwrap()
+    }
+
+    #[test]
+    fn test_currency_graph() {
+        let graph = order_books().get_currency_graph().unwrap();
+        // XRP, USD:rDk7, EUR:rAPK, EUR:r5m7 and USD:rPVM
+        assert_eq!(graph.currencies.len(), 5);
+        // Every order book has a bid and an ask edge.
+        assert_eq!(graph.edges.len(), 8);
+    }
+
+    #[test]
+    fn test_get_neighbors() {
+        let graph = order_books().get_currency_graph().unwrap();
+        let list = order_books_list();
+        let usd = &list[0].counter;
+        let neighbors = graph.get_neighbors(&Currency::xrp());
+        assert_eq!(neighbors.len(), 3);
+        let edge = neighbors
+            .iter()
+            .find(|edge| graph.currencies[edge.to].is_same_asset(usd))
+            .unwrap();
+        assert_eq!(edge.rate, Decimal::new(24, 2) * fee_multiplier(usd));
+        assert_eq!(edge.depth, Decimal::from(100));
+        // USD:rDk7 can be sold for XRP and EUR:rAPK.
+        assert_eq!(graph.get_neighbors(usd).len(), 2);
+    }
+
+    #[test]
+    fn test_get_shortest_path() {
+        let graph = order_books().get_currency_graph().unwrap();
+        let list = order_books_list();
+        let eur = &list[1].base;
+        let path = graph.get_shortest_path(&Currency::xrp(), eur).unwrap();
+        assert_eq!(path.currencies.len(), 3);
+        assert!(path.currencies[1].is_same_asset(&list[0].counter));
+        let expected_rate = Decimal::new(24, 2) * fee_multiplier(&list[0].counter)
+            / Decimal::new(106, 2)
+            * fee_multiplier(eur);
+        assert_eq!(path.rate, expected_rate);
+        // USD:rPVM and EUR:r5m7 are only connected through XRP.
+        assert!(graph
+            .get_shortest_path(&list[3].counter, &list[2].counter)
+            .unwrap()
+            .currencies
+            .iter()
+            .any(|currency| currency.is_xrp()));
+    }
+
+    #[test]
+    fn test_get_best_rate_path() {
+        let graph = order_books().get_currency_graph().unwrap();
+        let list = order_books_list();
+        let usd = &list[0].counter;
+        let path = graph.get_best_rate_path(&Currency::xrp(), usd, 1).unwrap();
+        assert_eq!(path.currencies.len(), 2);
+        assert_eq!(path.depth, Decimal::from(100));
+        let path = graph.get_best_rate_path(&Currency::xrp(), usd, 3).unwrap();
+        // Selling XRP for USD directly is better than going through EUR:rAPK.
+        assert_eq!(path.currencies.len(), 2);
+        let eur = &list[1].base;
+        assert!(graph.get_best_rate_path(&Currency::xrp(), eur, 1).is_none());
+        let path = graph.get_best_rate_path(&Currency::xrp(), eur, 2).unwrap();
+        // The depth is limited by the second leg: 100 EUR at 1.06 USD cost 106 USD, which is
+        // 106 / (0.24 * 0.9) XRP.
+        let first_rate = Decimal::new(24, 2) * fee_multiplier(usd);
+        assert_eq!(
+            path.depth,
+            Decimal::from(100).min(Decimal::new(106, 0) / first_rate)
+        );
+    }
+
+    #[test]
+    fn test_get_negative_cycles() {
+        let mut graph = CurrencyGraph::default();
+        for order_book in [
+            order_book(
+                Currency::xrp(),
+                token("USD", GATEHUB),
+                &[(100, 50)],
+                &[(100, 52)],
+            ),
+            order_book(
+                token("USD", GATEHUB),
+                token("EUR", BITSTAMP),
+                &[(50, 110)],
+                &[(50, 112)],
+            ),
+            order_book(
+                token("EUR", BITSTAMP),
+                Currency::xrp(),
+                &[(60, 185)],
+                &[(60, 190)],
+            ),
+        ] {
+            graph.add_order_book(&order_book).unwrap();
+        }
+        // XRP -> USD:GateHub -> EUR:Bitstamp -> XRP, once for all of its rotations.
+        let cycles = graph.get_negative_cycles(3, None);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].currencies.len(), 4);
+        assert!(cycles[0].currencies[0].is_xrp());
+        assert!(cycles[0].currencies[3].is_xrp());
+        assert_eq!(cycles[0].rate, Decimal::new(10175, 4));
+        assert_eq!(cycles[0].depth, Decimal::from(100));
+        let cycles = graph.get_negative_cycles(3, Some(&token("EUR", BITSTAMP)));
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0].currencies[0].is_same_asset(&token("EUR", BITSTAMP)));
+        assert_eq!(cycles[0].rate.round_dp(20), Decimal::new(10175, 4));
+        assert!(graph.get_negative_cycles(2, None).is_empty());
+        assert!(graph
+            .get_negative_cycles(3, Some(&token("EUR", GATEHUB)))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_disjoint_negative_cycles() {
+        let mut graph = CurrencyGraph::default();
+        let (usd, eur) = (token("USD", GATEHUB), token("EUR", BITSTAMP));
+        let (btc, eth, jpy) = (
+            token("BTC", GATEHUB),
+            token("ETH", BITSTAMP),
+            token("JPY", GATEHUB),
+        );
+        for order_book in [
+            order_book(Currency::xrp(), usd.clone(), &[(100, 50)], &[(100, 52)]),
+            order_book(usd, eur.clone(), &[(50, 110)], &[(50, 112)]),
+            order_book(eur, Currency::xrp(), &[(60, 185)], &[(60, 190)]),
+            order_book(btc.clone(), eth.clone(), &[(100, 50)], &[(100, 52)]),
+            order_book(eth, jpy.clone(), &[(50, 110)], &[(50, 112)]),
+            order_book(jpy, btc.clone(), &[(60, 186)], &[(60, 190)]),
+        ] {
+            graph.add_order_book(&order_book).unwrap();
+        }
+        // Both cycles have three hops, best rate first.
+        let cycles = graph.get_negative_cycles(3, None);
+        assert_eq!(cycles.len(), 2);
+        assert!(cycles[0].currencies[0].is_same_asset(&btc));
+        assert_eq!(cycles[0].rate, Decimal::new(10230, 4));
+        assert!(cycles[1].currencies[0].is_xrp());
+        assert_eq!(cycles[1].rate, Decimal::new(10175, 4));
+        let cycles = graph.get_negative_cycles(3, Some(&Currency::xrp()));
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0].currencies[0].is_xrp());
+    }
+
+    #[test]
+    fn test_overlapping_negative_cycles() {
+        let mut graph = CurrencyGraph::default();
+        let (usd, eur, btc) = (
+            token("USD", GATEHUB),
+            token("EUR", BITSTAMP),
+            token("BTC", GATEHUB),
+        );
+        for order_book in [
+            order_book(Currency::xrp(), usd.clone(), &[(100, 50)], &[(100, 52)]),
+            order_book(usd.clone(), eur.clone(), &[(50, 110)], &[(50, 112)]),
+            order_book(eur.clone(), Currency::xrp(), &[(60, 185)], &[(60, 190)]),
+            order_book(eur, btc.clone(), &[(50, 200)], &[(50, 210)]),
+            order_book(btc.clone(), usd.clone(), &[(100, 110)], &[(100, 120)]),
+        ] {
+            graph.add_order_book(&order_book).unwrap();
+        }
+        // USD:GateHub -> EUR:Bitstamp is the worst edge of the cycle without XRP and shared with
+        // the one through XRP.
+        let cycles = graph.get_negative_cycles(3, Some(&Currency::xrp()));
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0].currencies[1].is_same_asset(&usd));
+        assert_eq!(cycles[0].rate, Decimal::new(10175, 4));
+        let cycles = graph.get_negative_cycles(3, None);
+        assert_eq!(cycles.len(), 2);
+        assert!(cycles[0].currencies[0].is_same_asset(&usd));
+        assert!(cycles[0].currencies[2].is_same_asset(&btc));
+        assert_eq!(cycles[0].rate, Decimal::new(242, 2));
+        assert!(cycles[1].currencies[0].is_xrp());
+        // XRP -> EUR:Bitstamp -> BTC:GateHub -> USD:GateHub -> XRP takes four hops.
+        let cycles = graph.get_negative_cycles(4, Some(&Currency::xrp()));
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].currencies.len(), 5);
+        assert_eq!(cycles[1].rate, Decimal::new(10175, 4));
+        assert!(graph.get_negative_cycles(2, None).is_empty());
+    }
+
+    #[test]
+    fn test_zero_rates() {
+        let mut graph = CurrencyGraph::default();
+        // The zero rate ask is skipped instead of being inverted.
+        graph
+            .add_order_book(&order_book(
+                Currency::xrp(),
+                token("USD", GATEHUB),
+                &[(100, 50)],
+                &[(100, 0), (100, 52)],
+            ))
+            .unwrap();
+        assert_eq!(graph.edges.len(), 2);
+        assert_eq!(graph.edges[1].rate, Decimal::ONE / Decimal::new(52, 2));
+        // A transfer fee of 100 % eats the whole rate, so nothing can be sold past it.
+        let mut eur = token("EUR", BITSTAMP);
+        eur.transfer_fee = 1.0;
+        let btc = token("BTC", GATEHUB);
+        for order_book in [
+            order_book(token("USD", GATEHUB), eur.clone(), &[(50, 110)], &[]),
+            order_book(eur, btc.clone(), &[(10, 100)], &[]),
+        ] {
+            graph.add_order_book(&order_book).unwrap();
+        }
+        let path = graph.get_best_rate_path(&Currency::xrp(), &btc, 3).unwrap();
+        assert_eq!(path.rate, Decimal::ZERO);
+        assert_eq!(path.depth, Decimal::ZERO);
+        assert!(graph.get_negative_cycles(3, None).is_empty());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = order_books().get_currency_graph().unwrap().to_dot();
+        assert!(dot.starts_with("digraph currencies {"));
+        assert!(dot.contains("0 [label=\"XRP\"];"));
+        assert!(dot.contains("1 [label=\"USD.rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS\"];"));
+        assert!(dot.contains("0 -> 1 [label="));
+        assert_eq!(dot.matches("->").count(), 8);
+    }
+}
+
+#[cfg(test)]
+mod test_staleness {
+    use trading_lib::{
+        order_books::order_book::OrderBook, trading_types::arbitrage::swap::GetProfitableTrades,
+    };
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    fn copy_order_book<'a>(order_book: &OrderBook<'a>) -> OrderBook<'a> {
+        OrderBook::new(
+            order_book.base.clone(),
+            order_book.counter.clone(),
+            order_book.get_bids().unwrap().orders.to_vec(),
+            order_book.get_asks().unwrap().orders.to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_get_age() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        assert_eq!(order_books.get_age(&list[0]), None);
+        assert!(order_books.stamp_order_book(&list[0].base, &list[0].counter, 100, 1_000));
+        assert!(order_books.stamp_order_book(&list[1].base, &list[1].counter, 120, 1_080));
+        assert_eq!(order_books.current_ledger_index, Some(120));
+        assert_eq!(order_books.get_age(&list[0]), Some(20));
+        assert_eq!(order_books.get_age(&list[1]), Some(0));
+        // Stamping an older ledger doesn't move the current ledger back.
+        order_books.stamp_order_book(&list[1].base, &list[1].counter, 110, 1_040);
+        assert_eq!(order_books.current_ledger_index, Some(120));
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books.stamp_order_book(&list[0].base, &list[0].counter, 100, 1_000);
+        order_books.stamp_order_book(&list[1].base, &list[1].counter, 120, 1_080);
+        // Nothing is stale without a maximum age.
+        assert!(list
+            .iter()
+            .all(|order_book| !order_books.is_stale(order_book)));
+        order_books.max_age = Some(10);
+        assert!(order_books.is_stale(&list[0]));
+        assert!(!order_books.is_stale(&list[1]));
+        // Order books without a stamp are stale.
+        assert!(order_books.is_stale(&list[2]));
+    }
+
+    #[test]
+    fn test_stale_order_books_excluded() {
+        let mut order_books = order_books();
+        order_books.sort().unwrap();
+        let list = order_books_list();
+        assert_eq!(order_books.get_liquid_order_books().unwrap().len(), 1);
+        order_books.stamp_order_book(&list[1].base, &list[1].counter, 100, 1_000);
+        order_books.stamp_order_book(&list[0].base, &list[0].counter, 120, 1_080);
+        order_books.max_age = Some(10);
+        assert!(order_books.get_liquid_order_books().unwrap().is_empty());
+        assert!(order_books.get_profitable_trades(12).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_order_book_at_ledger() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books
+            .update_order_book_at_ledger(copy_order_book(&list[0]), 200, 2_000)
+            .unwrap();
+        let metadata = order_books
+            .get_metadata(&list[0].base, &list[0].counter)
+            .unwrap();
+        assert_eq!(metadata.last_update_ledger_index, Some(200));
+        assert_eq!(metadata.last_update_close_time, Some(2_000));
+        assert_eq!(order_books.current_ledger_index, Some(200));
+    }
+
+    #[test]
+    fn test_get_ledger_snapshot() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books.stamp_order_book(&list[0].base, &list[0].counter, 100, 1_000);
+        order_books.stamp_order_book(&list[1].base, &list[1].counter, 100, 1_000);
+        order_books.stamp_order_book(&list[2].base, &list[2].counter, 101, 1_004);
+        let snapshot = order_books.get_ledger_snapshot(100).unwrap();
+        assert_eq!(snapshot.ledger_index, 100);
+        assert_eq!(snapshot.timestamp, 1_000);
+        assert_eq!(snapshot.order_books.len(), 2);
+        assert!(order_books.get_ledger_snapshot(99).is_err());
+
+        // Restored order books carry the ledger of the snapshot.
+        let restored = snapshot.into_order_books();
+        assert_eq!(restored.current_ledger_index, Some(100));
+        assert_eq!(
+            restored
+                .get_metadata(&list[1].base, &list[1].counter)
+                .unwrap()
+                .last_update_close_time,
+            Some(1_000)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_issuer_policy {
+    use std::collections::HashSet;
+
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::issuer_policy::{ExclusionReason, IssuerPolicy};
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    const GATEHUB: &str = "rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS";
+    const BITSTAMP: &str = "rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy";
+
+    #[test]
+    fn test_check_issuer() {
+        let mut issuer_policy = IssuerPolicy::default();
+        assert_eq!(issuer_policy.check_issuer(GATEHUB), None);
+        issuer_policy.denied_issuers.insert(GATEHUB.into());
+        issuer_policy.allowed_issuers = Some(HashSet::from([GATEHUB.into()]));
+        // Denying wins over allowing.
+        assert_eq!(
+            issuer_policy.check_issuer(GATEHUB),
+            Some(ExclusionReason::DeniedIssuer {
+                issuer: GATEHUB.to_string()
+            })
+        );
+        assert_eq!(
+            issuer_policy.check_issuer(BITSTAMP),
+            Some(ExclusionReason::IssuerNotAllowed {
+                issuer: BITSTAMP.to_string()
+            })
+        );
+        // XRP has no issuer and is always allowed.
+        assert_eq!(issuer_policy.check_issuer(""), None);
+    }
+
+    #[test]
+    fn test_trust_score() {
+        let mut issuer_policy = IssuerPolicy {
+            min_trust_score: Some(0.5),
+            ..Default::default()
+        };
+        issuer_policy.trust_scores.insert(GATEHUB.into(), 0.9);
+        issuer_policy.trust_scores.insert(BITSTAMP.into(), 0.4);
+        assert_eq!(issuer_policy.check_issuer(GATEHUB), None);
+        assert_eq!(
+            issuer_policy.check_issuer(BITSTAMP),
+            Some(ExclusionReason::TrustScoreTooLow {
+                issuer: BITSTAMP.to_string(),
+                trust_score: Some(0.4),
+                min_trust_score: 0.5,
+            })
+        );
+        assert!(matches!(
+            issuer_policy.check_issuer("rUnknown"),
+            Some(ExclusionReason::TrustScoreTooLow {
+                trust_score: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_exclusions() {
+        let mut order_books = order_books();
+        order_books.sort().unwrap();
+        order_books
+            .issuer_policy
+            .denied_issuers
+            .insert(GATEHUB.into());
+        let exclusions = order_books.get_exclusions();
+        // XRP/USD:GateHub and EUR/USD:GateHub
+        assert_eq!(exclusions.len(), 2);
+        assert!(exclusions.iter().all(|exclusion| exclusion.reasons
+            == vec![ExclusionReason::DeniedIssuer {
+                issuer: GATEHUB.to_string()
+            }]));
+        assert!(order_books.get_liquid_order_books().unwrap().is_empty());
+        assert_eq!(
+            order_books
+                .get_evaluable_order_books()
+                .unwrap()
+                .order_books
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_stale_exclusion_reason() {
+        let mut order_books = order_books();
+        let list = order_books_list();
+        order_books.stamp_order_book(&list[0].base, &list[0].counter, 100, 1_000);
+        order_books.max_age = Some(10);
+        assert!(order_books.get_exclusion_reasons(&list[0]).is_empty());
+        assert_eq!(
+            order_books.get_exclusion_reasons(&list[1]),
+            vec![ExclusionReason::Stale { age: None }]
+        );
+    }
+
+    #[test]
+    fn test_exposure_caps() {
+        let order_book = order_books_list()[0].clone();
+        let mut issuer_policy = IssuerPolicy::default();
+        // The bids give us USD:GateHub. The best bid (0.24 * 100 = 24 USD) is cut to 10 USD.
+        issuer_policy
+            .exposure_caps
+            .insert(GATEHUB.into(), Decimal::from(10));
+        let mut capped = order_book.with_exposure_caps(&issuer_policy).unwrap();
+        capped.sort().unwrap();
+        let bids = capped.get_bids().unwrap();
+        assert_eq!(bids.orders.len(), 1);
+        assert_eq!(
+            bids.orders[0].base_quantity * bids.orders[0].rate,
+            Decimal::from(10)
+        );
+        // The asks give us XRP, which has no cap.
+        assert_eq!(
+            capped.get_asks().unwrap().orders.len(),
+            order_book.get_asks().unwrap().orders.len()
+        );
+
+        issuer_policy
+            .exposure_caps
+            .insert(GATEHUB.into(), Decimal::ZERO);
+        assert_eq!(
+            issuer_policy.check_issuer(GATEHUB),
+            Some(ExclusionReason::NoExposure {
+                issuer: GATEHUB.to_string()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_rippling {
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::OrderBooks;
+
+    use crate::common::_static::{
+        mixed_markets::{mixed_order_books_list, token, BITSTAMP, GATEHUB},
+        trust_lines::{rippling_network, DISCOUNTING_MAKER, MARKET_MAKER},
+    };
+
+    #[test]
+    fn test_get_routes() {
+        let network = rippling_network();
+        let (gatehub, bitstamp) = (token("USD", GATEHUB), token("USD", BITSTAMP));
+        let routes = network.get_routes(&gatehub, &bitstamp);
+        // The holder with rippling disabled is skipped.
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].intermediary, MARKET_MAKER);
+        assert_eq!(routes[0].rate, Decimal::ONE);
+        // Limited by the USD:Bitstamp balance of the market maker.
+        assert_eq!(routes[0].capacity, Decimal::from(500));
+        assert_eq!(routes[1].intermediary, DISCOUNTING_MAKER);
+        assert_eq!(routes[1].rate, Decimal::new(99, 2));
+        // Limited by the USD:GateHub limit of the discounting maker.
+        assert_eq!(routes[1].capacity, Decimal::from(200));
+        // Only the market maker holds USD:GateHub.
+        let routes = network.get_routes(&bitstamp, &gatehub);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].capacity, Decimal::from(100));
+        assert!(network
+            .get_routes(&gatehub, &token("EUR", GATEHUB))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_transfer_fees() {
+        let mut network = rippling_network();
+        for trust_line in network.trust_lines.iter_mut() {
+            trust_line.currency.transfer_fee = 0.002;
+        }
+        let (gatehub, bitstamp) = (token("USD", GATEHUB), token("USD", BITSTAMP));
+        let route = &network.get_routes(&gatehub, &bitstamp)[0];
+        let fee_multiplier = Decimal::new(998, 3);
+        assert_eq!(route.rate, fee_multiplier * fee_multiplier);
+        // The fee of the received token is applied to the order like to any other order.
+        assert_eq!(route.to_order().rate, fee_multiplier);
+    }
+
+    #[test]
+    fn test_get_rippling_opportunities() {
+        // Only XRP/USD:GateHub and USD:Bitstamp/XRP.
+        let order_books = OrderBooks::new(mixed_order_books_list()[..2].to_vec().into(), 0.05);
+        let opportunities = order_books
+            .get_rippling_opportunities(&rippling_network(), 3)
+            .unwrap();
+        // Converting USD:Bitstamp into USD:GateHub through XRP (4.80 * 0.24) beats rippling.
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert!(opportunity.route.from.is_same_asset(&token("USD", GATEHUB)));
+        assert_eq!(opportunity.route.rate, Decimal::ONE);
+        // USD:GateHub bought back at 0.26 and USD:Bitstamp bought at 5.00 XRP.
+        let order_book_rate = opportunity.order_book_rate.unwrap();
+        assert_eq!(order_book_rate.round_dp(4), Decimal::new(7692, 4));
     }
 }
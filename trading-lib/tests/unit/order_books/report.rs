@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod test_report {
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::{
+        order_book::OrderBook,
+        report::{LiquidityReportSortKey, OrderBookReport, DEPTH_LEVELS_PCT},
+    };
+
+    use crate::common::_static::order_books::{order_books, order_books_list};
+
+    #[test]
+    fn test_order_book_report() {
+        // EUR:rAPK/USD:rDk7 - bids 1.03 and 1.04, asks 1.06 and 1.08
+        let report = OrderBookReport::new(&order_books_list()[1], 0.05).unwrap();
+        assert_eq!(report.order_count, 4);
+        assert_eq!(report.maker_count, 0);
+        assert!(report.is_liquid);
+        assert!((report.spread_pct.unwrap() - 0.02 / 1.04).abs() < 1e-9);
+        assert_eq!(report.depths.len(), DEPTH_LEVELS_PCT.len());
+        // The mid rate is 1.05.
+        let depth = report.get_depth(1).unwrap();
+        assert_eq!(depth.bid_quantity, Decimal::from(100));
+        assert_eq!(depth.ask_quantity, Decimal::from(100));
+        let depth = report.get_depth(2).unwrap();
+        assert_eq!(depth.bid_quantity, Decimal::from(180));
+        assert_eq!(depth.ask_quantity, Decimal::from(100));
+        let depth = report.get_depth(5).unwrap();
+        assert_eq!(depth.get_total_quantity(), Decimal::from(380));
+    }
+
+    #[test]
+    fn test_maker_count_and_empty_side() {
+        let list = order_books_list();
+        let mut bids = list[0].get_bids().unwrap().orders.to_vec();
+        bids[0].offer_id = Some("rMaker1:1".into());
+        bids[1].offer_id = Some("rMaker1:2".into());
+        let mut asks = list[0].get_asks().unwrap().orders.to_vec();
+        asks[0].offer_id = Some("rMaker2:1".into());
+        let order_book = OrderBook::new(list[0].base.clone(), list[0].counter.clone(), bids, asks);
+        let report = OrderBookReport::new(&order_book, 0.05).unwrap();
+        assert_eq!(report.maker_count, 2);
+        // The spread is 0.02 / 0.24.
+        assert!(!report.is_liquid);
+
+        let order_book = OrderBook::new(
+            list[0].base.clone(),
+            list[0].counter.clone(),
+            list[0].get_bids().unwrap().orders.to_vec(),
+            Vec::new(),
+        );
+        let report = OrderBookReport::new(&order_book, 0.05).unwrap();
+        assert_eq!(report.spread_pct, None);
+        assert!(report.depths.is_empty());
+        assert!(!report.is_liquid);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut report = order_books().get_liquidity_report().unwrap();
+        assert_eq!(report.order_books.len(), 4);
+        report.sort(LiquidityReportSortKey::Spread);
+        assert!(report.order_books[0].base.currency_code == "EUR");
+        assert!(report
+            .order_books
+            .windows(2)
+            .all(|reports| reports[0].spread_pct <= reports[1].spread_pct));
+        report.sort(LiquidityReportSortKey::Depth(1));
+        assert!(report.order_books[0].base.currency_code == "EUR");
+        report.sort(LiquidityReportSortKey::OrderCount);
+        assert!(report
+            .order_books
+            .windows(2)
+            .all(|reports| reports[0].order_count >= reports[1].order_count));
+    }
+
+    #[test]
+    fn test_outputs() {
+        let mut report = order_books().get_liquidity_report().unwrap();
+        report.sort(LiquidityReportSortKey::Spread);
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("base,counter,spread_pct,bid_depth_1pct,ask_depth_1pct"));
+        assert!(lines[0].ends_with(",liquid"));
+        assert!(lines[1].starts_with("EUR.rAPKsP3tt7fV9Vj2QWzBk1r4Fg5vY1YhZ,"));
+        assert!(lines[1].ends_with(",true"));
+        assert_eq!(lines[1].split(',').count(), lines[0].split(',').count());
+
+        let table = report.to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        // The columns are aligned.
+        let separators: Vec<usize> = lines[0].match_indices(" | ").map(|(i, _)| i).collect();
+        assert!(lines[1..].iter().all(|line| {
+            line.match_indices(" | ")
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+                == separators
+        }));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let order_books = json["order_books"].as_array().unwrap();
+        assert_eq!(order_books.len(), 4);
+        assert_eq!(order_books[0]["is_liquid"], true);
+        assert_eq!(order_books[0]["depths"][0]["pct"], 1);
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod test_snapshots {
+    use std::{io::Cursor, str::FromStr};
+
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::{
+        order::Order,
+        snapshots::{OrderBooksSnapshot, SnapshotEncoding, SnapshotReader, SnapshotWriter},
+    };
+
+    use crate::common::_static::order_books::order_books;
+
+    /// Returns snapshots of the static order books with decimals that only survive a lossless
+    /// encoding: trailing zeros, the maximum and minimum representable values and high precision.
+    fn generate_snapshots() -> Vec<OrderBooksSnapshot<'static>> {
+        let mut snapshot =
+            OrderBooksSnapshot::new(&order_books(), 88_000_000, 770_000_000).unwrap();
+        let order_book = &mut snapshot.order_books[0];
+        let template = order_book.bids[0].clone();
+        for (base_quantity, rate) in [
+            ("10.000", "0.2300"),
+            (
+                "79228162514264337593543950335",
+                "0.0000000000000000000000000001",
+            ),
+            (
+                "0.1234567890123456789012345678",
+                "3.1415926535897932384626433832",
+            ),
+        ] {
+            order_book.bids.push(Order {
+                base_quantity: Decimal::from_str(base_quantity).unwrap(),
+                rate: Decimal::from_str(rate).unwrap(),
+                ..template.clone()
+            });
+        }
+        // An order with the currencies of the flipped order book.
+        let mut flipped = template.clone();
+        flipped.offer_id = Some("rPVMhWBsfF9iMXYj3aAzJVkPDTFNSyWdKy:42".into());
+        std::mem::swap(&mut flipped.base, &mut flipped.counter);
+        order_book.asks.push(flipped);
+
+        let mut next_snapshot = snapshot.clone();
+        next_snapshot.ledger_index += 1;
+        next_snapshot.timestamp += 4;
+        next_snapshot.order_books.pop();
+
+        vec![snapshot, next_snapshot]
+    }
+
+    fn assert_snapshots_eq(left: &[OrderBooksSnapshot<'_>], right: &[OrderBooksSnapshot<'_>]) {
+        assert_eq!(left.len(), right.len());
+        for (left, right) in left.iter().zip(right.iter()) {
+            assert_eq!(left.ledger_index, right.ledger_index);
+            assert_eq!(left.timestamp, right.timestamp);
+            assert_eq!(left.liquidity_spread, right.liquidity_spread);
+            assert_eq!(left.order_books.len(), right.order_books.len());
+            for (left, right) in left.order_books.iter().zip(right.order_books.iter()) {
+                assert_eq!(left.base, right.base);
+                assert_eq!(left.counter, right.counter);
+                for (left, right) in [(&left.bids, &right.bids), (&left.asks, &right.asks)] {
+                    assert_eq!(left.len(), right.len());
+                    for (left, right) in left.iter().zip(right.iter()) {
+                        assert_eq!(left.base, right.base);
+                        assert_eq!(left.counter, right.counter);
+                        assert_eq!(left.offer_id, right.offer_id);
+                        // Compare the representation to also check the scale is preserved.
+                        assert_eq!(
+                            left.base_quantity.serialize(),
+                            right.base_quantity.serialize()
+                        );
+                        assert_eq!(left.rate.serialize(), right.rate.serialize());
+                    }
+                }
+            }
+        }
+    }
+
+    fn round_trip(encoding: SnapshotEncoding) {
+        let snapshots = generate_snapshots();
+        let mut writer = SnapshotWriter::new(Vec::new(), encoding);
+        for snapshot in snapshots.iter() {
+            writer.write_snapshot(snapshot).unwrap();
+        }
+        let bytes = writer.into_inner();
+        let read_snapshots = SnapshotReader::new(Cursor::new(bytes), encoding)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_snapshots_eq(&snapshots, &read_snapshots);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        round_trip(SnapshotEncoding::Binary);
+    }
+
+    #[test]
+    fn test_json_lines_round_trip() {
+        round_trip(SnapshotEncoding::JsonLines);
+    }
+
+    #[test]
+    fn test_into_order_books() {
+        let snapshot = generate_snapshots().remove(0);
+        let order_books = snapshot.clone().into_order_books();
+        assert_eq!(order_books.order_books.len(), snapshot.order_books.len());
+        assert_eq!(
+            order_books.order_books[0].get_bids().unwrap().orders.len(),
+            snapshot.order_books[0].bids.len()
+        );
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut reader = SnapshotReader::new(
+            Cursor::new(b"NOPE\x01\x00".to_vec()),
+            SnapshotEncoding::Binary,
+        );
+        assert!(reader.read_snapshot().is_err());
+        let mut reader = SnapshotReader::new(
+            Cursor::new(b"{\"version\":999}\n".to_vec()),
+            SnapshotEncoding::JsonLines,
+        );
+        assert!(reader.read_snapshot().is_err());
+    }
+
+    #[test]
+    fn test_empty_stream() {
+        let mut writer = SnapshotWriter::new(Vec::new(), SnapshotEncoding::JsonLines);
+        writer.flush().unwrap();
+        let bytes = writer.into_inner();
+        let mut reader = SnapshotReader::new(Cursor::new(bytes), SnapshotEncoding::JsonLines);
+        assert!(reader.read_snapshot().unwrap().is_none());
+    }
+}
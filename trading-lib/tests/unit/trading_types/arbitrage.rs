@@ -0,0 +1,2336 @@
+#[cfg(test)]
+mod test_swap_sizing {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        models::currency::Currency, order_books::order::Order,
+        trading_types::arbitrage::swap::SwapTrade,
+    };
+
+    use crate::common::_static::order_books::order_books_list;
+
+    pub(super) fn xrp() -> Currency<'static> {
+        Currency::xrp()
+    }
+
+    pub(super) fn usd(transfer_fee: f32) -> Currency<'static> {
+        Currency {
+            currency_code: Cow::Borrowed("USD"),
+            issuer: Cow::Borrowed("rDk7FQvkQxQQNGTtfM2Fr66s7Nm3k87vdS"),
+            transfer_fee,
+        }
+    }
+
+    pub(super) fn level(
+        base: Currency<'static>,
+        counter: Currency<'static>,
+        base_quantity: i64,
+        rate: Decimal,
+    ) -> Order<'static> {
+        Order {
+            base,
+            counter,
+            base_quantity: Decimal::from(base_quantity),
+            rate,
+            offer_id: None,
+        }
+    }
+
+    /// Sells XRP for USD at 0.30, 0.25 and 0.20 and buys XRP back at 4.2 and 4.1.
+    pub(super) fn swap_trade(transfer_fee: f32) -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![
+                level(xrp(), usd(transfer_fee), 100, Decimal::new(30, 2)),
+                level(xrp(), usd(transfer_fee), 100, Decimal::new(25, 2)),
+                level(xrp(), usd(transfer_fee), 100, Decimal::new(20, 2)),
+            ],
+            vec![
+                level(usd(transfer_fee), xrp(), 20, Decimal::new(42, 1)),
+                level(usd(transfer_fee), xrp(), 100, Decimal::new(41, 1)),
+            ],
+            xrp(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_optimal_size_walks_levels() {
+        let sizing = swap_trade(0.0).get_optimal_size();
+        // 0.30 * 4.2, 0.30 * 4.1 and 0.25 * 4.1 are profitable, 0.20 * 4.1 is not.
+        assert_eq!(sizing.input_quantity.round_dp(10), Decimal::from(200));
+        assert_eq!(sizing.intermediate_quantity.round_dp(10), Decimal::from(55));
+        assert_eq!(sizing.output_quantity.round_dp(10), Decimal::new(2275, 1));
+        assert_eq!(sizing.get_profit().round_dp(10), Decimal::new(275, 1));
+
+        let sell_levels: Vec<(usize, Decimal)> = sizing
+            .sell_levels
+            .iter()
+            .map(|level| (level.index, level.base_quantity.round_dp(10)))
+            .collect();
+        assert_eq!(
+            sell_levels,
+            vec![(0, Decimal::from(100)), (1, Decimal::from(100))]
+        );
+        let buy_levels: Vec<(usize, Decimal)> = sizing
+            .buy_levels
+            .iter()
+            .map(|level| (level.index, level.base_quantity.round_dp(10)))
+            .collect();
+        assert_eq!(
+            buy_levels,
+            vec![(0, Decimal::from(20)), (1, Decimal::from(35))]
+        );
+    }
+
+    #[test]
+    fn test_optimal_size_with_transfer_fee() {
+        // With 10% transfer fee on USD only 0.27 * 4.2 and 0.27 * 4.1 are profitable.
+        let sizing = swap_trade(0.1).get_optimal_size();
+        assert_eq!(sizing.sell_levels.len(), 1);
+        assert_eq!(
+            sizing.sell_levels[0].base_quantity.round_dp(10),
+            Decimal::from(100)
+        );
+        assert_eq!(sizing.buy_levels.len(), 2);
+        assert!(sizing.get_profit() > Decimal::ZERO);
+        assert!(sizing.get_profit() < swap_trade(0.0).get_optimal_size().get_profit());
+    }
+
+    #[test]
+    fn test_optimal_size_unprofitable() {
+        let swap_trade = SwapTrade::new(
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(20, 2))],
+            vec![level(usd(0.0), xrp(), 100, Decimal::new(4, 0))],
+            xrp(),
+        )
+        .unwrap();
+        let sizing = swap_trade.get_optimal_size();
+        assert_eq!(sizing.input_quantity, Decimal::ZERO);
+        assert!(sizing.sell_levels.is_empty());
+        assert!(sizing.buy_levels.is_empty());
+    }
+
+    #[test]
+    fn test_empty_leg() {
+        assert!(SwapTrade::new(
+            Vec::new(),
+            vec![level(usd(0.0), xrp(), 100, Decimal::new(4, 0))],
+            xrp()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_try_from_keeps_all_levels() {
+        let list = order_books_list();
+        let mut gatehub = list[0].clone();
+        let mut bitstamp = list[3].clone();
+        let swap_trade =
+            SwapTrade::try_from((&mut gatehub, &mut bitstamp, Cow::Borrowed("XRP"))).unwrap();
+        assert_eq!(swap_trade.sell_levels.len(), 2);
+        assert_eq!(swap_trade.buy_levels.len(), 2);
+        assert_eq!(swap_trade.sell_order, swap_trade.sell_levels[0]);
+        // The best bid of GateHub and the flipped best ask of Bitstamp.
+        assert_eq!(swap_trade.sell_order.rate, Decimal::new(24, 2));
+        assert_eq!(swap_trade.buy_order.base.currency_code, "USD");
+        assert_eq!(
+            swap_trade.buy_order.rate,
+            Decimal::ONE / Decimal::new(26, 2)
+        );
+        assert!(swap_trade
+            .sell_levels
+            .windows(2)
+            .all(|levels| levels[0].rate >= levels[1].rate));
+        assert!(swap_trade
+            .buy_levels
+            .windows(2)
+            .all(|levels| levels[0].rate >= levels[1].rate));
+    }
+}
+
+#[cfg(test)]
+mod test_profit_estimate {
+    use rust_decimal::Decimal;
+    use trading_lib::trading_types::arbitrage::{
+        profit::GetProfitEstimate,
+        swap::{GetProfitableTrades, SwapTrade},
+    };
+
+    use super::test_swap_sizing::{level, swap_trade, usd, xrp};
+    use crate::common::_static::order_books::order_books;
+
+    #[test]
+    fn test_profit_estimate() {
+        let estimate = swap_trade(0.0).get_profit_estimate(12);
+        assert!(estimate.is_profitable());
+        assert!(estimate.starting_currency.is_xrp());
+        assert_eq!(estimate.input_quantity.round_dp(10), Decimal::from(200));
+        assert_eq!(estimate.output_quantity.round_dp(10), Decimal::new(2275, 1));
+        assert_eq!(estimate.network_fee, Some(Decimal::new(12, 6)));
+        assert_eq!(
+            estimate.profit.round_dp(10),
+            Decimal::new(275, 1) - Decimal::new(12, 6)
+        );
+        assert_eq!(estimate.profit_pct.round_dp(6), Decimal::new(13_749_994, 6));
+        assert_eq!(estimate.transfer_fees, vec![Decimal::ZERO, Decimal::ZERO]);
+        // The first segment earns 0.26 XRP per XRP.
+        assert_eq!(
+            estimate.break_even_quantity.unwrap().round_dp(10),
+            (Decimal::new(12, 6) / Decimal::new(26, 2)).round_dp(10)
+        );
+    }
+
+    #[test]
+    fn test_transfer_fees() {
+        let estimate = swap_trade(0.1).get_profit_estimate(0);
+        // 100 XRP are sold at 0.30 and 10% of the 30 USD are paid as transfer fee. XRP has none.
+        assert_eq!(estimate.transfer_fees[0].round_dp(10), Decimal::from(3));
+        assert_eq!(estimate.transfer_fees[1], Decimal::ZERO);
+        assert_eq!(estimate.break_even_quantity, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_unprofitable_estimate() {
+        let swap_trade = SwapTrade::new(
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(20, 2))],
+            vec![level(usd(0.0), xrp(), 100, Decimal::new(4, 0))],
+            xrp(),
+        )
+        .unwrap();
+        let estimate = swap_trade.get_profit_estimate(12);
+        assert!(!estimate.is_profitable());
+        assert_eq!(estimate.input_quantity, Decimal::ZERO);
+        assert_eq!(estimate.profit, -Decimal::new(12, 6));
+        assert_eq!(estimate.break_even_quantity, None);
+    }
+
+    #[test]
+    fn test_network_fee_conversion() {
+        // Sells USD for XRP at 4.2 and buys USD back at 0.25.
+        let swap_trade = SwapTrade::new(
+            vec![level(usd(0.0), xrp(), 10, Decimal::new(42, 1))],
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(25, 2))],
+            usd(0.0),
+        )
+        .unwrap();
+        let estimate = swap_trade.get_profit_estimate(1_000_000);
+        assert_eq!(estimate.network_fee, Some(Decimal::new(25, 2)));
+
+        // Without XRP in the swap the network fee can't be converted.
+        let mut eur = usd(0.0);
+        eur.currency_code = "EUR".into();
+        let swap_trade = SwapTrade::new(
+            vec![level(usd(0.0), eur.clone(), 10, Decimal::new(95, 2))],
+            vec![level(eur, usd(0.0), 100, Decimal::new(11, 1))],
+            usd(0.0),
+        )
+        .unwrap();
+        let estimate = swap_trade.get_profit_estimate(12);
+        assert_eq!(estimate.network_fee, None);
+        assert_eq!(estimate.profit.round_dp(10), Decimal::new(45, 2));
+    }
+
+    #[test]
+    fn test_profitable_trades_sorted() {
+        let mut order_books = order_books();
+        // Only the XRP/USD order books of GateHub and Bitstamp.
+        order_books.order_books.to_mut().retain(|order_book| {
+            order_book.base.is_xrp() && order_book.counter.currency_code == "USD"
+        });
+        order_books.reindex();
+        // Bitstamp bids 0.30 and 0.29 USD for XRP which GateHub sells for 0.26 USD.
+        for (order, rate) in order_books.order_books[1]
+            .get_bids()
+            .unwrap()
+            .orders
+            .to_mut()
+            .iter_mut()
+            .zip([Decimal::new(29, 2), Decimal::new(30, 2)])
+        {
+            order.rate = rate;
+        }
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        assert!(!profitable_trades.is_empty());
+        assert!(profitable_trades
+            .iter()
+            .all(|profitable_trade| profitable_trade.estimate.is_profitable()));
+        assert!(profitable_trades.windows(2).all(|profitable_trades| {
+            profitable_trades[0].estimate.profit >= profitable_trades[1].estimate.profit
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test_pair_matching {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        order_books::{order_book::OrderBook, OrderBooks},
+        trading_types::arbitrage::swap::{GetProfitableTrades, SwapTrade},
+    };
+
+    use crate::common::_static::{
+        mixed_markets::{
+            mixed_order_books, mixed_order_books_list, order_book, token, xrp, ANODE, BITSTAMP,
+            GATEHUB, SNAPSWAP,
+        },
+        order_books::order_books,
+    };
+
+    /// XRP/USD:Bitstamp with the same rates as XRP/USD:GateHub.
+    fn bitstamp() -> OrderBook<'static> {
+        order_book(
+            xrp(),
+            token("USD", BITSTAMP),
+            &[(100, 24), (80, 23)],
+            &[(100, 26), (100, 28)],
+        )
+    }
+
+    fn flipped(order_book: &OrderBook<'static>) -> OrderBook<'static> {
+        let (bids, asks) = order_book.get_oriented_orders(true).unwrap();
+        OrderBook::new(
+            order_book.counter.clone(),
+            order_book.base.clone(),
+            bids,
+            asks,
+        )
+    }
+
+    fn swap(
+        sell_order_book: &OrderBook<'static>,
+        buy_order_book: &OrderBook<'static>,
+        trading_currency: &'static str,
+    ) -> SwapTrade<'static> {
+        SwapTrade::try_from((
+            &mut sell_order_book.clone(),
+            &mut buy_order_book.clone(),
+            Cow::Borrowed(trading_currency),
+        ))
+        .unwrap()
+    }
+
+    /// Asserts the trade starts and ends with the currency and returns the rates of the best
+    /// sell and buy order rounded to 10 decimal places.
+    fn assert_round_trip(trade: &SwapTrade<'_>, currency_code: &str) -> (Decimal, Decimal) {
+        assert_eq!(trade.starting_currency.currency_code, currency_code);
+        assert_eq!(trade.sell_order.base.currency_code, currency_code);
+        assert_eq!(trade.buy_order.counter.currency_code, currency_code);
+        assert_eq!(
+            trade.sell_order.counter.currency_code,
+            trade.buy_order.base.currency_code
+        );
+
+        (
+            trade.sell_order.rate.round_dp(10),
+            trade.buy_order.rate.round_dp(10),
+        )
+    }
+
+    #[test]
+    fn test_swap_examples() {
+        let gatehub = mixed_order_books_list()[0].clone();
+        let bitstamp_flipped = mixed_order_books_list()[1].clone();
+        let best_bid = Decimal::new(24, 2);
+        let best_ask_flipped = (Decimal::ONE / Decimal::new(26, 2)).round_dp(10);
+
+        // 1. Example: bid of GateHub, ask of Bitstamp
+        let trade = swap(&gatehub, &bitstamp(), "XRP");
+        assert_eq!(
+            assert_round_trip(&trade, "XRP"),
+            (best_bid, best_ask_flipped)
+        );
+        // 2. Example: ask of GateHub, bid of Bitstamp
+        let trade = swap(&gatehub, &bitstamp(), "USD");
+        assert_eq!(
+            assert_round_trip(&trade, "USD"),
+            (best_ask_flipped, best_bid)
+        );
+        // 3. Example: bid of GateHub, bid of USD:Bitstamp/XRP
+        let trade = swap(&gatehub, &bitstamp_flipped, "XRP");
+        assert_eq!(
+            assert_round_trip(&trade, "XRP"),
+            (best_bid, Decimal::new(480, 2))
+        );
+        // 4. Example: ask of GateHub, ask of USD:Bitstamp/XRP
+        let trade = swap(&gatehub, &bitstamp_flipped, "USD");
+        assert_eq!(
+            assert_round_trip(&trade, "USD"),
+            (
+                best_ask_flipped,
+                (Decimal::ONE / Decimal::new(500, 2)).round_dp(10)
+            )
+        );
+        // 5. Example: ask of USD:GateHub/XRP, ask of Bitstamp
+        let trade = swap(&flipped(&gatehub), &bitstamp(), "XRP");
+        assert_eq!(
+            assert_round_trip(&trade, "XRP"),
+            (best_bid, best_ask_flipped)
+        );
+        // 6. Example: bid of USD:GateHub/XRP, bid of Bitstamp
+        let trade = swap(&flipped(&gatehub), &bitstamp(), "USD");
+        assert_eq!(
+            assert_round_trip(&trade, "USD"),
+            (best_ask_flipped, best_bid)
+        );
+    }
+
+    #[test]
+    fn test_incompatible_combinations() {
+        let list = mixed_order_books_list();
+        // XRP/USD and XRP/EUR don't share both currency codes.
+        assert!(SwapTrade::try_from((
+            &mut list[0].clone(),
+            &mut list[4].clone(),
+            Cow::Borrowed("XRP")
+        ))
+        .is_err());
+        // XRP/USD:SnapSwap has no asks to buy from.
+        assert!(SwapTrade::try_from((
+            &mut list[0].clone(),
+            &mut list[5].clone(),
+            Cow::Borrowed("XRP")
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_get_profitable_trades_mixed_markets() {
+        let order_books = mixed_order_books();
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        assert!(!profitable_trades.is_empty());
+        assert!(profitable_trades.windows(2).all(|profitable_trades| {
+            profitable_trades[0].estimate.profit >= profitable_trades[1].estimate.profit
+        }));
+        // Selling XRP for USD and buying it back on USD:Bitstamp/XRP.
+        let is_xrp_trade = |sell_issuer: &str| {
+            profitable_trades.iter().any(|profitable_trade| {
+                let trade = &profitable_trade.trade;
+                trade.starting_currency.is_xrp()
+                    && trade.sell_order.counter.issuer == sell_issuer
+                    && trade.buy_order.base.issuer == BITSTAMP
+            })
+        };
+        assert!(is_xrp_trade(GATEHUB));
+        assert!(is_xrp_trade(SNAPSWAP));
+        // Selling EUR on USD:Bitstamp/EUR:Anode and buying it back on EUR:Anode/USD:GateHub.
+        assert!(profitable_trades.iter().any(|profitable_trade| {
+            let trade = &profitable_trade.trade;
+            trade.starting_currency.is_same_asset(&token("EUR", ANODE))
+                && trade.sell_order.counter.issuer == BITSTAMP
+                && trade.buy_order.base.issuer == GATEHUB
+        }));
+        // Only markets sharing both currency codes are swapped.
+        assert!(profitable_trades.iter().all(|profitable_trade| {
+            let trade = &profitable_trade.trade;
+            trade
+                .sell_order
+                .counter
+                .is_same_currency(&trade.buy_order.base)
+                && !["BTC", "ETH"].contains(&trade.starting_currency.currency_code.as_ref())
+        }));
+    }
+
+    #[test]
+    fn test_exposure_cap_shrinks_trade() {
+        /// The input quantity and the USD:GateHub taken in by selling XRP on XRP/USD:GateHub.
+        fn get_gatehub_trade(order_books: &OrderBooks<'_>) -> (Decimal, Decimal) {
+            let profitable_trade = order_books
+                .get_profitable_trades(12)
+                .unwrap()
+                .into_iter()
+                .find(|profitable_trade| {
+                    let trade = &profitable_trade.trade;
+                    trade.starting_currency.is_xrp() && trade.sell_order.counter.issuer == GATEHUB
+                })
+                .unwrap();
+            let sell_order = &profitable_trade.trade.sell_order;
+
+            (
+                profitable_trade.estimate.input_quantity,
+                sell_order.base_quantity * sell_order.rate,
+            )
+        }
+        let mut order_books = mixed_order_books();
+        let (uncapped_input, uncapped_taken) = get_gatehub_trade(&order_books);
+        assert!(uncapped_taken > Decimal::from(5));
+        order_books
+            .issuer_policy
+            .exposure_caps
+            .insert(GATEHUB.into(), Decimal::from(5));
+        let (capped_input, capped_taken) = get_gatehub_trade(&order_books);
+        assert!(capped_input < uncapped_input);
+        assert!(capped_taken <= Decimal::from(5));
+    }
+
+    #[test]
+    fn test_own_offer_is_not_traded() {
+        // The only profitable level is the best GateHub bid, both with XRP and with USD as the
+        // trading currency.
+        let gatehub = order_book(
+            xrp(),
+            token("USD", GATEHUB),
+            &[(100, 24), (100, 20)],
+            &[(100, 30)],
+        );
+        gatehub.get_bids().unwrap().orders.to_mut()[0].offer_id = Some("rOwn:1".into());
+        let bitstamp = order_book(xrp(), token("USD", BITSTAMP), &[(100, 21)], &[(100, 22)]);
+        let mut order_books = OrderBooks::new(vec![gatehub, bitstamp].into(), 0.05);
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        assert!(!profitable_trades.is_empty());
+        assert!(profitable_trades.iter().all(|profitable_trade| {
+            let trade = &profitable_trade.trade;
+            [&trade.sell_order, &trade.buy_order]
+                .iter()
+                .any(|order| order.get_owner() == Some("rOwn"))
+        }));
+        // Our own offer isn't taken.
+        order_books.own_accounts.insert("rOwn".into());
+        assert!(order_books.get_profitable_trades(12).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_profitable_trades_static_fixtures() {
+        // The static order books mix XRP/USD, EUR/USD and XRP/EUR markets.
+        let order_books = order_books();
+        assert!(order_books.get_profitable_trades(12).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_network_fee_without_xrp() {
+        // EUR:GateHub sold at 1.20 for USD:GateHub and EUR:Bitstamp bought back at 1.16.
+        let eur_order_books = vec![
+            order_book(
+                token("EUR", GATEHUB),
+                token("USD", GATEHUB),
+                &[(100, 120)],
+                &[(100, 122)],
+            ),
+            order_book(
+                token("EUR", BITSTAMP),
+                token("USD", GATEHUB),
+                &[(100, 114)],
+                &[(100, 116)],
+            ),
+        ];
+        // The network fee can't be converted, so no swap is profitable.
+        let order_books = OrderBooks::new(eur_order_books.clone().into(), 0.05);
+        assert!(order_books.get_profitable_trades(12).unwrap().is_empty());
+
+        let mut with_xrp_order_books = eur_order_books;
+        for currency in [
+            token("EUR", GATEHUB),
+            token("EUR", BITSTAMP),
+            token("USD", GATEHUB),
+        ] {
+            with_xrp_order_books.push(order_book(xrp(), currency, &[(100, 25)], &[(100, 27)]));
+        }
+        let order_books = OrderBooks::new(with_xrp_order_books.into(), 0.05);
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        let swaps_without_xrp: Vec<_> = profitable_trades
+            .iter()
+            .filter(|profitable_trade| {
+                !profitable_trade.trade.starting_currency.is_xrp()
+                    && !profitable_trade.trade.buy_order.base.is_xrp()
+            })
+            .collect();
+        assert!(!swaps_without_xrp.is_empty());
+        for profitable_trade in swaps_without_xrp {
+            // 0.000012 XRP sold for the starting currency at 0.25.
+            assert_eq!(
+                profitable_trade.estimate.network_fee,
+                Some(Decimal::new(3, 6))
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_funding_constraints {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        models::{
+            account::{AccountState, TrustLine},
+            currency::Currency,
+        },
+        trading_types::arbitrage::swap::{constraints::SwapConstraint, GetProfitableTrades},
+    };
+
+    use super::test_swap_sizing::{swap_trade, usd, xrp};
+    use crate::common::_static::mixed_markets::{
+        mixed_order_books, token, BITSTAMP, GATEHUB, SNAPSWAP,
+    };
+
+    fn account(xrp_balance: i64) -> AccountState<'static> {
+        AccountState::new(
+            Cow::Borrowed("rTradingAccount1111111111111111111"),
+            Decimal::from(xrp_balance),
+        )
+    }
+
+    fn trust_line(currency: Currency<'static>, balance: i64, limit: i64) -> TrustLine<'static> {
+        TrustLine {
+            currency,
+            balance: Decimal::from(balance),
+            limit: Decimal::from(limit),
+        }
+    }
+
+    #[test]
+    fn test_account_state() {
+        let mut account = account(100);
+        account.add_trust_line(trust_line(usd(0.0), 30, 50));
+        // 1 XRP base reserve and 0.2 XRP for the trust line.
+        assert_eq!(account.get_reserve(), Decimal::new(12, 1));
+        assert_eq!(account.get_spendable(&xrp()), Some(Decimal::new(988, 1)));
+        assert_eq!(account.get_receivable(&xrp()), None);
+        assert_eq!(account.get_spendable(&usd(0.0)), Some(Decimal::from(30)));
+        assert_eq!(account.get_receivable(&usd(0.0)), Some(Decimal::from(20)));
+        // Without trust line the token can neither be given nor received.
+        let eur = token("EUR", GATEHUB);
+        assert_eq!(account.get_spendable(&eur), Some(Decimal::ZERO));
+        assert_eq!(account.get_receivable(&eur), Some(Decimal::ZERO));
+        // The issuer of a token has no limits.
+        let own_token = token("EUR", "rTradingAccount1111111111111111111");
+        assert_eq!(account.get_spendable(&own_token), None);
+        assert_eq!(account.get_receivable(&own_token), None);
+    }
+
+    #[test]
+    fn test_capped_by_xrp_reserve() {
+        let mut account = account(101);
+        account.add_trust_line(trust_line(usd(0.0), 0, 1_000));
+        let sizing = swap_trade(0.0).get_funded_size(&account);
+        assert_eq!(sizing.input_quantity, Decimal::new(998, 1));
+        assert_eq!(sizing.limited_by, Some(SwapConstraint::XrpReserve));
+        assert_eq!(sizing.sell_levels.len(), 1);
+        assert!(sizing.get_profit() > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_capped_by_trust_line_limit() {
+        let mut account = account(1_000);
+        account.add_trust_line(trust_line(usd(0.0), 0, 20));
+        let sizing = swap_trade(0.0).get_funded_size(&account);
+        // 20 USD are bought for 66.67 XRP at 0.30 and sold at 4.2.
+        assert_eq!(sizing.intermediate_quantity.round_dp(10), Decimal::from(20));
+        assert_eq!(
+            sizing.input_quantity.round_dp(10),
+            (Decimal::from(20) / Decimal::new(30, 2)).round_dp(10)
+        );
+        assert_eq!(sizing.limited_by, Some(SwapConstraint::TrustLineLimit));
+    }
+
+    #[test]
+    fn test_not_capped() {
+        let mut account = account(1_000);
+        account.add_trust_line(trust_line(usd(0.0), 0, 1_000));
+        let sizing = swap_trade(0.0).get_funded_size(&account);
+        assert_eq!(sizing, swap_trade(0.0).get_optimal_size());
+        assert_eq!(sizing.limited_by, None);
+    }
+
+    #[test]
+    fn test_unfundable_trades_flagged() {
+        let mut order_books = mixed_order_books();
+        let mut account = account(1_000);
+        account.add_trust_line(trust_line(token("USD", GATEHUB), 0, 1_000));
+        account.add_trust_line(trust_line(token("USD", BITSTAMP), 1_000, 1_000));
+        order_books.trading_account = Some(account);
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        let find_xrp_trade = |sell_issuer: &str| {
+            profitable_trades
+                .iter()
+                .find(|profitable_trade| {
+                    let trade = &profitable_trade.trade;
+                    trade.starting_currency.is_xrp()
+                        && trade.sell_order.counter.issuer == sell_issuer
+                        && trade.buy_order.base.issuer == BITSTAMP
+                })
+                .unwrap()
+        };
+        // USD:GateHub can be received and USD:Bitstamp given.
+        let gatehub_trade = find_xrp_trade(GATEHUB);
+        assert!(gatehub_trade.is_fundable);
+        assert!(gatehub_trade.estimate.is_profitable());
+        // There is no trust line to receive USD:SnapSwap.
+        let snapswap_trade = find_xrp_trade(SNAPSWAP);
+        assert!(!snapswap_trade.is_fundable);
+        assert_eq!(
+            snapswap_trade.limited_by,
+            Some(SwapConstraint::TrustLineLimit)
+        );
+        assert_eq!(snapswap_trade.estimate.input_quantity, Decimal::ZERO);
+        // Unfundable trades come last.
+        let first_unfundable = profitable_trades
+            .iter()
+            .position(|profitable_trade| !profitable_trade.is_fundable)
+            .unwrap();
+        assert!(profitable_trades[first_unfundable..]
+            .iter()
+            .all(|profitable_trade| !profitable_trade.is_fundable));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "xrpl")]
+mod test_swap_payment {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::trading_types::arbitrage::swap::{
+        exceptions::SwapArbitrageException, payment::SwapPaymentMode, SwapTrade,
+    };
+    use xrpl::models::{
+        amount::{Amount, IssuedCurrencyAmount, XRPAmount},
+        transactions::{OfferCreate, PaymentFlag},
+        FlagCollection, PathStep,
+    };
+
+    use super::test_swap_sizing::{level, swap_trade, usd, xrp};
+
+    const ACCOUNT: &str = "rTradingAccount1111111111111111111";
+
+    /// Sells USD for XRP at 4.2 and buys USD back at 0.25.
+    fn usd_swap_trade() -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![level(usd(0.0), xrp(), 10, Decimal::new(42, 1))],
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(25, 2))],
+            usd(0.0),
+        )
+        .unwrap()
+    }
+
+    fn usd_amount(value: &'static str) -> Amount<'static> {
+        IssuedCurrencyAmount::new(
+            usd(0.0).currency_code,
+            usd(0.0).issuer,
+            Cow::Borrowed(value),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_exact_payment() {
+        let trade = usd_swap_trade();
+        let payment = trade
+            .get_payment(
+                Cow::Borrowed(ACCOUNT),
+                &trade.get_optimal_size(),
+                SwapPaymentMode::Exact,
+            )
+            .unwrap();
+        assert_eq!(payment.account, ACCOUNT);
+        assert_eq!(payment.destination, ACCOUNT);
+        // 10 USD buy 42 XRP which buy 10.5 USD.
+        assert_eq!(payment.send_max, Some(usd_amount("10")));
+        assert_eq!(payment.amount, usd_amount("10.5"));
+        assert_eq!(payment.deliver_min, None);
+        assert_eq!(
+            payment.flags,
+            Some(FlagCollection::new(vec![PaymentFlag::TfNoDirectRipple]))
+        );
+        assert_eq!(
+            payment.paths,
+            Some(vec![vec![
+                PathStep {
+                    currency: Some(Cow::Borrowed("XRP")),
+                    ..Default::default()
+                },
+                PathStep {
+                    currency: Some(usd(0.0).currency_code),
+                    issuer: Some(usd(0.0).issuer),
+                    ..Default::default()
+                },
+            ]])
+        );
+    }
+
+    #[test]
+    fn test_partial_payment() {
+        let trade = usd_swap_trade();
+        let sizing = trade.get_optimal_size();
+        let payment = trade
+            .get_payment(
+                Cow::Borrowed(ACCOUNT),
+                &sizing,
+                SwapPaymentMode::Partial {
+                    min_output_quantity: Decimal::from(10),
+                    limit_quality: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(payment.deliver_min, Some(usd_amount("10")));
+        assert_eq!(
+            payment.flags,
+            Some(FlagCollection::new(vec![
+                PaymentFlag::TfNoDirectRipple,
+                PaymentFlag::TfPartialPayment,
+                PaymentFlag::TfLimitQuality,
+            ]))
+        );
+
+        let error = trade
+            .get_payment(
+                Cow::Borrowed(ACCOUNT),
+                &sizing,
+                SwapPaymentMode::Partial {
+                    min_output_quantity: Decimal::from(11),
+                    limit_quality: false,
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SwapArbitrageException>(),
+            Some(SwapArbitrageException::InvalidMinOutputQuantity)
+        ));
+    }
+
+    #[test]
+    fn test_unpayable_swaps() {
+        let get_error = |trade: &SwapTrade<'static>| {
+            trade
+                .get_payment(
+                    Cow::Borrowed(ACCOUNT),
+                    &trade.get_optimal_size(),
+                    SwapPaymentMode::Exact,
+                )
+                .unwrap_err()
+                .downcast::<SwapArbitrageException>()
+                .unwrap()
+        };
+        assert!(matches!(
+            get_error(&swap_trade(0.0)),
+            SwapArbitrageException::XrpRoundTripPayment
+        ));
+        let mut eur = usd(0.0);
+        eur.currency_code = "EUR".into();
+        let mut other_eur = eur.clone();
+        other_eur.issuer = "rOtherIssuer".into();
+        let trade = SwapTrade::new(
+            vec![level(usd(0.0), eur, 10, Decimal::new(95, 2))],
+            vec![level(other_eur, usd(0.0), 100, Decimal::new(11, 1))],
+            usd(0.0),
+        )
+        .unwrap();
+        assert!(matches!(
+            get_error(&trade),
+            SwapArbitrageException::DisconnectedLegs
+        ));
+        // Nothing to pay if the swap isn't profitable.
+        let trade = SwapTrade::new(
+            vec![level(usd(0.0), xrp(), 10, Decimal::new(4, 0))],
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(25, 2))],
+            usd(0.0),
+        )
+        .unwrap();
+        assert!(matches!(
+            get_error(&trade),
+            SwapArbitrageException::EmptySwapSize
+        ));
+    }
+
+    #[test]
+    fn test_offers() {
+        let trade = usd_swap_trade();
+        let (sell_offer, buy_offer) =
+            trade.get_offers(Cow::Borrowed(ACCOUNT), &trade.get_optimal_size());
+        // Each offer gives the base of its leg for the counter.
+        assert_eq!(sell_offer.taker_gets, usd_amount("10"));
+        assert_eq!(
+            sell_offer.taker_pays,
+            Amount::XRPAmount(XRPAmount(Cow::Borrowed("42000000")))
+        );
+        assert_eq!(
+            buy_offer.taker_gets,
+            Amount::XRPAmount(XRPAmount(Cow::Borrowed("42000000")))
+        );
+        assert_eq!(buy_offer.taker_pays, usd_amount("10.5"));
+        // Without an account at the optimal size.
+        let (sell_offer, buy_offer): (OfferCreate<'_>, OfferCreate<'_>) = trade.into();
+        assert_eq!(sell_offer.account, "");
+        assert_eq!(sell_offer.taker_gets, usd_amount("10"));
+        assert_eq!(buy_offer.taker_pays, usd_amount("10.5"));
+    }
+}
+
+#[cfg(test)]
+mod test_swap_plan {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        models::{
+            account::{AccountState, TrustLine},
+            currency::Currency,
+        },
+        order_books::OrderBooks,
+        trading_types::arbitrage::swap::{
+            constraints::SwapConstraint, exceptions::SwapArbitrageException, SwapTrade,
+        },
+    };
+
+    use super::test_swap_sizing::{level, swap_trade, usd, xrp};
+    use crate::common::_static::mixed_markets::{order_book, token, BITSTAMP};
+
+    pub(super) const ACCOUNT: &str = "rTradingAccount1111111111111111111";
+
+    /// Sells USD for XRP at 4.2 and buys USD back at 0.30.
+    pub(super) fn usd_swap_trade() -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![level(usd(0.0), xrp(), 10, Decimal::new(42, 1))],
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(30, 2))],
+            usd(0.0),
+        )
+        .unwrap()
+    }
+
+    pub(super) fn eur<'a>() -> Currency<'a> {
+        token("EUR", BITSTAMP)
+    }
+
+    /// Sells USD for EUR at 0.9 and buys USD back at 1.2.
+    pub(super) fn eur_swap_trade() -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![level(usd(0.0), eur(), 10, Decimal::new(9, 1))],
+            vec![level(eur(), usd(0.0), 100, Decimal::new(12, 1))],
+            usd(0.0),
+        )
+        .unwrap()
+    }
+
+    /// XRP/USD bidding 0.24 USD and asking 0.26 USD for `bid_quantity` XRP.
+    pub(super) fn order_books(bid_quantity: i64, usd_balance: i64) -> OrderBooks<'static> {
+        let mut order_books = OrderBooks::new(
+            vec![order_book(
+                xrp(),
+                usd(0.0),
+                &[(bid_quantity, 24)],
+                &[(100, 26)],
+            )]
+            .into(),
+            0.5,
+        );
+        let mut account = AccountState::new(Cow::Borrowed(ACCOUNT), Decimal::from(1_000));
+        account.add_trust_line(TrustLine {
+            currency: usd(0.0),
+            balance: Decimal::from(usd_balance),
+            limit: Decimal::from(1_000),
+        });
+        order_books.trading_account = Some(account);
+
+        order_books
+    }
+
+    #[test]
+    fn test_plan_without_funding_leg() {
+        let plan = order_books(100, 50)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        assert_eq!(plan.funding_leg, None);
+        assert_eq!(plan.estimate.funding_cost, None);
+        assert_eq!(plan.sizing.input_quantity, Decimal::from(10));
+        assert_eq!(plan.get_transaction_count(), 1);
+    }
+
+    #[test]
+    fn test_plan_with_funding_leg() {
+        let plan = order_books(100, 0)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let funding_leg = plan.funding_leg.as_ref().unwrap();
+        assert!(funding_leg.get_held_currency().is_xrp());
+        assert_eq!(funding_leg.output_quantity, Decimal::from(10));
+        assert_eq!(
+            funding_leg.input_quantity.round_dp(10),
+            (Decimal::from(10) / Decimal::new(24, 2)).round_dp(10)
+        );
+        // Buying 10 USD at 0.24 and selling them back at 0.26 loses 1 - 0.24 / 0.26.
+        let funding_cost =
+            Decimal::from(10) * (Decimal::ONE - Decimal::new(24, 2) / Decimal::new(26, 2));
+        assert_eq!(
+            plan.estimate.funding_cost.unwrap().round_dp(10),
+            funding_cost.round_dp(10)
+        );
+        // 10 USD buy 42 XRP which buy 12.6 USD. Both transactions pay 12 drops.
+        let network_fee = Decimal::new(24, 6) * Decimal::new(30, 2);
+        assert_eq!(plan.estimate.network_fee, Some(network_fee));
+        assert_eq!(
+            plan.estimate.profit.round_dp(10),
+            (Decimal::new(26, 1) - funding_cost - network_fee).round_dp(10)
+        );
+        // Each USD swapped earns 0.26 USD, which has to cover the funding cost as well.
+        assert_eq!(
+            plan.estimate.break_even_quantity.unwrap().round_dp(10),
+            ((funding_cost + network_fee) / Decimal::new(26, 2)).round_dp(10)
+        );
+        assert_eq!(plan.get_transaction_count(), 2);
+    }
+
+    #[test]
+    fn test_plan_capped_by_funding_depth() {
+        // The bid only takes 20 XRP for 4.8 USD.
+        let plan = order_books(20, 0)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        assert_eq!(plan.sizing.input_quantity, Decimal::new(48, 1));
+        assert_eq!(plan.sizing.limited_by, Some(SwapConstraint::FundingDepth));
+    }
+
+    #[test]
+    fn test_unprofitable_after_funding() {
+        // Selling 10 USD for 42 XRP and buying 10.5 USD back doesn't cover the funding cost.
+        let trade = SwapTrade::new(
+            vec![level(usd(0.0), xrp(), 10, Decimal::new(42, 1))],
+            vec![level(xrp(), usd(0.0), 100, Decimal::new(25, 2))],
+            usd(0.0),
+        )
+        .unwrap();
+        let order_books = order_books(100, 0);
+        assert!(order_books.get_swap_plan(&trade, 3, 12).unwrap().is_none());
+        assert!(order_books
+            .get_swap_plan(&swap_trade(0.0), 3, 12)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_plan_network_fee_without_xrp() {
+        let mut order_books = order_books(100, 50);
+        if let Some(account) = order_books.trading_account.as_mut() {
+            account.add_trust_line(TrustLine {
+                currency: eur(),
+                balance: Decimal::ZERO,
+                limit: Decimal::from(1_000),
+            });
+        }
+        let plan = order_books
+            .get_swap_plan(&eur_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        // 10 USD buy 9 EUR which buy 10.8 USD. The network fee is converted at the 0.24 USD bid.
+        let network_fee = Decimal::new(12, 6) * Decimal::new(24, 2);
+        assert_eq!(plan.estimate.network_fee, Some(network_fee));
+        assert_eq!(
+            plan.estimate.profit.round_dp(10),
+            (Decimal::new(8, 1) - network_fee).round_dp(10)
+        );
+
+        // Without an XRP order book the network fee can't be converted.
+        order_books.order_books.to_mut().clear();
+        order_books.reindex();
+        assert!(order_books
+            .get_swap_plan(&eur_swap_trade(), 3, 12)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_plan_without_trading_account() {
+        let mut order_books = order_books(100, 0);
+        order_books.trading_account = None;
+        let error = order_books
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SwapArbitrageException>(),
+            Some(SwapArbitrageException::NoTradingAccount)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "xrpl")]
+    fn test_plan_transactions() {
+        use trading_lib::trading_types::execution::{GetTransactions, TradeTransaction};
+        use xrpl::models::amount::{Amount, IssuedCurrencyAmount, XRPAmount};
+
+        let plan = order_books(100, 0)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let transactions = plan.get_transactions(Cow::Borrowed(ACCOUNT)).unwrap();
+        assert_eq!(transactions.len(), 2);
+        let TradeTransaction::Payment(funding_payment) = &transactions[0] else {
+            panic!("The funding leg is a payment.");
+        };
+        // 41.666666 XRP in drops for 10 USD.
+        assert_eq!(
+            funding_payment.send_max,
+            Some(Amount::XRPAmount(XRPAmount(Cow::Borrowed("41666666"))))
+        );
+        assert_eq!(
+            funding_payment.amount,
+            Amount::IssuedCurrencyAmount(IssuedCurrencyAmount::new(
+                usd(0.0).currency_code,
+                usd(0.0).issuer,
+                Cow::Borrowed("10")
+            ))
+        );
+        assert!(matches!(transactions[1], TradeTransaction::Payment(_)));
+
+        // Swaps starting with XRP are executed with one offer per leg.
+        let plan = order_books(100, 0)
+            .get_swap_plan(&swap_trade(0.0), 3, 12)
+            .unwrap()
+            .unwrap();
+        let transactions = plan.get_transactions(Cow::Borrowed(ACCOUNT)).unwrap();
+        assert_eq!(transactions.len(), 2);
+        let TradeTransaction::OfferCreate(sell_offer) = &transactions[0] else {
+            panic!("The sell leg is an offer.");
+        };
+        assert_eq!(
+            sell_offer.taker_gets,
+            Amount::XRPAmount(XRPAmount(Cow::Borrowed("200000000")))
+        );
+        assert!(matches!(transactions[1], TradeTransaction::OfferCreate(_)));
+    }
+}
+
+#[cfg(test)]
+mod test_reconciliation {
+    use std::io::BufReader;
+
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use trading_lib::{
+        models::{account::TrustLine, metadata::TransactionOutcome},
+        trading_types::journal::{TradeJournalReader, TradeJournalWriter},
+    };
+
+    use super::test_swap_plan::{eur, eur_swap_trade, order_books, usd_swap_trade, ACCOUNT};
+    use crate::common::_static::mixed_markets::{BITSTAMP, GATEHUB};
+
+    /// The swap paid at once: 10 USD sold for 41.9 XRP and those sold for 12.57 USD.
+    fn swap_payment() -> Value {
+        json!({
+            "hash": "A1",
+            "tx_json": {
+                "Account": ACCOUNT,
+                "TransactionType": "Payment",
+                "Fee": "12"
+            },
+            "meta": {
+                "TransactionResult": "tesSUCCESS",
+                "delivered_amount": {"currency": "USD", "issuer": GATEHUB, "value": "12.57"},
+                "AffectedNodes": [
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "AccountRoot",
+                        "FinalFields": {"Account": ACCOUNT, "Balance": "999999988"},
+                        "PreviousFields": {"Balance": "1000000000"}
+                    }},
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "RippleState",
+                        "FinalFields": {
+                            "Balance": {"currency": "USD", "issuer": "rrrrrrrrrrrrrrrrrrrrBZbvji", "value": "52.57"},
+                            "LowLimit": {"currency": "USD", "issuer": ACCOUNT, "value": "1000"},
+                            "HighLimit": {"currency": "USD", "issuer": GATEHUB, "value": "0"}
+                        },
+                        "PreviousFields": {
+                            "Balance": {"currency": "USD", "issuer": "rrrrrrrrrrrrrrrrrrrrBZbvji", "value": "50"}
+                        }
+                    }},
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "Offer",
+                        "FinalFields": {
+                            "Account": "rMakerA",
+                            "TakerGets": "58100000",
+                            "TakerPays": {"currency": "USD", "issuer": GATEHUB, "value": "5"}
+                        },
+                        "PreviousFields": {
+                            "TakerGets": "100000000",
+                            "TakerPays": {"currency": "USD", "issuer": GATEHUB, "value": "15"}
+                        }
+                    }},
+                    {"DeletedNode": {
+                        "LedgerEntryType": "Offer",
+                        "FinalFields": {
+                            "Account": "rMakerB",
+                            "TakerGets": {"currency": "USD", "issuer": GATEHUB, "value": "0"},
+                            "TakerPays": "0"
+                        },
+                        "PreviousFields": {
+                            "TakerGets": {"currency": "USD", "issuer": GATEHUB, "value": "12.57"},
+                            "TakerPays": "41900000"
+                        }
+                    }}
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_transaction_outcome() {
+        let outcome = TransactionOutcome::from_json(&swap_payment(), ACCOUNT).unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(outcome.hash, "A1");
+        assert_eq!(outcome.fee_drops, 12);
+        // The XRP balance only changed by the fee.
+        assert_eq!(outcome.balance_changes.len(), 1);
+        assert_eq!(outcome.balance_changes[0].currency.issuer, GATEHUB);
+        assert_eq!(outcome.balance_changes[0].quantity, Decimal::new(257, 2));
+        assert_eq!(
+            outcome.delivered_amount.unwrap().quantity,
+            Decimal::new(1257, 2)
+        );
+        assert_eq!(outcome.offer_fills.len(), 2);
+        assert_eq!(outcome.offer_fills[0].owner, "rMakerA");
+        assert_eq!(
+            outcome.offer_fills[0].taker_gets.quantity,
+            Decimal::new(419, 1)
+        );
+        assert_eq!(
+            outcome.offer_fills[0].taker_pays.quantity,
+            Decimal::from(10)
+        );
+
+        // The same balance seen from the issuer.
+        let outcome = TransactionOutcome::from_json(&swap_payment(), GATEHUB).unwrap();
+        assert_eq!(outcome.fee_drops, 0);
+        assert_eq!(outcome.balance_changes[0].currency.issuer, ACCOUNT);
+        assert_eq!(outcome.balance_changes[0].quantity, -Decimal::new(257, 2));
+
+        assert!(TransactionOutcome::from_json(&json!({"hash": "A1"}), ACCOUNT).is_err());
+    }
+
+    #[test]
+    fn test_reconcile_swap() {
+        let plan = order_books(100, 50)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let outcome = TransactionOutcome::from_json(&swap_payment(), ACCOUNT).unwrap();
+        let reconciliation = plan.reconcile(&[outcome]);
+        assert!(reconciliation.is_success);
+        assert_eq!(reconciliation.hashes, vec!["A1".to_string()]);
+        assert_eq!(reconciliation.funding_fill, None);
+        let [sell_fill, buy_fill] = &reconciliation.leg_fills;
+        assert_eq!(sell_fill.given_quantity, Decimal::from(10));
+        assert_eq!(sell_fill.received_quantity, Decimal::new(419, 1));
+        assert_eq!(sell_fill.predicted_received_quantity, Decimal::from(42));
+        // 4.19 instead of 4.2 XRP per USD.
+        assert_eq!(
+            sell_fill.get_rate_slippage_pct().unwrap().round_dp(10),
+            (Decimal::new(1, 2) / Decimal::new(42, 1) * Decimal::ONE_HUNDRED).round_dp(10)
+        );
+        assert_eq!(buy_fill.received_quantity, Decimal::new(1257, 2));
+        // The network fee is 12 drops converted at 0.30 USD per XRP in both.
+        let network_fee = Decimal::new(12, 6) * Decimal::new(30, 2);
+        assert_eq!(
+            reconciliation.realized_profit.round_dp(10),
+            (Decimal::new(257, 2) - network_fee).round_dp(10)
+        );
+        assert_eq!(
+            reconciliation.get_slippage().round_dp(10),
+            Decimal::new(3, 2)
+        );
+    }
+
+    /// The EUR swap paid at once with both legs auto-bridged through XRP: 10 USD sold for 40 XRP
+    /// and those for 8.9 EUR, which are sold for 42 XRP and those for 10.68 USD.
+    fn bridged_swap_payment() -> Value {
+        let offer = |maker: &str, taker_pays: [Value; 2], taker_gets: [Value; 2]| {
+            let [previous_taker_pays, final_taker_pays] = taker_pays;
+            let [previous_taker_gets, final_taker_gets] = taker_gets;
+            json!({"ModifiedNode": {
+                "LedgerEntryType": "Offer",
+                "FinalFields": {
+                    "Account": maker,
+                    "TakerGets": final_taker_gets,
+                    "TakerPays": final_taker_pays
+                },
+                "PreviousFields": {
+                    "TakerGets": previous_taker_gets,
+                    "TakerPays": previous_taker_pays
+                }
+            }})
+        };
+        let amount = |currency: &str, issuer: &str, value: &str| json!({"currency": currency, "issuer": issuer, "value": value});
+
+        json!({
+            "hash": "A2",
+            "tx_json": {
+                "Account": ACCOUNT,
+                "TransactionType": "Payment",
+                "Fee": "12"
+            },
+            "meta": {
+                "TransactionResult": "tesSUCCESS",
+                "delivered_amount": amount("USD", GATEHUB, "10.68"),
+                "AffectedNodes": [
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "AccountRoot",
+                        "FinalFields": {"Account": ACCOUNT, "Balance": "999999988"},
+                        "PreviousFields": {"Balance": "1000000000"}
+                    }},
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "RippleState",
+                        "FinalFields": {
+                            "Balance": amount("USD", "rrrrrrrrrrrrrrrrrrrrBZbvji", "50.68"),
+                            "LowLimit": amount("USD", ACCOUNT, "1000"),
+                            "HighLimit": amount("USD", GATEHUB, "0")
+                        },
+                        "PreviousFields": {
+                            "Balance": amount("USD", "rrrrrrrrrrrrrrrrrrrrBZbvji", "50")
+                        }
+                    }},
+                    offer(
+                        "rMakerA",
+                        [amount("USD", GATEHUB, "20"), amount("USD", GATEHUB, "10")],
+                        [json!("80000000"), json!("40000000")],
+                    ),
+                    offer(
+                        "rMakerB",
+                        [json!("50000000"), json!("10000000")],
+                        [amount("EUR", BITSTAMP, "11.125"), amount("EUR", BITSTAMP, "2.225")],
+                    ),
+                    offer(
+                        "rMakerC",
+                        [amount("EUR", BITSTAMP, "8.9"), amount("EUR", BITSTAMP, "0")],
+                        [json!("42000000"), json!("0")],
+                    ),
+                    offer(
+                        "rMakerD",
+                        [json!("100000000"), json!("58000000")],
+                        [amount("USD", GATEHUB, "25.43"), amount("USD", GATEHUB, "14.75")],
+                    )
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_reconcile_bridged_swap() {
+        let mut order_books = order_books(100, 50);
+        if let Some(account) = order_books.trading_account.as_mut() {
+            account.add_trust_line(TrustLine {
+                currency: eur(),
+                balance: Decimal::ZERO,
+                limit: Decimal::from(1_000),
+            });
+        }
+        let plan = order_books
+            .get_swap_plan(&eur_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let outcome = TransactionOutcome::from_json(&bridged_swap_payment(), ACCOUNT).unwrap();
+        assert_eq!(outcome.offer_fills.len(), 4);
+        let reconciliation = plan.reconcile(&[outcome]);
+        let [sell_fill, buy_fill] = &reconciliation.leg_fills;
+        assert_eq!(sell_fill.given_quantity, Decimal::from(10));
+        assert_eq!(sell_fill.received_quantity, Decimal::new(89, 1));
+        assert_eq!(sell_fill.predicted_received_quantity, Decimal::from(9));
+        assert_eq!(buy_fill.given_quantity, Decimal::new(89, 1));
+        assert_eq!(buy_fill.received_quantity, Decimal::new(1068, 2));
+        assert_eq!(
+            buy_fill.get_rate_slippage_pct().unwrap().round_dp(10),
+            Decimal::ZERO
+        );
+        // The network fee is 12 drops converted at the 0.24 USD bid of the XRP/USD order book.
+        let network_fee = Decimal::new(12, 6) * Decimal::new(24, 2);
+        assert_eq!(plan.estimate.network_fee, Some(network_fee));
+        assert_eq!(
+            reconciliation.realized_profit.round_dp(10),
+            (Decimal::new(68, 2) - network_fee).round_dp(10)
+        );
+    }
+
+    /// The funding payment delivering 10 USD for 43 XRP instead of the estimated 41.67 XRP.
+    fn funding_payment() -> Value {
+        json!({
+            "hash": "A0",
+            "tx_json": {
+                "Account": ACCOUNT,
+                "TransactionType": "Payment",
+                "Fee": "12"
+            },
+            "meta": {
+                "TransactionResult": "tesSUCCESS",
+                "delivered_amount": {"currency": "USD", "issuer": GATEHUB, "value": "10"},
+                "AffectedNodes": [
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "AccountRoot",
+                        "FinalFields": {"Account": ACCOUNT, "Balance": "956999988"},
+                        "PreviousFields": {"Balance": "1000000000"}
+                    }},
+                    {"ModifiedNode": {
+                        "LedgerEntryType": "RippleState",
+                        "FinalFields": {
+                            "Balance": {"currency": "USD", "issuer": "rrrrrrrrrrrrrrrrrrrrBZbvji", "value": "10"},
+                            "LowLimit": {"currency": "USD", "issuer": ACCOUNT, "value": "1000"},
+                            "HighLimit": {"currency": "USD", "issuer": GATEHUB, "value": "0"}
+                        },
+                        "PreviousFields": {
+                            "Balance": {"currency": "USD", "issuer": "rrrrrrrrrrrrrrrrrrrrBZbvji", "value": "0"}
+                        }
+                    }}
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_reconcile_funded_swap() {
+        let plan = order_books(100, 0)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let outcomes = [funding_payment(), swap_payment()]
+            .iter()
+            .map(|json| TransactionOutcome::from_json(json, ACCOUNT).unwrap())
+            .collect::<Vec<_>>();
+        let reconciliation = plan.reconcile(&outcomes);
+        let funding_fill = reconciliation.funding_fill.as_ref().unwrap();
+        assert_eq!(funding_fill.given_quantity, Decimal::from(43));
+        assert_eq!(funding_fill.received_quantity, Decimal::from(10));
+        // Unwinding the 10 USD at 0.26 after buying them at 10 / 43 loses 1 - 10 / 43 / 0.26,
+        // more than the estimated 1 - 0.24 / 0.26.
+        let funding_cost = Decimal::from(10)
+            * (Decimal::ONE - Decimal::from(10) / Decimal::from(43) / Decimal::new(26, 2));
+        assert_eq!(
+            reconciliation.funding_cost.unwrap().round_dp(10),
+            funding_cost.round_dp(10)
+        );
+        assert!(funding_cost > plan.estimate.funding_cost.unwrap());
+        let network_fee = Decimal::new(24, 6) * Decimal::new(30, 2);
+        assert_eq!(
+            reconciliation.realized_profit.round_dp(10),
+            (Decimal::new(257, 2) - network_fee - funding_cost).round_dp(10)
+        );
+    }
+
+    #[test]
+    fn test_trade_journal() {
+        let plan = order_books(100, 50)
+            .get_swap_plan(&usd_swap_trade(), 3, 12)
+            .unwrap()
+            .unwrap();
+        let outcome = TransactionOutcome::from_json(&swap_payment(), ACCOUNT).unwrap();
+        let entry = plan.reconcile(&[outcome]).to_journal_entry(780_000_000);
+        let mut writer = TradeJournalWriter::new(Vec::new());
+        writer.write_entry(&entry).unwrap();
+        writer.write_entry(&entry).unwrap();
+        let journal = writer.into_inner();
+        let entries = TradeJournalReader::new(BufReader::new(journal.as_slice()))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(entries, vec![entry.clone(), entry]);
+        assert_eq!(entries[0].trade_type, "swap");
+        assert_eq!(entries[0].input_quantity, Decimal::from(10));
+        assert_eq!(entries[0].output_quantity, Decimal::new(1257, 2));
+    }
+}
+
+#[cfg(test)]
+mod test_rippling_trades {
+    use rust_decimal::Decimal;
+    use trading_lib::order_books::OrderBooks;
+
+    use crate::common::_static::{
+        mixed_markets::{mixed_order_books, order_book, token, xrp, BITSTAMP, GATEHUB},
+        trust_lines::{rippling_network, MARKET_MAKER},
+    };
+
+    /// USD:Bitstamp/USD:GateHub bidding 1.02 for up to 50 USD:Bitstamp and both USD tokens
+    /// traded at 0.30 for XRP, which converts the network fee.
+    fn cross_issuer_order_books() -> OrderBooks<'static> {
+        OrderBooks::new(
+            vec![
+                order_book(
+                    token("USD", BITSTAMP),
+                    token("USD", GATEHUB),
+                    &[(50, 102)],
+                    &[(50, 104)],
+                ),
+                order_book(xrp(), token("USD", GATEHUB), &[(100, 30)], &[(100, 32)]),
+                order_book(xrp(), token("USD", BITSTAMP), &[(100, 30)], &[(100, 32)]),
+            ]
+            .into(),
+            0.05,
+        )
+    }
+
+    #[test]
+    fn test_rippling_leg() {
+        let order_books = cross_issuer_order_books();
+        let profitable_trades = order_books
+            .get_rippling_trades(&rippling_network(), 12)
+            .unwrap();
+        // Either token can start the round trip.
+        assert_eq!(profitable_trades.len(), 2);
+        let profitable_trade = profitable_trades
+            .iter()
+            .find(|profitable_trade| {
+                profitable_trade
+                    .starting_currency
+                    .is_same_asset(&token("USD", GATEHUB))
+            })
+            .unwrap();
+        // USD:GateHub ripples through the market maker into USD:Bitstamp at face value, which is
+        // sold back at 1.02.
+        assert_eq!(profitable_trade.sell_levels[0].rate, Decimal::ONE);
+        assert!(profitable_trade.sell_levels[0].offer_id.is_none());
+        assert_eq!(profitable_trade.routes[0][0].intermediary, MARKET_MAKER);
+        assert_eq!(profitable_trade.buy_levels[0].rate, Decimal::new(102, 2));
+        assert_eq!(profitable_trade.estimate.input_quantity, Decimal::from(50));
+        // 0.000012 XRP sold for USD:GateHub at 0.30.
+        assert_eq!(
+            profitable_trade.estimate.network_fee,
+            Some(Decimal::new(36, 7))
+        );
+        assert_eq!(
+            profitable_trade.estimate.profit,
+            Decimal::ONE - Decimal::new(36, 7)
+        );
+        // Without an XRP order book the network fee can't be converted.
+        let mut order_books_without_xrp = cross_issuer_order_books();
+        order_books_without_xrp.order_books.to_mut().truncate(1);
+        assert!(order_books_without_xrp
+            .get_rippling_trades(&rippling_network(), 12)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            order_books
+                .get_rippling_opportunities(&rippling_network(), 1)
+                .unwrap()[0]
+                .route
+                .intermediary,
+            MARKET_MAKER
+        );
+    }
+
+    #[test]
+    fn test_no_rippling_trades() {
+        // Rippling back and forth at face value doesn't pay.
+        let order_books = mixed_order_books();
+        assert!(order_books
+            .get_rippling_trades(&rippling_network(), 12)
+            .unwrap()
+            .is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_parity {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        order_books::OrderBooks,
+        trading_types::arbitrage::swap::{
+            constraints::SwapConstraint,
+            parity::{ParityStep, ParityStrategy},
+            GetProfitableTrades, SwapTrade,
+        },
+    };
+
+    use crate::common::_static::mixed_markets::{mixed_order_books, BITSTAMP, GATEHUB, SNAPSWAP};
+
+    /// XRP -> USD:GateHub and USD:Bitstamp -> XRP: 126.087 XRP in, 30 USD through and 142 XRP
+    /// out.
+    fn gatehub_trade<'a>(order_books: &'a OrderBooks<'static>) -> SwapTrade<'a> {
+        order_books
+            .get_profitable_trades(0)
+            .unwrap()
+            .into_iter()
+            .map(|profitable_trade| profitable_trade.trade)
+            .find(|trade| {
+                trade.starting_currency.is_xrp() && trade.sell_order.counter.issuer == GATEHUB
+            })
+            .unwrap()
+    }
+
+    fn strategy(haircuts: &[(&'static str, i64)]) -> ParityStrategy<'static> {
+        ParityStrategy {
+            haircuts: haircuts
+                .iter()
+                .map(|&(issuer, haircut_pct)| (Cow::Borrowed(issuer), Decimal::new(haircut_pct, 2)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_haircut_cost() {
+        let order_books = mixed_order_books();
+        let trade = gatehub_trade(&order_books);
+        assert_eq!(trade.get_parity_step(), Some(ParityStep::Intermediate));
+        let parity_trade = strategy(&[(GATEHUB, 5)]).evaluate(&trade, None, 0).unwrap();
+        assert_eq!(parity_trade.received.issuer, GATEHUB);
+        assert_eq!(parity_trade.given.issuer, BITSTAMP);
+        assert_eq!(parity_trade.exposure, Decimal::from(30));
+        // 5 % of the 142 XRP the received USD:GateHub is worth.
+        assert_eq!(parity_trade.haircut_cost, Decimal::new(71, 1));
+        assert_eq!(
+            parity_trade.net_edge,
+            parity_trade.estimate.profit - Decimal::new(71, 1)
+        );
+        // Giving away the riskier token adds to the edge.
+        let parity_trade = strategy(&[(BITSTAMP, 5)])
+            .evaluate(&trade, None, 0)
+            .unwrap();
+        assert!(parity_trade.net_edge > parity_trade.estimate.profit);
+    }
+
+    #[test]
+    fn test_exposure_limit() {
+        let mut strategy = strategy(&[]);
+        strategy
+            .exposure_limits
+            .insert(Cow::Borrowed(GATEHUB), Decimal::from(10));
+        let order_books = mixed_order_books();
+        let parity_trade = strategy
+            .evaluate(&gatehub_trade(&order_books), None, 0)
+            .unwrap();
+        assert_eq!(parity_trade.exposure, Decimal::from(10));
+        assert_eq!(parity_trade.sizing.output_quantity, Decimal::from(48));
+        assert_eq!(
+            parity_trade.sizing.limited_by,
+            Some(SwapConstraint::ExposureLimit)
+        );
+    }
+
+    #[test]
+    fn test_get_parity_trades() {
+        let order_books = mixed_order_books();
+        let parity_trades = strategy(&[]).get_parity_trades(&order_books, 12).unwrap();
+        // Without haircuts the net edge is the profit.
+        assert_eq!(
+            parity_trades.len(),
+            order_books
+                .get_profitable_trades(12)
+                .unwrap()
+                .iter()
+                .filter(|profitable_trade| profitable_trade.trade.get_parity_step().is_some())
+                .count()
+        );
+        assert!(parity_trades
+            .iter()
+            .all(|parity_trade| parity_trade.net_edge == parity_trade.estimate.profit));
+        assert!(parity_trades
+            .windows(2)
+            .all(|parity_trades| { parity_trades[0].net_edge >= parity_trades[1].net_edge }));
+        // A 20 % haircut eats the edge of taking in USD:GateHub.
+        let parity_trades = strategy(&[(GATEHUB, 20)])
+            .get_parity_trades(&order_books, 12)
+            .unwrap();
+        assert!(parity_trades
+            .iter()
+            .all(|parity_trade| parity_trade.received.issuer != GATEHUB));
+        assert!(parity_trades
+            .iter()
+            .any(|parity_trade| parity_trade.received.issuer == SNAPSWAP));
+    }
+}
+
+#[cfg(test)]
+mod test_trade_filters {
+    use rust_decimal::Decimal;
+    use trading_lib::trading_types::arbitrage::{
+        filters::{RejectionReason, TradeFilter},
+        swap::{constraints::SwapConstraint, GetProfitableTrades},
+    };
+
+    use crate::common::_static::mixed_markets::{mixed_order_books, token, xrp, GATEHUB, SNAPSWAP};
+
+    #[test]
+    fn test_no_filters() {
+        let order_books = mixed_order_books();
+        let filtered_trades = order_books.get_filtered_trades(12).unwrap();
+        assert!(!filtered_trades.accepted.is_empty());
+        assert!(filtered_trades.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_min_profit_per_starting_currency() {
+        let mut order_books = mixed_order_books();
+        order_books.trade_filters.set_filter(
+            &xrp(),
+            TradeFilter {
+                min_profit: Some(Decimal::from(1000)),
+                ..Default::default()
+            },
+        );
+        let filtered_trades = order_books.get_filtered_trades(12).unwrap();
+        // Only trades starting with XRP are rejected.
+        assert!(filtered_trades
+            .accepted
+            .iter()
+            .all(|profitable_trade| !profitable_trade.trade.starting_currency.is_xrp()));
+        assert!(!filtered_trades.rejected.is_empty());
+        for rejected_trade in filtered_trades.rejected.iter() {
+            assert!(rejected_trade
+                .profitable_trade
+                .trade
+                .starting_currency
+                .is_xrp());
+            assert_eq!(
+                rejected_trade.reasons,
+                vec![RejectionReason::ProfitTooLow {
+                    profit: rejected_trade.profitable_trade.estimate.profit,
+                    min_profit: Decimal::from(1000),
+                }]
+            );
+        }
+        assert_eq!(
+            order_books.get_profitable_trades(12).unwrap(),
+            filtered_trades.accepted
+        );
+    }
+
+    #[test]
+    fn test_blacklisted_pair_and_max_legs() {
+        let mut order_books = mixed_order_books();
+        let snapswap = token("USD", SNAPSWAP);
+        order_books
+            .trade_filters
+            .default_filter
+            .blacklist_pair(&snapswap, &xrp());
+        let filtered_trades = order_books.get_filtered_trades(12).unwrap();
+        // Starting with XRP or with USD:Bitstamp.
+        assert_eq!(filtered_trades.rejected.len(), 2);
+        assert!(filtered_trades.rejected.iter().all(|rejected_trade| {
+            rejected_trade.reasons
+                == vec![RejectionReason::BlacklistedPair {
+                    base: xrp().get_label(),
+                    counter: snapswap.get_label(),
+                }]
+        }));
+        // Swaps have two legs.
+        order_books.trade_filters.default_filter.max_legs = Some(1);
+        let filtered_trades = order_books.get_filtered_trades(12).unwrap();
+        assert!(filtered_trades.accepted.is_empty());
+        assert!(filtered_trades.rejected.iter().all(|rejected_trade| {
+            rejected_trade
+                .reasons
+                .contains(&RejectionReason::TooManyLegs {
+                    leg_count: 2,
+                    max_legs: 1,
+                })
+        }));
+    }
+
+    #[test]
+    fn test_trade_size() {
+        let mut order_books = mixed_order_books();
+        order_books.trade_filters.default_filter = TradeFilter {
+            min_input_quantity: Some(Decimal::from(30)),
+            max_input_quantity: Some(Decimal::from(50)),
+            ..Default::default()
+        };
+        let filtered_trades = order_books.get_filtered_trades(12).unwrap();
+        let gatehub_trade = filtered_trades
+            .accepted
+            .iter()
+            .find(|profitable_trade| {
+                let trade = &profitable_trade.trade;
+                trade.starting_currency.is_xrp() && trade.sell_order.counter.issuer == GATEHUB
+            })
+            .unwrap();
+        // Capped at 50 of the 126.087 XRP the trade takes at its optimal size.
+        assert_eq!(
+            gatehub_trade.estimate.input_quantity.round_dp(20),
+            Decimal::from(50)
+        );
+        assert_eq!(
+            gatehub_trade.limited_by,
+            Some(SwapConstraint::MaxInputQuantity)
+        );
+        assert!(filtered_trades.accepted.iter().all(|profitable_trade| {
+            profitable_trade.estimate.input_quantity.round_dp(20) <= Decimal::from(50)
+        }));
+        assert!(filtered_trades.rejected.iter().all(|rejected_trade| {
+            matches!(
+                rejected_trade.reasons[..],
+                [RejectionReason::InputTooSmall { .. }]
+            )
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test_triangular {
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        order_books::OrderBooks,
+        trading_types::arbitrage::{
+            cycle::exceptions::CycleArbitrageException, filters::TradeFilter,
+            profit::GetProfitEstimate, triangular::TriangularTrade,
+        },
+    };
+
+    use super::test_swap_sizing::{level, usd, xrp};
+    use crate::common::_static::mixed_markets::{order_book, token, BITSTAMP, GATEHUB};
+
+    /// The example of the README: 100 XRP buy 50 USD:GateHub, which buy 55 EUR:Bitstamp, which
+    /// buy 101.75 XRP. The opposite direction loses.
+    pub(super) fn readme_order_books(usd_transfer_fee: f32) -> OrderBooks<'static> {
+        let mut usd = token("USD", GATEHUB);
+        usd.transfer_fee = usd_transfer_fee;
+        OrderBooks::new(
+            vec![
+                order_book(xrp(), usd.clone(), &[(100, 50)], &[(100, 52)]),
+                order_book(usd, token("EUR", BITSTAMP), &[(50, 110)], &[(50, 112)]),
+                order_book(token("EUR", BITSTAMP), xrp(), &[(60, 185)], &[(60, 190)]),
+            ]
+            .into(),
+            0.05,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let eur = token("EUR", BITSTAMP);
+        let get_error = |legs| {
+            TriangularTrade::new(legs)
+                .unwrap_err()
+                .downcast::<CycleArbitrageException>()
+                .unwrap()
+        };
+        assert!(matches!(
+            get_error(vec![
+                vec![level(xrp(), usd(0.0), 10, Decimal::ONE)],
+                vec![],
+                vec![level(eur.clone(), xrp(), 10, Decimal::ONE)],
+            ]),
+            CycleArbitrageException::EmptyLeg
+        ));
+        // The last leg doesn't receive the starting currency.
+        assert!(matches!(
+            get_error(vec![
+                vec![level(xrp(), usd(0.0), 10, Decimal::ONE)],
+                vec![level(usd(0.0), eur.clone(), 10, Decimal::ONE)],
+                vec![level(eur, usd(0.0), 10, Decimal::ONE)],
+            ]),
+            CycleArbitrageException::DisconnectedLegs
+        ));
+    }
+
+    #[test]
+    fn test_get_triangular_trades() {
+        let order_books = readme_order_books(0.0);
+        let profitable_trades = order_books.get_triangular_trades(36).unwrap();
+        // The rotations are one trade, starting with XRP as it is traded first.
+        assert_eq!(profitable_trades.len(), 1);
+        let xrp_trade = &profitable_trades[0];
+        assert!(xrp_trade.trade.starting_currency.is_xrp());
+        let legs = xrp_trade.trade.get_legs();
+        assert!(legs[0].1.is_same_asset(&token("USD", GATEHUB)));
+        assert!(legs[1].1.is_same_asset(&token("EUR", BITSTAMP)));
+        let estimate = &xrp_trade.estimate;
+        assert_eq!(estimate.input_quantity, Decimal::from(100));
+        assert_eq!(
+            xrp_trade.sizing.quantities[1..3],
+            [Decimal::from(50), Decimal::from(55)]
+        );
+        assert_eq!(estimate.output_quantity, Decimal::new(10175, 2));
+        assert_eq!(estimate.network_fee, Some(Decimal::new(36, 6)));
+        assert_eq!(estimate.profit, Decimal::new(1749964, 6));
+        assert_eq!(estimate.transfer_fees.len(), 3);
+        assert_eq!(xrp_trade.trade.get_profit_estimate(36), *estimate);
+    }
+
+    #[test]
+    fn test_transfer_fees_and_filters() {
+        let mut order_books = readme_order_books(0.0);
+        order_books.trade_filters.set_filter(
+            &xrp(),
+            TradeFilter {
+                max_input_quantity: Some(Decimal::from(40)),
+                ..Default::default()
+            },
+        );
+        let profitable_trades = order_books.get_triangular_trades(36).unwrap();
+        let xrp_trade = profitable_trades
+            .iter()
+            .find(|profitable_trade| profitable_trade.trade.starting_currency.is_xrp())
+            .unwrap();
+        assert_eq!(xrp_trade.estimate.input_quantity, Decimal::from(40));
+        assert!(xrp_trade.sizing.is_capped);
+
+        // A 2 % transfer fee on USD:GateHub eats the 1.75 % edge.
+        let order_books = readme_order_books(0.02);
+        assert!(order_books.get_triangular_trades(36).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_network_fee_without_xrp() {
+        // The example of the README with BTC:GateHub instead of XRP.
+        let btc = token("BTC", GATEHUB);
+        let cycle_order_books = vec![
+            order_book(btc.clone(), usd(0.0), &[(100, 50)], &[(100, 52)]),
+            order_book(usd(0.0), token("EUR", BITSTAMP), &[(50, 110)], &[(50, 112)]),
+            order_book(
+                token("EUR", BITSTAMP),
+                btc.clone(),
+                &[(60, 185)],
+                &[(60, 190)],
+            ),
+        ];
+        // The network fee can't be converted, so the cycle isn't profitable.
+        let order_books = OrderBooks::new(cycle_order_books.clone().into(), 0.05);
+        assert!(order_books.get_triangular_trades(36).unwrap().is_empty());
+
+        let mut with_xrp_order_book = cycle_order_books;
+        with_xrp_order_book.push(order_book(xrp(), btc.clone(), &[(100, 50)], &[(100, 52)]));
+        let order_books = OrderBooks::new(with_xrp_order_book.into(), 0.05);
+        let profitable_trades = order_books.get_triangular_trades(36).unwrap();
+        assert_eq!(profitable_trades.len(), 1);
+        let btc_trade = &profitable_trades[0];
+        assert!(btc_trade.trade.starting_currency.is_same_asset(&btc));
+        // 0.000036 XRP sold for BTC:GateHub at 0.5.
+        assert_eq!(btc_trade.estimate.network_fee, Some(Decimal::new(18, 6)));
+        assert_eq!(btc_trade.estimate.profit, Decimal::new(1749982, 6));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "xrpl")]
+mod test_triangular_payment {
+    use std::borrow::Cow;
+
+    use trading_lib::trading_types::{
+        arbitrage::cycle::{exceptions::CycleArbitrageException, sizing::CycleSizing},
+        execution::{GetTransactions, TradeTransaction},
+    };
+
+    use super::test_triangular::readme_order_books;
+    use crate::common::_static::mixed_markets::{token, GATEHUB};
+
+    const ACCOUNT: &str = "rTradingAccount1111111111111111111";
+
+    #[test]
+    fn test_transactions() {
+        let order_books = readme_order_books(0.0);
+        let mut profitable_trades = order_books.get_triangular_trades(36).unwrap();
+        // The rotation starting with USD:GateHub is paid at once.
+        profitable_trades.extend(
+            order_books
+                .get_cycle_trades(3, Some(&token("USD", GATEHUB)), 36)
+                .unwrap(),
+        );
+        assert_eq!(profitable_trades.len(), 2);
+        for profitable_trade in profitable_trades.iter() {
+            let transactions = profitable_trade
+                .get_transactions(Cow::Borrowed(ACCOUNT))
+                .unwrap();
+            if profitable_trade.trade.starting_currency.is_xrp() {
+                // XRP to XRP payments can't have paths.
+                assert_eq!(transactions.len(), 3);
+                assert!(transactions
+                    .iter()
+                    .all(|transaction| matches!(transaction, TradeTransaction::OfferCreate(_))));
+            } else {
+                assert_eq!(transactions.len(), 1);
+                let TradeTransaction::Payment(payment) = &transactions[0] else {
+                    panic!("expected a payment");
+                };
+                assert_eq!(payment.destination, ACCOUNT);
+                assert_eq!(payment.paths.as_ref().unwrap()[0].len(), 3);
+            }
+        }
+        let error = profitable_trades[0]
+            .trade
+            .get_sized_transactions(Cow::Borrowed(ACCOUNT), &CycleSizing::default())
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<CycleArbitrageException>(),
+            Some(CycleArbitrageException::EmptyTradeSize)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_cycle {
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        order_books::OrderBooks,
+        trading_types::arbitrage::{
+            cycle::{exceptions::CycleArbitrageException, CycleTrade},
+            profit::GetProfitEstimate,
+        },
+    };
+
+    use super::{
+        test_swap_sizing::{level, usd, xrp},
+        test_triangular::readme_order_books,
+    };
+    use crate::common::_static::mixed_markets::{order_book, token, BITSTAMP, GATEHUB};
+
+    /// The example of the README with a detour from USD:GateHub to USD:Bitstamp at par, so the
+    /// cycle takes four legs and there is no triangle.
+    fn four_leg_order_books() -> OrderBooks<'static> {
+        OrderBooks::new(
+            vec![
+                order_book(xrp(), token("USD", GATEHUB), &[(100, 50)], &[(100, 52)]),
+                order_book(
+                    token("USD", GATEHUB),
+                    token("USD", BITSTAMP),
+                    &[(50, 100)],
+                    &[(50, 101)],
+                ),
+                order_book(
+                    token("USD", BITSTAMP),
+                    token("EUR", BITSTAMP),
+                    &[(50, 110)],
+                    &[(50, 112)],
+                ),
+                order_book(token("EUR", BITSTAMP), xrp(), &[(60, 185)], &[(60, 190)]),
+            ]
+            .into(),
+            0.05,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let get_error = |legs| {
+            CycleTrade::new(legs)
+                .unwrap_err()
+                .downcast::<CycleArbitrageException>()
+                .unwrap()
+        };
+        assert!(matches!(
+            get_error(vec![vec![level(xrp(), usd(0.0), 10, Decimal::ONE)]]),
+            CycleArbitrageException::TooFewLegs
+        ));
+        assert!(matches!(
+            get_error(vec![vec![level(xrp(), usd(0.0), 10, Decimal::ONE)], vec![]]),
+            CycleArbitrageException::EmptyLeg
+        ));
+        assert!(matches!(
+            get_error(vec![
+                vec![level(xrp(), usd(0.0), 10, Decimal::ONE)],
+                vec![level(usd(0.0), token("EUR", BITSTAMP), 10, Decimal::ONE)],
+            ]),
+            CycleArbitrageException::DisconnectedLegs
+        ));
+    }
+
+    #[test]
+    fn test_matches_triangular_trade() {
+        let order_books = readme_order_books(0.0);
+        let profitable_trades = order_books.get_cycle_trades(4, None, 36).unwrap();
+        // The rotations of the triangle are one cycle, starting with the first currency.
+        assert_eq!(profitable_trades.len(), 1);
+        let cycle_trade = &profitable_trades[0];
+        assert!(cycle_trade.trade.starting_currency.is_xrp());
+        let triangular_trades = order_books.get_triangular_trades(36).unwrap();
+        let xrp_trade = triangular_trades
+            .iter()
+            .find(|profitable_trade| profitable_trade.trade.starting_currency.is_xrp())
+            .unwrap();
+        assert_eq!(cycle_trade.estimate, xrp_trade.estimate);
+        assert_eq!(
+            cycle_trade.sizing.quantities,
+            vec![
+                Decimal::from(100),
+                Decimal::from(50),
+                Decimal::from(55),
+                Decimal::new(10175, 2)
+            ]
+        );
+
+        let usd = token("USD", GATEHUB);
+        let profitable_trades = order_books.get_cycle_trades(4, Some(&usd), 36).unwrap();
+        assert_eq!(profitable_trades.len(), 1);
+        assert!(profitable_trades[0]
+            .trade
+            .starting_currency
+            .is_same_asset(&usd));
+        assert!(order_books
+            .get_cycle_trades(2, None, 36)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_four_legs() {
+        let order_books = four_leg_order_books();
+        assert!(order_books.get_triangular_trades(36).unwrap().is_empty());
+        assert!(order_books
+            .get_cycle_trades(3, None, 36)
+            .unwrap()
+            .is_empty());
+        let profitable_trades = order_books.get_cycle_trades(4, Some(&xrp()), 36).unwrap();
+        assert_eq!(profitable_trades.len(), 1);
+        let profitable_trade = &profitable_trades[0];
+        assert_eq!(profitable_trade.trade.legs.len(), 4);
+        assert_eq!(
+            profitable_trade.estimate.output_quantity,
+            Decimal::new(10175, 2)
+        );
+        assert_eq!(profitable_trade.estimate.profit, Decimal::new(1749964, 6));
+        assert_eq!(
+            profitable_trade.trade.get_profit_estimate(36),
+            profitable_trade.estimate
+        );
+
+        // The four legs are too many for a filter allowing three.
+        let mut order_books = four_leg_order_books();
+        order_books.trade_filters.default_filter.max_legs = Some(3);
+        assert!(order_books
+            .get_cycle_trades(4, None, 36)
+            .unwrap()
+            .is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_opportunities {
+    use std::collections::BTreeSet;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        order_books::{registry::OrderBookKey, OrderBooks},
+        trading_types::arbitrage::{
+            opportunities::{Candidate, Opportunity, OpportunityIndex},
+            swap::GetProfitableTrades,
+        },
+    };
+
+    use crate::common::_static::mixed_markets::{
+        mixed_order_books, mixed_order_books_list, order_book, token, xrp, ANODE, BITSTAMP, GATEHUB,
+    };
+
+    fn assert_matches_rescan(index: &OpportunityIndex<'static>, order_books: &OrderBooks<'static>) {
+        let rescanned = OpportunityIndex::new(
+            order_books,
+            index.max_hops,
+            index.anchor.clone(),
+            index.network_fee_drops,
+        )
+        .unwrap();
+        assert_eq!(index.get_opportunities(), rescanned.get_opportunities());
+    }
+
+    #[test]
+    fn test_full_scan() {
+        let order_books = mixed_order_books();
+        let index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        let opportunities = index.get_opportunities();
+        assert!(opportunities.windows(2).all(|opportunities| {
+            opportunities[0].get_estimate().profit >= opportunities[1].get_estimate().profit
+        }));
+        let swap_trades: Vec<_> = opportunities
+            .iter()
+            .filter_map(|opportunity| match opportunity {
+                Opportunity::Swap(profitable_trade) => Some(profitable_trade.as_ref().clone()),
+                Opportunity::Cycle(_) => None,
+            })
+            .collect();
+        let profitable_trades = order_books.get_profitable_trades(12).unwrap();
+        assert!(!profitable_trades.is_empty());
+        assert_eq!(swap_trades.len(), profitable_trades.len());
+        assert!(profitable_trades
+            .iter()
+            .all(|profitable_trade| swap_trades.contains(profitable_trade)));
+        // Every cycle of the negative-cycle search is a candidate.
+        let cycle_trades = order_books.get_cycle_trades(3, None, 12).unwrap();
+        assert!(!cycle_trades.is_empty());
+        assert!(cycle_trades.iter().all(|cycle_trade| {
+            opportunities.iter().any(|opportunity| {
+                matches!(opportunity, Opportunity::Cycle(profitable_trade) if profitable_trade.as_ref() == cycle_trade)
+            })
+        }));
+    }
+
+    #[test]
+    fn test_get_candidates() {
+        let order_books = mixed_order_books();
+        let index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        let list = mixed_order_books_list();
+        // XRP/EUR:Anode is the only XRP/EUR market, so it is only part of cycles and converts
+        // the network fee of swap pairs not trading XRP.
+        let key = OrderBookKey::from(&list[4]);
+        let candidates = index.get_candidates(&key);
+        assert!(!candidates.is_empty());
+        assert!(candidates.iter().all(|candidate| match candidate {
+            Candidate::SwapPair(pair) => !pair.contains(&key),
+            Candidate::Cycle(_) => true,
+        }));
+        // XRP/USD:GateHub is swapped with USD:Bitstamp/XRP and XRP/USD:SnapSwap.
+        let key = OrderBookKey::from(&list[0]);
+        let swap_pairs = index
+            .get_candidates(&key)
+            .into_iter()
+            .filter(
+                |candidate| matches!(candidate, Candidate::SwapPair(pair) if pair.contains(&key)),
+            )
+            .count();
+        assert_eq!(swap_pairs, 2);
+        // BTC:GateHub/ETH:GateHub has no partner, its bids and asks only form a crossed-book
+        // cycle.
+        let candidates = index.get_candidates(&OrderBookKey::from(&list[6]));
+        assert_eq!(candidates.len(), 1);
+        assert!(matches!(&candidates[0], Candidate::Cycle(cycle) if cycle.len() == 2));
+    }
+
+    #[test]
+    fn test_network_fee_order_book_update() {
+        // The example of the README with BTC:GateHub instead of XRP, so the network fee is
+        // converted with XRP/BTC:GateHub.
+        let btc = token("BTC", GATEHUB);
+        let mut order_books = OrderBooks::new(
+            vec![
+                order_book(
+                    btc.clone(),
+                    token("USD", GATEHUB),
+                    &[(100, 50)],
+                    &[(100, 52)],
+                ),
+                order_book(
+                    token("USD", GATEHUB),
+                    token("EUR", BITSTAMP),
+                    &[(50, 110)],
+                    &[(50, 112)],
+                ),
+                order_book(
+                    token("EUR", BITSTAMP),
+                    btc.clone(),
+                    &[(60, 185)],
+                    &[(60, 190)],
+                ),
+                order_book(xrp(), btc.clone(), &[(100, 50)], &[(100, 52)]),
+            ]
+            .into(),
+            0.05,
+        );
+        let mut index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        let get_network_fees = |index: &OpportunityIndex<'_>| {
+            index
+                .get_opportunities()
+                .iter()
+                .map(|opportunity| opportunity.get_estimate().network_fee)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(get_network_fees(&index), vec![Some(Decimal::new(6, 6))]);
+        index
+            .update_order_book(
+                &mut order_books,
+                order_book(xrp(), btc, &[(100, 40)], &[(100, 52)]),
+            )
+            .unwrap();
+        assert_matches_rescan(&index, &order_books);
+        assert_eq!(get_network_fees(&index), vec![Some(Decimal::new(48, 7))]);
+    }
+
+    #[test]
+    fn test_staleness() {
+        let mut order_books = mixed_order_books();
+        order_books.max_age = Some(2);
+        for order_book in mixed_order_books_list() {
+            order_books.stamp_order_book(&order_book.base, &order_book.counter, 10, 0);
+        }
+        let mut index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        assert!(!index.get_opportunities().is_empty());
+        let receiver = index.subscribe();
+
+        // Advancing the current ledger turns all other order books stale, although they didn't
+        // change.
+        let list = mixed_order_books_list();
+        index
+            .update_order_book_at_ledger(&mut order_books, list[0].clone(), 13, 0)
+            .unwrap();
+        assert!(order_books.is_stale(&list[1]));
+        assert_matches_rescan(&index, &order_books);
+        assert!(index.get_opportunities().is_empty());
+        assert_eq!(receiver.try_recv().unwrap(), index.get_opportunities());
+
+        // Fresh again once stamped with the current ledger.
+        for order_book in list.iter() {
+            order_books.stamp_order_book(&order_book.base, &order_book.counter, 13, 0);
+        }
+        index.update_staleness(&order_books).unwrap();
+        assert_matches_rescan(&index, &order_books);
+        assert!(!index.get_opportunities().is_empty());
+        assert_eq!(receiver.try_recv().unwrap(), index.get_opportunities());
+        // Nothing changed since.
+        index.update_staleness(&order_books).unwrap();
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_max_cycles() {
+        let order_books = mixed_order_books();
+        let get_cycles = |index: &OpportunityIndex<'_>| {
+            mixed_order_books_list()
+                .iter()
+                .flat_map(|order_book| index.get_candidates(&OrderBookKey::from(order_book)))
+                .filter(|candidate| matches!(candidate, Candidate::Cycle(_)))
+                .collect::<BTreeSet<_>>()
+        };
+        let mut index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        assert!(get_cycles(&index).len() > 2);
+        index.max_cycles = 2;
+        index.rescan(&order_books).unwrap();
+        assert_eq!(get_cycles(&index).len(), 2);
+    }
+
+    #[test]
+    fn test_update_matches_rescan() {
+        let mut order_books = mixed_order_books();
+        let mut index = OpportunityIndex::new(&order_books, 3, None, 12).unwrap();
+        let receiver = index.subscribe();
+        let updates = [
+            order_book(xrp(), token("EUR", ANODE), &[(100, 30)], &[(100, 32)]),
+            order_book(token("USD", BITSTAMP), xrp(), &[(10, 400)], &[(20, 500)]),
+            // A new market.
+            order_book(
+                token("USD", GATEHUB),
+                token("USD", BITSTAMP),
+                &[(50, 99)],
+                &[(50, 101)],
+            ),
+            // An existing market in the other orientation.
+            order_book(
+                token("ETH", GATEHUB),
+                token("BTC", GATEHUB),
+                &[(10, 6)],
+                &[(10, 7)],
+            ),
+        ];
+        for update in updates.iter() {
+            let events = index
+                .update_order_book(&mut order_books, update.clone())
+                .unwrap();
+            assert!(!events.is_empty());
+            assert_matches_rescan(&index, &order_books);
+            assert_eq!(receiver.try_recv().unwrap(), index.get_opportunities());
+        }
+        assert!(index
+            .get_candidates(&OrderBookKey::new(
+                &token("USD", GATEHUB),
+                &token("USD", BITSTAMP)
+            ))
+            .iter()
+            .all(|candidate| matches!(candidate, Candidate::Cycle(_))));
+
+        // An unchanged order book publishes nothing.
+        index
+            .update_order_book(&mut order_books, updates[3].clone())
+            .unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        // An anchored index only keeps the cycles starting with the anchor.
+        let mut index = OpportunityIndex::new(&order_books, 3, Some(xrp()), 12).unwrap();
+        index
+            .update_order_book(&mut order_books, mixed_order_books_list()[4].clone())
+            .unwrap();
+        assert_matches_rescan(&index, &order_books);
+        assert!(index
+            .get_opportunities()
+            .iter()
+            .all(|opportunity| match opportunity {
+                Opportunity::Cycle(profitable_trade) =>
+                    profitable_trade.trade.starting_currency.is_xrp(),
+                Opportunity::Swap(_) => true,
+            }));
+    }
+}
@@ -0,0 +1,208 @@
+#[cfg(test)]
+#[cfg(feature = "xrpl")]
+mod test_execution_plan {
+    use std::borrow::Cow;
+
+    use rust_decimal::Decimal;
+    use trading_lib::{
+        models::currency::Currency,
+        order_books::order::Order,
+        trading_types::{
+            arbitrage::swap::SwapTrade,
+            execution::{
+                exceptions::ExecutionException, ExecutionPlan, ExecutionSettings, SequenceSlot,
+                Sequencing, TradeTransaction,
+            },
+        },
+    };
+
+    use crate::common::_static::mixed_markets::{token, xrp, GATEHUB};
+
+    const ACCOUNT: &str = "rTradingAccount1111111111111111111";
+
+    fn level(
+        base: Currency<'static>,
+        counter: Currency<'static>,
+        base_quantity: i64,
+        rate: Decimal,
+    ) -> Order<'static> {
+        Order {
+            base,
+            counter,
+            base_quantity: Decimal::from(base_quantity),
+            rate,
+            offer_id: None,
+        }
+    }
+
+    /// Sells 100 XRP for USD at 0.30 and buys XRP back at 4.2, one offer per leg.
+    fn xrp_swap_trade() -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![level(
+                xrp(),
+                token("USD", GATEHUB),
+                100,
+                Decimal::new(30, 2),
+            )],
+            vec![level(
+                token("USD", GATEHUB),
+                xrp(),
+                100,
+                Decimal::new(42, 1),
+            )],
+            xrp(),
+        )
+        .unwrap()
+    }
+
+    /// Sells 10 USD for XRP at 4.2 and buys USD back at 0.25, paid at once.
+    fn usd_swap_trade() -> SwapTrade<'static> {
+        SwapTrade::new(
+            vec![level(token("USD", GATEHUB), xrp(), 10, Decimal::new(42, 1))],
+            vec![level(
+                xrp(),
+                token("USD", GATEHUB),
+                100,
+                Decimal::new(25, 2),
+            )],
+            token("USD", GATEHUB),
+        )
+        .unwrap()
+    }
+
+    fn settings(sequencing: Sequencing) -> ExecutionSettings<'static> {
+        ExecutionSettings {
+            account: Cow::Borrowed(ACCOUNT),
+            sequencing,
+            fee_drops: 12,
+            current_ledger_index: 1_000,
+            max_ledger_offset: 4,
+        }
+    }
+
+    #[test]
+    fn test_consecutive_sequences() {
+        let plan =
+            ExecutionPlan::new(&xrp_swap_trade(), &settings(Sequencing::Sequence(7))).unwrap();
+        assert_eq!(plan.transactions.len(), 2);
+        assert_eq!(plan.last_ledger_sequence, 1_004);
+        assert_eq!(plan.get_total_fee_drops(), 24);
+        assert_eq!(plan.get_next_sequence(), Some(9));
+        for (planned, sequence) in plan.transactions.iter().zip([7, 8]) {
+            assert_eq!(planned.slot, SequenceSlot::Sequence(sequence));
+            let TradeTransaction::OfferCreate(offer_create) = &planned.transaction else {
+                panic!("Swaps starting with XRP are executed with offers.");
+            };
+            assert_eq!(offer_create.account, ACCOUNT);
+            assert_eq!(offer_create.sequence, Some(sequence));
+            assert_eq!(offer_create.ticket_sequence, None);
+            assert_eq!(offer_create.last_ledger_sequence, Some(1_004));
+            assert_eq!(
+                offer_create.fee.as_ref().map(|fee| fee.0.as_ref()),
+                Some("12")
+            );
+        }
+    }
+
+    #[test]
+    fn test_tickets() {
+        let plan = ExecutionPlan::new(
+            &usd_swap_trade(),
+            &settings(Sequencing::Tickets(vec![20, 21])),
+        )
+        .unwrap();
+        assert_eq!(plan.transactions.len(), 1);
+        assert_eq!(plan.get_next_sequence(), None);
+        let TradeTransaction::Payment(payment) = &plan.transactions[0].transaction else {
+            panic!("Swaps not starting with XRP are executed with one payment.");
+        };
+        assert_eq!(payment.sequence, Some(0));
+        assert_eq!(payment.ticket_sequence, Some(20));
+
+        let error = ExecutionPlan::new(&usd_swap_trade(), &settings(Sequencing::Tickets(vec![])))
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ExecutionException>(),
+            Some(ExecutionException::NotEnoughTickets {
+                transaction_count: 1,
+                ticket_count: 0
+            })
+        ));
+        // The second offer gives what the first one receives.
+        let error = ExecutionPlan::new(
+            &xrp_swap_trade(),
+            &settings(Sequencing::Tickets(vec![20, 21, 22])),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ExecutionException>(),
+            Some(ExecutionException::DependentTransactions {
+                transaction_count: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_overflows() {
+        let error = ExecutionPlan::new(
+            &xrp_swap_trade(),
+            &settings(Sequencing::Sequence(u32::MAX - 1)),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ExecutionException>(),
+            Some(ExecutionException::SequenceOverflow {
+                transaction_count: 2,
+                ..
+            })
+        ));
+        let plan = ExecutionPlan::new(
+            &xrp_swap_trade(),
+            &settings(Sequencing::Sequence(u32::MAX - 2)),
+        )
+        .unwrap();
+        assert_eq!(plan.get_next_sequence(), Some(u32::MAX));
+
+        let mut settings = settings(Sequencing::Sequence(7));
+        settings.current_ledger_index = u32::MAX - 2;
+        let error = ExecutionPlan::new(&xrp_swap_trade(), &settings).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ExecutionException>(),
+            Some(ExecutionException::LedgerIndexOverflow {
+                max_ledger_offset: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let plan =
+            ExecutionPlan::new(&xrp_swap_trade(), &settings(Sequencing::Sequence(7))).unwrap();
+        let usd = format!("USD.{GATEHUB}");
+        assert_eq!(
+            plan.get_dry_run(),
+            format!(
+                "1. OfferCreate (sequence 7, fee 12 drops): give 100 XRP for 30 {usd}\n\
+                 2. OfferCreate (sequence 8, fee 12 drops): give 30 {usd} for 126 XRP\n\
+                 Total fee 24 drops, valid until ledger 1004\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_unprofitable_trade() {
+        let trade = SwapTrade::new(
+            vec![level(
+                xrp(),
+                token("USD", GATEHUB),
+                100,
+                Decimal::new(20, 2),
+            )],
+            vec![level(token("USD", GATEHUB), xrp(), 100, Decimal::new(4, 0))],
+            xrp(),
+        )
+        .unwrap();
+        assert!(ExecutionPlan::new(&trade, &settings(Sequencing::Sequence(7))).is_err());
+    }
+}
@@ -0,0 +1,2 @@
+mod arbitrage;
+mod execution;